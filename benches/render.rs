@@ -0,0 +1,82 @@
+//!   Criterion benchmarks for the frame compute and paint passes.
+//!   Run with `cargo bench`.
+//!   @author Van Gouache
+
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use burning_ship_fractal::burning_ship_frac::{build_frame, get_orbit_rate, OrbitParams, MAX_ITERATIONS};
+use burning_ship_fractal::painter::{generate_random_palette, paint_frame, PaintOptions};
+
+const STARTING_X_RANGE : (f64, f64) = (-3.45, 0.05);
+const STARTING_Y_RANGE : (f64, f64) = (-0.99, 0.99);
+const ZOOM_RATE : f64 = 0.96;
+// a representative frame partway into the default zoom
+const MID_ZOOM_FRAME : u32 = 60;
+const RESOLUTIONS : [(usize, usize); 2] = [(400, 230), (1000, 575)];
+
+fn bench_build_frame(c : &mut Criterion) {
+    let mut group = c.benchmark_group("build_frame");
+    group.sample_size(10);
+    for (width, height) in RESOLUTIONS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}x{}", width, height)),
+            &(width, height),
+            |b, &(width, height)| b.iter(|| build_frame::<u8>(
+                width,
+                height,
+                STARTING_X_RANGE,
+                STARTING_Y_RANGE,
+                black_box(MID_ZOOM_FRAME),
+                ZOOM_RATE,
+                &OrbitParams::default()
+            ).unwrap())
+        );
+    }
+    group.finish();
+}
+
+fn bench_paint_frame(c : &mut Criterion) {
+    let palette = generate_random_palette(MAX_ITERATIONS);
+    let options = PaintOptions::default();
+    let mut group = c.benchmark_group("paint_frame");
+    for (width, height) in RESOLUTIONS {
+        let frame = build_frame::<u8>(
+            width,
+            height,
+            STARTING_X_RANGE,
+            STARTING_Y_RANGE,
+            MID_ZOOM_FRAME,
+            ZOOM_RATE,
+            &OrbitParams::default()
+        ).unwrap();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}x{}", width, height)),
+            &frame,
+            |b, frame| b.iter(|| paint_frame(
+                width as u32,
+                height as u32,
+                frame,
+                &palette,
+                &options,
+                0
+            ))
+        );
+    }
+    group.finish();
+}
+
+fn bench_get_orbit_rate(c : &mut Criterion) {
+    let mut group = c.benchmark_group("get_orbit_rate");
+    // inside the main body, runs all MAX_ITERATIONS
+    group.bench_function("interior", |b| b.iter(|| {
+        get_orbit_rate(0, 0, 0.0, 0.0, black_box(-0.5), black_box(-0.5), &OrbitParams::default())
+    }));
+    // escapes after a couple of iterations
+    group.bench_function("escaping", |b| b.iter(|| {
+        get_orbit_rate(0, 0, 0.0, 0.0, black_box(-0.5), black_box(0.5), &OrbitParams::default())
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, bench_build_frame, bench_paint_frame, bench_get_orbit_rate);
+criterion_main!(benches);
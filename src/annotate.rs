@@ -0,0 +1,257 @@
+//!   Module contains an overlay annotating a painted frame
+//!   with the complex-plane coordinates of its viewport, tick
+//!   marks with labels along the bottom and left edges and a
+//!   scale bar, and a frame number stamp. Text uses a tiny
+//!   embedded 3x5 bitmap font.
+//!   @author Van Gouache
+
+use image::{ImageBuffer, Rgb};
+use crate::burning_ship_frac::{calc_step_size, frame_ranges, Range};
+use crate::config::RenderConfig;
+use crate::painter::PaintOptions;
+
+//labelled ticks per axis
+const TICKS : u32 = 4;
+const GLYPH_WIDTH : u32 = 3;
+const GLYPH_HEIGHT : u32 = 5;
+const TEXT_COLOR : Rgb<u8> = Rgb([255, 255, 255]);
+const BACKGROUND : Rgb<u8> = Rgb([0, 0, 0]);
+//length and center gap of the crosshair arms in pixels at scale 1
+const CROSSHAIR_ARM : i64 = 12;
+const CROSSHAIR_GAP : i64 = 3;
+
+
+///    ### (PURE)
+///    Rows of the 3x5 glyph of c, bit 2 is the leftmost column. Letters
+///    other than e and x have uppercase glyphs only. Characters without a
+///    glyph are blank.
+fn glyph(c : char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        'e' => [0b000, 0b111, 0b111, 0b100, 0b111],
+        'x' => [0b000, 0b101, 0b010, 0b101, 0b000],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        _ => [0; 5],
+    }
+}
+
+
+///    ### (I/O)
+///    Fills the rectangle at (x, y), clipped to the buffer.
+fn fill_rect(buffer : &mut ImageBuffer<Rgb<u8>, Vec<u8>>, x : u32, y : u32, width : u32, height : u32, color : Rgb<u8>) {
+    let x_end = (x + width).min(buffer.width());
+    let y_end = (y + height).min(buffer.height());
+    for py in y.min(y_end)..y_end {
+        for px in x.min(x_end)..x_end {
+            buffer.put_pixel(px, py, color);
+        }
+    }
+}
+
+
+///    ### (PURE)
+///    Pixel width and height of text drawn at scale.
+pub fn text_size(text : &str, scale : u32) -> (u32, u32) {
+    let chars = text.chars().count() as u32;
+    ((chars * (GLYPH_WIDTH + 1)).saturating_sub(1) * scale, GLYPH_HEIGHT * scale)
+}
+
+
+///    ### (I/O)
+///    Draws text with its top left corner at (x, y) over a background
+///    box padded by one glyph pixel, each glyph pixel scale x scale.
+pub fn draw_text(buffer : &mut ImageBuffer<Rgb<u8>, Vec<u8>>, x : u32, y : u32, text : &str, scale : u32) {
+    let (width, height) = text_size(text, scale);
+    fill_rect(buffer, x.saturating_sub(scale), y.saturating_sub(scale), width + 2 * scale, height + 2 * scale, BACKGROUND);
+    for (index, c) in text.chars().enumerate() {
+        let glyph_x = x + index as u32 * (GLYPH_WIDTH + 1) * scale;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                    fill_rect(buffer, glyph_x + col * scale, y + row as u32 * scale, scale, scale, TEXT_COLOR);
+                }
+            }
+        }
+    }
+}
+
+
+///    ### (PURE)
+///    Decimal places needed to tell apart labels spacing apart.
+fn label_decimals(spacing : f64) -> usize {
+    (1.0 - spacing.log10().floor()).clamp(0.0, 17.0) as usize
+}
+
+
+///    ### (PURE)
+///    Largest 1, 2 or 5 times a power of ten not above target.
+fn nice_length(target : f64) -> f64 {
+    let magnitude = 10f64.powf(target.log10().floor());
+    [5.0, 2.0, 1.0]
+    .iter()
+    .map(|step| step * magnitude)
+    .find(|length| *length <= target)
+    .unwrap_or(magnitude)
+}
+
+
+///    ### (I/O)
+///    Draws TICKS - 1 labelled tick marks along the bottom (x) and left (y)
+///    edges of buffer for the viewport x_range by y_range, and a scale bar
+///    in the bottom right corner sized from the pixel step. With flip_y
+///    the y labels run down from y_range.1, see OrbitParams::flip_y.
+pub fn annotate(buffer : &mut ImageBuffer<Rgb<u8>, Vec<u8>>, x_range : Range, y_range : Range, flip_y : bool) {
+    let (width, height) = buffer.dimensions();
+    if width == 0 || height == 0 {
+        return;
+    }
+    let scale = (height / 400).max(1);
+    let tick_length = 4 * scale;
+    let (x_step_size, y_step_size) = calc_step_size(width as usize, height as usize, x_range, y_range);
+    let x_decimals = label_decimals(x_step_size * (width / TICKS) as f64);
+    let y_decimals = label_decimals(y_step_size * (height / TICKS) as f64);
+
+    for tick in 1..TICKS {
+        let px = width * tick / TICKS;
+        fill_rect(buffer, px, height - tick_length, scale, tick_length, TEXT_COLOR);
+        let label = format!("{:.*}", x_decimals, x_range.0 + px as f64 * x_step_size);
+        let (label_width, label_height) = text_size(&label, scale);
+        draw_text(
+            buffer,
+            px.saturating_sub(label_width / 2),
+            height.saturating_sub(tick_length + label_height + 2 * scale),
+            &label,
+            scale
+        );
+
+        let py = height * tick / TICKS;
+        fill_rect(buffer, 0, py, tick_length, scale, TEXT_COLOR);
+        let row = if flip_y { (height - 1 - py) as f64 } else { py as f64 };
+        let label = format!("{:.*}", y_decimals, y_range.0 + row * y_step_size);
+        let (_, label_height) = text_size(&label, scale);
+        draw_text(buffer, tick_length + 2 * scale, py.saturating_sub(label_height / 2), &label, scale);
+    }
+
+    let bar_length = nice_length(x_step_size * width as f64 / 5.0);
+    let bar_pixels = (bar_length / x_step_size).round() as u32;
+    let margin = 8 * scale;
+    let bar_x = width.saturating_sub(margin + bar_pixels);
+    let bar_y = height.saturating_sub(margin + tick_length + GLYPH_HEIGHT * scale * 3);
+    fill_rect(buffer, bar_x, bar_y, bar_pixels, scale * 2, TEXT_COLOR);
+    let label = format!("{:e}", bar_length);
+    let (label_width, _) = text_size(&label, scale);
+    draw_text(
+        buffer,
+        bar_x + bar_pixels.saturating_sub(label_width) / 2,
+        bar_y.saturating_sub((GLYPH_HEIGHT + 2) * scale),
+        &label,
+        scale
+    );
+}
+
+
+///    ### (I/O)
+///    Draws frame_number, followed by the magnification as "x4.50e2"
+///    when given, in the top left corner of buffer.
+pub fn stamp_frame(buffer : &mut ImageBuffer<Rgb<u8>, Vec<u8>>, frame_number : u32, magnification : Option<f64>) {
+    let scale = (buffer.height() / 400).max(1);
+    let label = match magnification {
+        Some(magnification) => format!("{} x{:.2e}", frame_number, magnification),
+        None => frame_number.to_string(),
+    };
+    draw_text(buffer, 2 * scale, 2 * scale, &label, scale);
+}
+
+
+///    ### (I/O)
+///    Overlays the viewport coordinates of frame_number of config on
+///    buffer when options.annotate is set, and the frame number and
+///    magnification when options.stamp is set.
+pub fn decorate_frame(
+    buffer : &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+    config : &RenderConfig,
+    frame_number : u32,
+    options : &PaintOptions
+) {
+    if options.annotate {
+        let (x_range, y_range) = frame_ranges(config, frame_number);
+        let origin = config.frame_origin();
+        let shift = |range : Range, by : f64| (range.0 + by, range.1 + by);
+        annotate(buffer, shift(x_range, origin.0), shift(y_range, origin.1), config.flip_y);
+    }
+    if options.stamp {
+        stamp_frame(buffer, frame_number, Some(config.magnification(frame_number)));
+    }
+}
+
+
+///    ### (I/O)
+///    Draws a crosshair centered on the (fractional) pixel position
+///    point, white on a black outline so it shows on any palette. The
+///    arms leave a gap so the targeted pixel itself stays visible. Nothing
+///    is drawn when point is outside buffer.
+pub fn draw_crosshair(buffer : &mut ImageBuffer<Rgb<u8>, Vec<u8>>, point : (f64, f64)) {
+    let (width, height) = buffer.dimensions();
+    if !(point.0 >= 0.0 && point.0 < width as f64 && point.1 >= 0.0 && point.1 < height as f64) {
+        return;
+    }
+    let scale = (height / 400).max(1) as i64;
+    let (x, y) = (point.0 as i64, point.1 as i64);
+    let (arm, gap) = (CROSSHAIR_ARM * scale, CROSSHAIR_GAP * scale);
+    // (x, y, width, height) of the left, right, top and bottom arms
+    let arms = [
+        (x - gap - arm, y, arm, 1),
+        (x + gap + 1, y, arm, 1),
+        (x, y - gap - arm, 1, arm),
+        (x, y + gap + 1, 1, arm),
+    ];
+    for (border, color) in [(scale, BACKGROUND), (0, TEXT_COLOR)] {
+        for (arm_x, arm_y, arm_width, arm_height) in arms {
+            let (left, top) = (arm_x - border - scale / 2, arm_y - border - scale / 2);
+            let (right, bottom) = (left + arm_width + scale - 1 + 2 * border, top + arm_height + scale - 1 + 2 * border);
+            let (left, top) = (left.max(0), top.max(0));
+            if right > left && bottom > top {
+                fill_rect(buffer, left as u32, top as u32, (right - left) as u32, (bottom - top) as u32, color);
+            }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//!   Module contains an adaptive anti-aliasing pass. Frames are
+//!   rendered with one sample per pixel, then only the edge pixels,
+//!   whose escape value jumps against a neighbour, are sampled again
+//!   at subpixel offsets and combined by a reconstruction filter.
+//!   Flat regions cost nothing extra.
+//!   @author Van Gouache
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use crate::parallel::*;
+use crate::burning_ship_frac::{
+    calc_step_size, frame_ranges, get_orbit_rate, render_escape_grid,
+    EscapeValue, Fractal, OrbitParams, Range, RenderError,
+};
+use crate::config::RenderConfig;
+
+//escape values differing by more than this from a neighbour mark an edge
+pub const EDGE_THRESHOLD : f64 = 1.0;
+//subpixel sample positions of an edge pixel, in pixels from its sample point
+const SUBPIXEL_OFFSETS : [(f64, f64); 4] = [(-0.25, -0.25), (0.25, -0.25), (-0.25, 0.25), (0.25, 0.25)];
+//subpixel sample positions along each axis of the weighted filters, reaching
+//half a pixel into the neighbours
+const FILTER_OFFSETS : [f64; 4] = [-0.75, -0.25, 0.25, 0.75];
+//largest shift of a jittered sample along each axis in pixels, half the
+//spacing of the offsets so jittered samples keep to their own cell
+const JITTER_SPREAD : f64 = 0.25;
+//standard deviation of the Gaussian filter in pixels
+const GAUSSIAN_SIGMA : f64 = 0.4;
+//names accepted by ReconstructionFilter::from_name
+pub const FILTER_NAMES : [&str; 3] = ["box", "tent", "gaussian"];
+
+
+///    How the subpixel samples of an edge pixel are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ReconstructionFilter {
+    ///    Plain mean of the SUBPIXEL_OFFSETS samples inside the pixel.
+    #[default]
+    Box,
+    ///    Samples up to a pixel from the center weighted by
+    ///    (1 - |dx|)(1 - |dy|), sharper than Box.
+    Tent,
+    ///    Samples up to a pixel from the center weighted by a Gaussian of
+    ///    GAUSSIAN_SIGMA, the smoothest falloff.
+    Gaussian,
+}
+
+impl ReconstructionFilter {
+    ///    ### (PURE)
+    ///    Filter called name, one of FILTER_NAMES.
+    pub fn from_name(name : &str) -> Option<Self> {
+        match name {
+            "box" => Some(ReconstructionFilter::Box),
+            "tent" => Some(ReconstructionFilter::Tent),
+            "gaussian" => Some(ReconstructionFilter::Gaussian),
+            _ => None,
+        }
+    }
+
+    ///    ### (PURE)
+    ///    Subpixel sample positions, in pixels from the sample point.
+    pub fn offsets(&self) -> Vec<(f64, f64)> {
+        match self {
+            ReconstructionFilter::Box => SUBPIXEL_OFFSETS.to_vec(),
+            ReconstructionFilter::Tent | ReconstructionFilter::Gaussian => FILTER_OFFSETS
+                .iter()
+                .flat_map(|dy| FILTER_OFFSETS.iter().map(move |dx| (*dx, *dy)))
+                .collect(),
+        }
+    }
+
+    ///    ### (PURE)
+    ///    Unnormalised weight of the sample at offset (dx, dy).
+    pub fn weight(&self, (dx, dy) : (f64, f64)) -> f64 {
+        match self {
+            ReconstructionFilter::Box => 1.0,
+            ReconstructionFilter::Tent => (1.0 - dx.abs()).max(0.0) * (1.0 - dy.abs()).max(0.0),
+            ReconstructionFilter::Gaussian => (-(dx * dx + dy * dy) / (2.0 * GAUSSIAN_SIGMA * GAUSSIAN_SIGMA)).exp(),
+        }
+    }
+}
+
+
+///    ### (PURE)
+///    Subpixel sample positions of pixel (x, y) under filter. With a
+///    jitter seed every offset is shifted by up to JITTER_SPREAD along
+///    each axis, drawn from a generator seeded by the seed and the pixel,
+///    so the same seed always places the same samples.
+pub fn sample_offsets(filter : ReconstructionFilter, jitter : Option<u64>, x : usize, y : usize) -> Vec<(f64, f64)> {
+    let offsets = filter.offsets();
+    let Some(seed) = jitter else {
+        return offsets;
+    };
+    let mut rng = StdRng::seed_from_u64(seed ^ ((y as u64) << 32 | x as u64));
+    offsets.iter().map(|(dx, dy)|{
+        (dx + rng.gen_range(-JITTER_SPREAD..JITTER_SPREAD), dy + rng.gen_range(-JITTER_SPREAD..JITTER_SPREAD))
+    }).collect()
+}
+
+
+///    ### (PURE)
+///    True if the cell (x, y) of frame differs from one of its four
+///    neighbours by more than threshold.
+fn is_edge<T : EscapeValue>(frame : &[Vec<T>], x : usize, y : usize, threshold : f64) -> bool {
+    let value = frame[y][x].escape_value();
+    let neighbours = [
+        (x.checked_sub(1), Some(y)),
+        (Some(x + 1), Some(y)),
+        (Some(x), y.checked_sub(1)),
+        (Some(x), Some(y + 1)),
+    ];
+    neighbours.iter().any(|neighbour| match *neighbour {
+        (Some(nx), Some(ny)) => frame
+            .get(ny)
+            .and_then(|row| row.get(nx))
+            .is_some_and(|cell| (cell.escape_value() - value).abs() > threshold),
+        _ => false,
+    })
+}
+
+
+///    ### (PURE)
+///    Orbit rate of pixel (x, y) averaged over its sample_offsets, each
+///    sample weighted by filter.weight.
+fn supersample(
+    x : usize,
+    y : usize,
+    (x_step_size, y_step_size) : (f64, f64),
+    (x_floor, y_floor) : (f64, f64),
+    params : &OrbitParams,
+    (filter, jitter) : (ReconstructionFilter, Option<u64>)
+) -> f64
+{
+    let (total, total_weight) = sample_offsets(filter, jitter, x, y).iter().fold((0.0, 0.0), |(total, total_weight), (dx, dy)|{
+        let weight = filter.weight((*dx, *dy));
+        let rate = get_orbit_rate(
+            x,
+            y,
+            x_step_size,
+            y_step_size,
+            x_floor + dx * x_step_size,
+            y_floor + dy * y_step_size,
+            params
+        );
+        (total + weight * rate as f64, total_weight + weight)
+    });
+    total / total_weight
+}
+
+
+///    ### (PURE)
+///    Anti-aliases the edges of frame, the single sample render of the
+///    viewport x_range by y_range. Cells flagged by is_edge are replaced
+///    by their supersample through filter, jittered by the seed jitter
+///    when given, every other cell keeps its escape value.
+pub fn antialias_edges<T : EscapeValue>(
+    frame : &[Vec<T>],
+    x_range : Range,
+    y_range : Range,
+    params : &OrbitParams,
+    threshold : f64,
+    (filter, jitter) : (ReconstructionFilter, Option<u64>)
+) -> Fractal<f64>
+{
+    let img_height = frame.len();
+    let img_width = frame.first().map_or(0, |row| row.len());
+    let (x_step_size, y_step_size) = calc_step_size(img_width, img_height, x_range, y_range);
+    (0..img_height).into_par_iter().map(|y|{
+        (0..img_width).map(|x|{
+            match is_edge(frame, x, y, threshold) {
+                true => supersample(x, y, (x_step_size, y_step_size), (x_range.0, y_range.0), params, (filter, jitter)),
+                false => frame[y][x].escape_value(),
+            }
+        }).collect()
+    }).collect()
+}
+
+
+///    ### (PURE)
+///    Composes render_escape_grid -> antialias_edges for frame_number
+///    of config with EDGE_THRESHOLD, filter and the jitter seed.
+pub fn render_edge_aa_grid(
+    config : &RenderConfig,
+    frame_number : u32,
+    filter : ReconstructionFilter,
+    jitter : Option<u64>
+) -> Result<Fractal<f64>, RenderError>
+{
+    let frame : Fractal<u32> = render_escape_grid(config, frame_number)?;
+    let (x_range, y_range) = frame_ranges(config, frame_number);
+    // ⬇
+    Ok(antialias_edges(&frame, x_range, y_range, &config.orbit_params(frame_number), EDGE_THRESHOLD, (filter, jitter)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jittered_aa_samples_are_seeded_and_stay_near_the_grid() {
+        let filter = ReconstructionFilter::Tent;
+        let grid = sample_offsets(filter, None, 3, 7);
+        assert_eq!(grid, filter.offsets());
+        let jittered = sample_offsets(filter, Some(42), 3, 7);
+        assert_eq!(jittered, sample_offsets(filter, Some(42), 3, 7));
+        assert_ne!(jittered, sample_offsets(filter, Some(42), 4, 7));
+        assert_ne!(jittered, sample_offsets(filter, Some(43), 3, 7));
+        for ((gx, gy), (jx, jy)) in grid.iter().zip(&jittered) {
+            assert!((gx - jx).abs() <= 0.25 && (gy - jy).abs() <= 0.25);
+        }
+    }
+}
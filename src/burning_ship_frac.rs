@@ -6,99 +6,740 @@
 //!   Z[n+1] = (|Re(Z[n])| + |Im(Z[n])|i)^2 + C
 //!   @author Van Gouache
 
+use num_complex::{Complex, Complex64};
+use num_traits::Float;
+use crate::parallel::*;
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::config::RenderConfig;
+use crate::double_double::DoubleDouble;
 
+//default max length of burning_ship sequence 
+pub const MAX_ITERATIONS : u32 = 100;
+//coefficients (k_a, k_b) of the canonical burning ship map
+pub const DEFAULT_COEFFICIENTS : (f64, f64) = (1.0, 1.0);
+//radius an orbit must leave to count as escaped
+pub const ESCAPE_RADIUS : f64 = 2.0;
+//larger escape radius of smooth orbit rates, see get_smooth_orbit_rate
+pub const SMOOTH_BAILOUT : f64 = 256.0;
+//names of the BuiltinMap kernels, as accepted by --blend-map
+pub const MAP_NAMES : [&str; 3] = ["burning-ship", "mandelbrot", "tricorn"];
 
-//max length of burning_ship sequence 
-pub const MAX_ITERATIONS : u8 = 100;
-//prints debug logs if true
-const DEBUG_MODULE : bool = false;
+///    Escape-time grid, row-major with row 0 at the top of the image.
+///    Defaults to u8 cells, see EscapeCount for the wider variants.
+pub type Fractal<T = u8> = Vec<Vec<T>>;
+pub type Range = (f64, f64);
+//pixel step sizes, x and y ranges and orbit params of one frame
+type FrameViewport = ((f64, f64), (Range, Range), OrbitParams);
 
-pub type Fractal = Vec<Vec<u8>>;
-type Range = (f64, f64);
 
-#[derive(Debug)]
-struct ComplexNumber{   
-    a : f64,
-    b : f64,
+///    Integer type an escape grid stores its orbit rates in.
+///    A u8 grid costs 1 byte per pixel (9.2 MB for a 4000x2300 frame) but
+///    only holds max_iterations up to 255; u16 and u32 cost 2 and 4 bytes
+///    per pixel (18.4 MB and 36.8 MB) for deeper renders.
+pub trait EscapeCount : EscapeValue + Default + Into<u32> {
+    ///    Largest orbit rate the type can hold.
+    const MAX_COUNT : u32;
+
+    ///    Converts an orbit rate, saturating at MAX_COUNT.
+    fn from_count(count : u32) -> Self;
+}
+
+impl EscapeCount for u8 {
+    const MAX_COUNT : u32 = u8::MAX as u32;
+
+    fn from_count(count : u32) -> Self {
+        count.min(Self::MAX_COUNT) as u8
+    }
+}
+
+impl EscapeCount for u16 {
+    const MAX_COUNT : u32 = u16::MAX as u32;
+
+    fn from_count(count : u32) -> Self {
+        count.min(Self::MAX_COUNT) as u16
+    }
+}
+
+impl EscapeCount for u32 {
+    const MAX_COUNT : u32 = u32::MAX;
+
+    fn from_count(count : u32) -> Self {
+        count
+    }
+}
+
+
+///    Cell of a tensor grid from render_tensor_grid: the orbit rate and
+///    smooth escape value of one orbit, and the max_iterations it was
+///    iterated up to. Cells equal to max_iterations are interior.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EscapeSample {
+    pub iterations : u32,
+    pub smooth : f64,
+    pub max_iterations : u32,
+}
+
+impl EscapeSample {
+    ///    ### (PURE)
+    ///    Channels of the cell normalized by max_iterations: orbit rate
+    ///    and smooth escape value \[0 to 1\], and 1.0 for interior cells
+    ///    or 0.0 for escaping ones.
+    pub fn channels(self) -> [f32; 3] {
+        let max = self.max_iterations.max(1) as f64;
+        let interior = self.iterations >= self.max_iterations;
+        [(self.iterations as f64 / max) as f32, (self.smooth / max) as f32, interior as u8 as f32]
+    }
+}
+
+
+///    Cell of a grid the painter can color, either an integer orbit rate
+///    or a smooth (fractional) escape value from render_smooth_grid.
+pub trait EscapeValue : Copy + Send + Sync {
+    ///    Escape value of the cell, the integer part is the orbit rate.
+    fn escape_value(self) -> f64;
+}
+
+impl EscapeValue for u8 {
+    fn escape_value(self) -> f64 {
+        self as f64
+    }
+}
+
+impl EscapeValue for u16 {
+    fn escape_value(self) -> f64 {
+        self as f64
+    }
+}
+
+impl EscapeValue for u32 {
+    fn escape_value(self) -> f64 {
+        self as f64
+    }
+}
+
+impl EscapeValue for f64 {
+    fn escape_value(self) -> f64 {
+        self
+    }
+}
+
+impl EscapeValue for EscapeSample {
+    fn escape_value(self) -> f64 {
+        self.smooth
+    }
+}
+
+///    Escape-time map a frame iterates.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum FractalKind {
+    ///    C is the pixel coordinate, as is Z\[0\] unless OrbitParams::z0
+    ///    seeds it.
+    #[default]
+    BurningShip,
+    ///    Burning ship Julia set, C is fixed at c and Z\[0\] is the pixel.
+    Julia { c : (f64, f64) },
+}
+
+
+///    Float type orbits are iterated in. Pixel coordinates are always
+///    computed in f64 and rounded to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Precision {
+    #[default]
+    F64,
+    ///    Halves the width of the iteration, enough for shallow zooms.
+    ///    Neighbouring pixels round to the same f32 point once the pixel
+    ///    step falls below about 1e-7 of the viewport, so deep zooms
+    ///    turn blocky well before the f64 limit.
+    F32,
+    ///    double_double::DoubleDouble, about 32 digits at roughly 7 times
+    ///    the cost of F64. Frames are laid out about the zoom pivot
+    ///    (see frame_ranges and OrbitParams::origin) so pixel steps stay
+    ///    exact, which lets zooms run to steps near 1e-30 of the
+    ///    coordinates instead of 1e-16.
+    DoubleDouble,
+}
+
+
+///    Float types the escape loop can iterate in: f32, f64 and
+///    double_double::DoubleDouble.
+pub trait OrbitFloat : Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {
+    fn abs(self) -> Self;
+    fn from_f64(value : f64) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+impl<F : Float> OrbitFloat for F {
+    fn abs(self) -> Self {
+        Float::abs(self)
+    }
+
+    fn from_f64(value : f64) -> Self {
+        F::from(value).unwrap()
+    }
+
+    fn to_f64(self) -> f64 {
+        num_traits::ToPrimitive::to_f64(&self).unwrap()
+    }
+}
+
+
+///    Iteration parameters shared by every pixel of a frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitParams {
+    pub max_iterations : u32,
+    ///    Iterations after which an orbit that has not escaped is called
+    ///    interior and stops early, for fast previews. Interior pixels
+    ///    still return max_iterations, so coloring keeps its scale. None
+    ///    iterates up to max_iterations, see iteration_cap.
+    pub interior_threshold : Option<u32>,
+    pub kind : FractalKind,
+    ///    Seeds Z\[0\] of BurningShip orbits independently of C, None
+    ///    starts at the pixel. Ignored by Julia, where Z\[0\] is the pixel.
+    pub z0 : Option<(f64, f64)>,
+    ///    Compute only half the columns of frames that are exactly mirror
+    ///    symmetric and mirror the rest, see mirror_symmetric.
+    pub exploit_symmetry : bool,
+    ///    Angle in radians the sampling grid is turned by about pivot,
+    ///    counter-clockwise in the complex plane. 0.0 is axis aligned.
+    pub rotation : f64,
+    ///    Complex coordinate the sampling grid is rotated about.
+    pub pivot : (f64, f64),
+    ///    Complex coordinate pixel coordinates are offsets from, added to
+    ///    them in the float type of precision. (0.0, 0.0) except for
+    ///    DoubleDouble frames, which are laid out about the zoom pivot.
+    pub origin : (f64, f64),
+    ///    Coefficients (k_a, k_b) of the generalized map, see
+    ///    calculate_next_z. DEFAULT_COEFFICIENTS is the burning ship.
+    pub coefficients : (f64, f64),
+    ///    Float type of the escape loop, see Precision.
+    pub precision : Precision,
+    ///    Height in rows of a frame drawn with +i pointing up, row y
+    ///    sampling what row height - 1 - y would otherwise. None keeps
+    ///    image orientation, row 0 at the low imaginary edge.
+    pub flip_y : Option<usize>,
+}
+
+impl OrbitParams {
+    ///    ### (PURE)
+    ///    Row of the unflipped grid sampled by row position y, see
+    ///    flip_y. Its own inverse.
+    pub fn row_position(&self, y : f64) -> f64 {
+        self.flip_y.map_or(y, |height| (height as f64 - 1.0) - y)
+    }
+
+
+    ///    ### (PURE)
+    ///    Iterations an orbit runs for at most, interior_threshold when
+    ///    it is below max_iterations.
+    pub fn iteration_cap(&self) -> u32 {
+        self.interior_threshold.map_or(self.max_iterations, |threshold| threshold.min(self.max_iterations))
+    }
+
+    ///    ### (PURE)
+    ///    Orbit rate of an orbit stopped after iterations, max_iterations
+    ///    for orbits that reached iteration_cap without escaping.
+    pub fn interior_clamp(&self, iterations : u32) -> u32 {
+        if iterations >= self.iteration_cap() { self.max_iterations } else { iterations }
+    }
+
+    ///    ### (PURE)
+    ///    Kernel of the built in map, the burning ship with coefficients.
+    pub fn kernel(&self) -> BurningShipKernel {
+        BurningShipKernel { coefficients : self.coefficients }
+    }
+}
+
+impl Default for OrbitParams {
+    fn default() -> Self {
+        OrbitParams {
+            max_iterations : MAX_ITERATIONS,
+            interior_threshold : None,
+            kind : FractalKind::default(),
+            z0 : None,
+            exploit_symmetry : false,
+            rotation : 0.0,
+            pivot : (0.0, 0.0),
+            origin : (0.0, 0.0),
+            coefficients : DEFAULT_COEFFICIENTS,
+            precision : Precision::default(),
+            flip_y : None,
+        }
+    }
+}
+
+
+///    Reasons a frame cannot be rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderError {
+    ///    The image has no pixels.
+    EmptyImage { width : usize, height : usize },
+    ///    A viewport bound is NaN or infinite.
+    NonFiniteViewport { x_range : Range, y_range : Range },
+    ///    A viewport range does not run from a lower to a higher bound.
+    EmptyViewport { x_range : Range, y_range : Range },
+    ///    The viewport is so narrow that a pixel step underflows to zero.
+    DegenerateStep { x_step_size : f64, y_step_size : f64 },
 }
 
-// sqrt shorthand
-fn sqr(x : f64) -> f64{
-    x * x
+impl fmt::Display for RenderError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenderError::EmptyImage { width, height } => write!(
+                f,
+                "image must have at least one pixel, got {}x{}",
+                width,
+                height
+            ),
+            RenderError::NonFiniteViewport { x_range, y_range } => write!(
+                f,
+                "viewport bounds must be finite, got x {:?} y {:?}",
+                x_range,
+                y_range
+            ),
+            RenderError::EmptyViewport { x_range, y_range } => write!(
+                f,
+                "viewport ranges must run from low to high, got x {:?} y {:?}",
+                x_range,
+                y_range
+            ),
+            RenderError::DegenerateStep { x_step_size, y_step_size } => write!(
+                f,
+                "pixel steps must be positive, got {} by {}",
+                x_step_size,
+                y_step_size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+
+///    ### (PURE)
+///    Folds Z into the first quadrant, (|Re(Z)| + |Im(Z)|i). The only
+///    thing separating the burning ship step from the Mandelbrot one.
+fn abs_components<F : OrbitFloat>(z : Complex<F>) -> Complex<F> {
+    Complex::new(z.re.abs(), z.im.abs())
 }
 
 
 ///    ### (PURE)
 ///    Calcualtes Z\[n+1\] in burning frac func
 ///    Z\[n+1\] = (|Re(Z\[n\])| + |Im(Z\[n\])|i)^2 + C
-///    where Z\[0\] = 0 and C = a + bi where 
-///    a = (x pixel coordinate) and b = (y pixel coordinate)
-fn calculate_next_z(constant : &ComplexNumber, prev :&ComplexNumber) -> ComplexNumber{
-    let sqr_a = sqr(prev.a);
-    let sqr_b = sqr(prev.b);
-
-    if sqr_a.is_infinite() || sqr_b.is_infinite(){
-        return ComplexNumber{
-            a : f64::INFINITY,
-            b : f64::INFINITY
-        };
+///    The squared real part is (Re^2 - Im^2) and the imaginary part
+///    2|Re||Im|, bit for bit the same as |2 Re Im|. The generalized map
+///    scales them by coefficients (k_a, k_b), giving
+///    k_a (Re^2 - Im^2) and |2 k_b Re Im|. Multiplying by the
+///    DEFAULT_COEFFICIENTS of 1.0 is exact, so they leave the ship as is.
+///    Written out per component, in the order Complex multiplies, so
+///    float types without a Complex implementation can use it.
+fn calculate_next_z<F : OrbitFloat>(constant : Complex<F>, prev : Complex<F>, coefficients : (F, F)) -> Complex<F> {
+    let folded = abs_components(prev);
+    let squared = Complex::new(
+        folded.re * folded.re - folded.im * folded.im,
+        folded.re * folded.im + folded.im * folded.re
+    );
+    Complex::new(
+        coefficients.0 * squared.re + constant.re,
+        (coefficients.1 * squared.im).abs() + constant.im
+    )
+}
+
+
+///    ### (PURE)
+///  Predicate to determine if burning_ship sequence is still in orbit,
+///  within bailout_radius of the origin.
+///  Infinite and NaN orbits compare false and count as escaped.
+fn orbit_contained<F : OrbitFloat>(z : Complex<F>, bailout_radius : F) -> bool {
+    z.re * z.re + z.im * z.im < bailout_radius * bailout_radius
+}
+
+
+///    Escape-time map orbits are iterated with, Z\[n+1\] = next(Z\[n\], C)
+///    until escaped. Methods are generic over OrbitFloat so a kernel runs
+///    at every Precision. Implement it to render a map of your own through
+///    render_kernel_grid and paint it like any other frame.
+pub trait IterationKernel : Sync {
+    ///    Z\[n+1\] from Z\[n\] = z and the constant C = c.
+    fn next<F : OrbitFloat>(&self, z : Complex<F>, c : Complex<F>) -> Complex<F>;
+
+    ///    Whether z has left the disc of bailout_radius about the origin.
+    ///    Infinite and NaN orbits must count as escaped.
+    fn escaped<F : OrbitFloat>(&self, z : Complex<F>, bailout_radius : F) -> bool {
+        !orbit_contained(z, bailout_radius)
+    }
+}
+
+
+///    The generalized burning ship map of calculate_next_z, the map every
+///    built in render iterates with OrbitParams::coefficients.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BurningShipKernel {
+    pub coefficients : (f64, f64),
+}
+
+impl Default for BurningShipKernel {
+    fn default() -> Self {
+        BurningShipKernel { coefficients : DEFAULT_COEFFICIENTS }
+    }
+}
+
+impl IterationKernel for BurningShipKernel {
+    fn next<F : OrbitFloat>(&self, z : Complex<F>, c : Complex<F>) -> Complex<F> {
+        let coefficients = (F::from_f64(self.coefficients.0), F::from_f64(self.coefficients.1));
+        calculate_next_z(c, z, coefficients)
+    }
+}
+
+
+///    The Mandelbrot map Z\[n+1\] = Z\[n\]^2 + C, the burning ship
+///    without abs_components.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MandelbrotKernel;
+
+impl IterationKernel for MandelbrotKernel {
+    fn next<F : OrbitFloat>(&self, z : Complex<F>, c : Complex<F>) -> Complex<F> {
+        Complex::new(z.re * z.re - z.im * z.im + c.re, z.re * z.im + z.im * z.re + c.im)
+    }
+}
+
+
+///    The Tricorn (Mandelbar) map Z\[n+1\] = conj(Z\[n\])^2 + C.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TricornKernel;
+
+impl IterationKernel for TricornKernel {
+    fn next<F : OrbitFloat>(&self, z : Complex<F>, c : Complex<F>) -> Complex<F> {
+        Complex::new(z.re * z.re - z.im * z.im + c.re, c.im - (z.re * z.im + z.im * z.re))
     }
+}
+
 
-    let new_a = sqr_a - sqr_b + constant.a;
-    let new_b = (2.0 * prev.a * prev.b).abs() + constant.b;
+///    Built in kernel picked by name, for blending with the burning ship
+///    in render_blended_kernel_grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BuiltinMap {
+    ///    BurningShipKernel with OrbitParams::coefficients.
+    #[default]
+    BurningShip,
+    Mandelbrot,
+    Tricorn,
+}
 
-    
-    ComplexNumber { 
-        a: new_a, 
-        b: new_b 
+impl BuiltinMap {
+    ///    ### (PURE)
+    ///    Map called name, one of MAP_NAMES.
+    pub fn from_name(name : &str) -> Option<Self> {
+        match name {
+            "burning-ship" => Some(BuiltinMap::BurningShip),
+            "mandelbrot" => Some(BuiltinMap::Mandelbrot),
+            "tricorn" => Some(BuiltinMap::Tricorn),
+            _ => None,
+        }
+    }
+
+    ///    ### (PURE)
+    ///    render_blended_kernel_grid with the kernel of this map.
+    pub fn render_blended_grid(
+        &self,
+        config : &RenderConfig,
+        frame_number : u32,
+        weight : f64
+    ) -> Result<Fractal<f64>, RenderError>
+    {
+        match self {
+            BuiltinMap::BurningShip => {
+                render_blended_kernel_grid(config, frame_number, &config.orbit_params(frame_number).kernel(), weight)
+            },
+            BuiltinMap::Mandelbrot => render_blended_kernel_grid(config, frame_number, &MandelbrotKernel, weight),
+            BuiltinMap::Tricorn => render_blended_kernel_grid(config, frame_number, &TricornKernel, weight),
+        }
     }
 }
 
 
+
 ///    ### (PURE)
-///  Predicate to determine if burning_ship sequence is still in orbit.
-fn orbit_contained(z : &ComplexNumber) -> bool{
-    match z.a.is_infinite() || z.b.is_infinite(){
-        true => false,
-        false =>{
-            (sqr(z.a) + sqr(z.b)) < 4.0
-        } 
+///    Maps the (possibly fractional) pixel position (x, y) to its offset
+///    from params.origin, a_floor + x * x_step_size on the real axis and
+///    b_floor + y * y_step_size on the imaginary one, turned by
+///    params.rotation about params.pivot.
+fn pixel_offset(
+    x : f64,
+    y : f64,
+    x_step_size : f64,
+    y_step_size : f64,
+    a_floor : f64,
+    b_floor : f64,
+    params : &OrbitParams
+) -> Complex64
+{
+    let point = Complex64::new(a_floor + (x * x_step_size), b_floor + (params.row_position(y) * y_step_size));
+    if params.rotation == 0.0 {
+        return point;
     }
+    let pivot = Complex64::new(params.pivot.0, params.pivot.1);
+    pivot + (point - pivot) * Complex64::from_polar(1.0, params.rotation)
+}
+
+
+///    ### (PURE)
+///    Maps the (possibly fractional) pixel position (x, y) to its
+///    complex coordinate, pixel_offset plus params.origin, rounded to f64.
+pub fn pixel_coordinate(
+    x : f64,
+    y : f64,
+    x_step_size : f64,
+    y_step_size : f64,
+    a_floor : f64,
+    b_floor : f64,
+    params : &OrbitParams
+) -> Complex64
+{
+    let offset = pixel_offset(x, y, x_step_size, y_step_size, a_floor, b_floor, params);
+    offset + Complex64::new(params.origin.0, params.origin.1)
 }
 
 
+///    ### (PURE)
+///    Complex coordinate (a, b) of the pixel position (x, y) of the
+///    viewport x_range by y_range sampled with step, the steps from
+///    calc_step_size. The same mapping get_orbit_rate renders with, so a
+///    clicked pixel converts to the point drawn there.
+pub fn pixel_to_complex(
+    (x, y) : (f64, f64),
+    (x_range, y_range) : (Range, Range),
+    (x_step_size, y_step_size) : (f64, f64),
+    params : &OrbitParams
+) -> (f64, f64)
+{
+    let point = pixel_coordinate(x, y, x_step_size, y_step_size, x_range.0, y_range.0, params);
+    (point.re, point.im)
+}
+
 
 ///    ### (PURE)
-///    Calculates the orbit rate for a given pixel. \[0 to MAX_ITERATIONS\]
-fn get_orbit_rate(
+///    Inverse of pixel_to_complex: the (fractional) pixel position of
+///    the complex point (a, b), undoing params.origin and params.rotation
+///    about params.pivot. Points outside the viewport give positions outside
+///    the image.
+pub fn complex_to_pixel(
+    (a, b) : (f64, f64),
+    (x_range, y_range) : (Range, Range),
+    (x_step_size, y_step_size) : (f64, f64),
+    params : &OrbitParams
+) -> (f64, f64)
+{
+    let mut point = Complex64::new(a - params.origin.0, b - params.origin.1);
+    if params.rotation != 0.0 {
+        let pivot = Complex64::new(params.pivot.0, params.pivot.1);
+        point = pivot + (point - pivot) * Complex64::from_polar(1.0, -params.rotation);
+    }
+    ((point.re - x_range.0) / x_step_size, params.row_position((point.im - y_range.0) / y_step_size))
+}
+
+
+///    ### (PURE)
+///    Iterates the orbit of the pixel at offset pixel from params.origin
+///    with kernel until it leaves bailout_radius, returning the orbit rate
+///    \[0 to max_iterations\] and the last Z reached. Orbits still inside
+///    after params.iteration_cap() are interior. Runs in the float type of
+///    params.precision.
+fn iterate_orbit<K : IterationKernel>(
+    kernel : &K,
+    pixel : Complex64,
+    params : &OrbitParams,
+    bailout_radius : f64
+) -> (u32, Complex64)
+{
+    match params.precision {
+        Precision::F64 => iterate_orbit_in::<f64, K>(kernel, pixel, params, bailout_radius),
+        Precision::F32 => iterate_orbit_in::<f32, K>(kernel, pixel, params, bailout_radius),
+        Precision::DoubleDouble => iterate_orbit_in::<DoubleDouble, K>(kernel, pixel, params, bailout_radius),
+    }
+}
+
+
+///    ### (PURE)
+///    Constant C and seed Z\[0\] of the orbit of the pixel at offset
+///    pixel from params.origin, summed and rounded in F.
+fn orbit_start<F : OrbitFloat>(pixel : Complex64, params : &OrbitParams) -> (Complex<F>, Complex<F>) {
+    let to_complex = |(re, im) : (f64, f64)| Complex::new(F::from_f64(re), F::from_f64(im));
+    let origin = to_complex(params.origin);
+    let pixel = Complex::new(origin.re + F::from_f64(pixel.re), origin.im + F::from_f64(pixel.im));
+    match (params.kind, params.z0) {
+        (FractalKind::BurningShip, None) => (pixel, pixel),
+        (FractalKind::BurningShip, Some(z0)) => (pixel, to_complex(z0)),
+        (FractalKind::Julia { c }, _) => (to_complex(c), pixel),
+    }
+}
+
+
+///    ### (PURE)
+///    iterate_orbit with every value rounded to F before iterating.
+fn iterate_orbit_in<F : OrbitFloat, K : IterationKernel>(
+    kernel : &K,
+    pixel : Complex64,
+    params : &OrbitParams,
+    bailout_radius : f64
+) -> (u32, Complex64)
+{
+    let (constant, seed) = orbit_start::<F>(pixel, params);
+    let bailout_radius = F::from_f64(bailout_radius);
+    let mut i = 0;
+    let mut z = seed;
+    let cap = params.iteration_cap();
+    while i < cap && !kernel.escaped(z, bailout_radius) {
+        z = kernel.next(z, constant);
+        i += 1;
+    }
+    (params.interior_clamp(i), Complex64::new(z.re.to_f64(), z.im.to_f64()))
+}
+
+
+///    ### (PURE)
+///    Orbit rate of the pixel at offset pixel from params.origin (see
+///    pixel_offset) under kernel. \[0 to max_iterations\]
+pub fn get_orbit_rate_with<K : IterationKernel>(kernel : &K, pixel : Complex64, params : &OrbitParams) -> u32 {
+    iterate_orbit(kernel, pixel, params, ESCAPE_RADIUS).0
+}
+
+
+///    ### (PURE)
+///    Calculates the orbit rate for a given pixel under params.kernel(),
+///    see get_orbit_rate_with. Orbits surviving params.interior_threshold
+///    iterations stop there as interior. \[0 to max_iterations\]
+pub fn get_orbit_rate(
     x : usize, 
     y: usize, 
     x_step_size: f64, 
     y_step_size : f64, 
     a_floor : f64, 
-    b_floor : f64
-) -> u8
+    b_floor : f64,
+    params : &OrbitParams
+) -> u32
 {
-    let starting_a = a_floor +  (x as f64 * x_step_size);
-    let starting_b = b_floor + (y as f64 * y_step_size);
-    let constant = ComplexNumber {
-        a : starting_a,
-        b : starting_b
-    };
-    let mut i = 0;
-    let mut z = ComplexNumber {
-        a : starting_a,
-        b : starting_b
-    };
-    while i < MAX_ITERATIONS && orbit_contained(&z) {
-        z = calculate_next_z(&constant, &z);
-        i = i + 1;
+    let pixel = pixel_offset(x as f64, y as f64, x_step_size, y_step_size, a_floor, b_floor, params);
+    get_orbit_rate_with(&params.kernel(), pixel, params)
+}
+
+
+///    ### (PURE)
+///    Records the orbit of the point pixel, every Z from Z\[0\] up to the
+///    first one outside ESCAPE_RADIUS, or params.max_iterations + 1
+///    values for interior points. Iterates in f64 whatever
+///    params.precision is. The number of steps taken is the orbit rate.
+pub fn trace_orbit(pixel : (f64, f64), params : &OrbitParams) -> Vec<(f64, f64)> {
+    let params = &OrbitParams { origin : (0.0, 0.0), ..*params };
+    let (constant, seed) = orbit_start::<f64>(Complex64::new(pixel.0, pixel.1), params);
+    let mut z = seed;
+    let mut orbit = vec![(z.re, z.im)];
+    while orbit.len() <= params.max_iterations as usize && orbit_contained(z, ESCAPE_RADIUS) {
+        z = params.kernel().next(z, constant);
+        orbit.push((z.re, z.im));
+    }
+    orbit
+}
+
+
+///    ### (PURE)
+///    Smooth escape value i + 1 - log2(log2|Z\[i\]|) of the pixel at
+///    offset pixel from params.origin, iterated until it leaves
+///    bailout_radius. Interior pixels return max_iterations. Pixels
+///    escaping at i == 0 or 1 return 0.0, the value the formula is
+///    clamped to for every i below 3 anyway: their Z has not been squared
+///    often enough for the log-log estimate, and far out it overflows to
+///    inf or NaN, which would otherwise slip through the clamp.
+///    \[0 to max_iterations\]
+fn smooth_orbit_rate(pixel : Complex64, params : &OrbitParams, bailout_radius : f64) -> f64 {
+    let (i, z) = iterate_orbit(&params.kernel(), pixel, params, bailout_radius);
+    smooth_escape(i, z, params.max_iterations)
+}
+
+
+///    ### (PURE)
+///    Smooth escape value of an orbit that stopped at Z\[i\] = z, see
+///    smooth_orbit_rate.
+fn smooth_escape(i : u32, z : Complex64, max_iterations : u32) -> f64 {
+    let max = max_iterations as f64;
+    if i >= max_iterations {
+        return max;
     }
-    i
+    let log_log = z.norm().log2().log2();
+    if i <= 1 || !log_log.is_finite() {
+        return 0.0;
+    }
+    (i as f64 + 1.0 - log_log).clamp(0.0, max)
+}
+
+
+///    ### (PURE)
+///    Calculates the smooth escape value of a given pixel,
+///    i + 1 - log2(log2|Z\[i\]|). The log-log normalization assumes
+///    |Z\[i+1\]| = |Z\[i\]|^2, which C spoils close to the escape circle, so
+///    orbits are iterated out to SMOOTH_BAILOUT instead of 2. The value
+///    then runs continuously from i - 2 to i - 3 as the escaping |Z\[i\]|
+///    goes from 256 to 256^2. Interior pixels return max_iterations.
+///    \[0 to max_iterations\]
+pub fn get_smooth_orbit_rate(
+    x : usize,
+    y : usize,
+    x_step_size : f64,
+    y_step_size : f64,
+    a_floor : f64,
+    b_floor : f64,
+    params : &OrbitParams
+) -> f64
+{
+    let pixel = pixel_offset(x as f64, y as f64, x_step_size, y_step_size, a_floor, b_floor, params);
+    smooth_orbit_rate(pixel, params, SMOOTH_BAILOUT)
 }
 
 
 
+///    ### (PURE)
+///    Orbit rate and smooth escape value of a given pixel from a single
+///    orbit iterated out to SMOOTH_BAILOUT, see get_smooth_orbit_rate.
+///    The orbit rate counts the iterations to SMOOTH_BAILOUT, a few more
+///    than get_orbit_rate counts to 2.
+pub fn get_escape_sample(
+    x : usize,
+    y : usize,
+    x_step_size : f64,
+    y_step_size : f64,
+    a_floor : f64,
+    b_floor : f64,
+    params : &OrbitParams
+) -> EscapeSample
+{
+    let pixel = pixel_offset(x as f64, y as f64, x_step_size, y_step_size, a_floor, b_floor, params);
+    let (iterations, z) = iterate_orbit(&params.kernel(), pixel, params, SMOOTH_BAILOUT);
+    EscapeSample {
+        iterations,
+        smooth : smooth_escape(iterations, z, params.max_iterations),
+        max_iterations : params.max_iterations,
+    }
+}
+
+
+///    ### (PURE)
+///    Electrostatic potential G = ln|Z\[n\]| / 2^n of an escaped pixel
+///    from its smooth escape value s = n + 1 - log2(log2|Z\[n\]|), which
+///    rearranges to G = 2 ln(2) 2^-s. Equipotential lines of G follow the
+///    same smooth bands, G halves with every extra iteration. Interior
+///    pixels have no potential and return 0.0.
+pub fn potential(smooth_escape : f64, max_iterations : u32) -> f64 {
+    if smooth_escape >= max_iterations as f64 {
+        return 0.0;
+    }
+    2.0 * std::f64::consts::LN_2 * (-smooth_escape).exp2()
+}
+
+
 ///    ### (PURE)
 ///    Calculates the height and width of the current frame given zoom_rate and frame number.
 ///    Returns the new x and y ranges for zoom.
@@ -107,7 +748,7 @@ fn calc_zoomed_ranges(
     starting_height : f64,
     starting_x_range : Range,
     starting_y_range : Range,
-    frame_number : u16,
+    frame_number : u32,
     zoom_rate : f64
 ) -> (Range, Range)
 {
@@ -137,61 +778,168 @@ fn calc_zoomed_ranges(
 
 ///    ### (PURE)
 ///    Takes a row of pixels and maps each entry to orbit 
-///    representation 0 to MAX_ITERATIONS
-
-fn map_row(
-    curr_row_tuple : (usize, Vec<u8>), 
+///    representation 0 to max_iterations, see fill_row.
+fn map_row<T : EscapeCount>(
+    curr_row_tuple : (usize, Vec<T>), 
     x_step_size: f64, y_step_size : f64,
     x_range : Range, 
-    y_range : Range
-) -> Vec<u8>
+    y_range : Range,
+    params : &OrbitParams
+) -> Vec<T>
 {
-    let (row_index, curr_row) = curr_row_tuple;
+    let (row_index, mut curr_row) = curr_row_tuple;
+    fill_row(row_index, &mut curr_row, x_step_size, y_step_size, x_range, y_range, params);
+    curr_row
+}
+
+
+///    ### (PURE)
+///    Overwrites row with the orbit rates of the first row.len() pixels
+///    of row row_index, in place. SIMD builds hand f64 rows to
+///    simd::fill_row.
+fn fill_row<T : EscapeCount>(
+    row_index : usize,
+    row : &mut [T],
+    x_step_size: f64, y_step_size : f64,
+    x_range : Range,
+    y_range : Range,
+    params : &OrbitParams
+) {
     let (x_floor, _) = x_range;
     let (y_floor, _) = y_range;
-    let updated_row = curr_row
-    .iter()
-    .enumerate()
-    .map(|curr_cell_tuple| {
-        let (col_index, _) = curr_cell_tuple;
-        get_orbit_rate(
+    #[cfg(feature = "simd")]
+    if params.precision == Precision::F64 {
+        return crate::simd::fill_row(row_index, row, x_step_size, y_step_size, x_floor, y_floor, params);
+    }
+    for (col_index, cell) in row.iter_mut().enumerate() {
+        *cell = T::from_count(get_orbit_rate(
             col_index,
             row_index,
-             x_step_size,
-             y_step_size, 
-             x_floor,
-              y_floor
-        )
-    });
-    updated_row.collect()
+            x_step_size,
+            y_step_size,
+            x_floor,
+            y_floor,
+            params
+        ));
+    }
+}
+
+
+///    ### (PURE)
+///    Predicate to determine if column j and column img_width - j of a frame
+///    sample mirrored points x and -x with equal orbit rates. Julia orbits
+///    lose the signs of Z\[0\] in the first step, so their sets are mirror
+///    symmetric about Re = 0 and the columns pair up when x_range is
+///    centered on it and the grid is not rotated. The burning ship itself
+///    has no exact mirror symmetry.
+fn mirror_symmetric(params : &OrbitParams, x_range : Range) -> bool {
+    matches!(params.kind, FractalKind::Julia { .. })
+        && x_range.0 + params.origin.0 == -(x_range.1 + params.origin.0)
+        && params.rotation == 0.0
+}
+
+
+///    ### (PURE)
+///    Widens rows holding columns 0 to img_width / 2 to img_width columns,
+///    column j > img_width / 2 copying column img_width - j.
+fn mirror_columns<T : EscapeCount>(half : Fractal<T>, img_width : usize) -> Fractal<T> {
+    half
+    .into_iter()
+    .map(|row| {
+        (0..img_width)
+        .map(|j| if j < row.len() { row[j] } else { row[img_width - j] })
+        .collect()
+    }).collect()
 }
 
 
 ///    ### (PURE)
-///    Maps each row of pixels to corresponding orbit rate.
-fn gen_burning_ship_fractal(
+///    Maps each row of pixels to corresponding orbit rate, rows in parallel,
+///    returning the frame alongside the largest orbit rate in it.
+///    With params.exploit_symmetry, mirror symmetric frames only compute
+///    their left half.
+fn gen_burning_ship_fractal<T : EscapeCount>(
     img_width : usize,
     img_height : usize, 
     x_range : Range,
     y_range : Range,
     x_step_size : f64, 
-    y_step_size : f64
-) -> Fractal
+    y_step_size : f64,
+    params : &OrbitParams
+) -> (Fractal<T>, u32)
 {
+    if params.exploit_symmetry && img_width > 2 && mirror_symmetric(params, x_range) {
+        let half = gen_burning_ship_fractal(
+            img_width / 2 + 1,
+            img_height,
+            x_range,
+            y_range,
+            x_step_size,
+            y_step_size,
+            &OrbitParams { exploit_symmetry : false, ..*params }
+        );
+        // ⬇
+        return (mirror_columns(half.0, img_width), half.1);
+    }
 
-    let grid : Fractal = vec![vec![0; img_width]; img_height];
+    let grid : Fractal<T> = vec![vec![T::default(); img_width]; img_height];
+    let (frame, row_maxima) : (Fractal<T>, Vec<u32>) = grid.into_par_iter().enumerate().map(|curr_row_tuple|{
+        let row = map_row(curr_row_tuple, x_step_size, y_step_size, x_range, y_range, params);
+        let row_max = row.iter().map(|cell| (*cell).into()).max().unwrap_or(0);
+        (row, row_max)
+    }).unzip();
+    (frame, row_maxima.into_iter().max().unwrap_or(0))
+
+}
 
-    // println!("x_step_size: {}\ny_step_size: {}", x_step_size, y_step_size);
-    grid.into_iter().enumerate().map(|curr_row_tuple|{
-        map_row(curr_row_tuple, x_step_size, y_step_size, x_range, y_range)
-    }).collect()
 
+///    ### (PURE)
+///    Runs gen_burning_ship_fractal on the GPU when the `gpu` feature is
+///    enabled and an adapter is available, otherwise on the rayon CPU path.
+fn gen_fractal_with_fallback<T : EscapeCount>(
+    img_width : usize,
+    img_height : usize, 
+    x_range : Range,
+    y_range : Range,
+    x_step_size : f64, 
+    y_step_size : f64,
+    params : &OrbitParams
+) -> (Fractal<T>, u32)
+{
+    // the shader iterates in f32 about the absolute coordinates, so
+    // DoubleDouble frames stay on the CPU
+    #[cfg(feature = "gpu")]
+    if params.precision != Precision::DoubleDouble {
+        if let Some(frame) = crate::gpu::gen_burning_ship_fractal(
+            img_width,
+            img_height,
+            x_range,
+            y_range,
+            x_step_size,
+            y_step_size,
+            params
+        ){
+            let max_escape = frame.iter().flatten().map(|cell| Into::<u32>::into(*cell)).max().unwrap_or(0);
+            return (frame, max_escape);
+        }
+    }
+    gen_burning_ship_fractal(
+        img_width,
+        img_height, 
+        x_range, 
+        y_range,
+        x_step_size,
+        y_step_size,
+        params
+    )
 }
 
 
 ///    ### (PURE)
 ///    Calculates the size of each pixel in terms of the burning_ship fractal func.
-fn calc_step_size(
+///    Unchecked: a 0 dimension gives an infinite step and a zero-width
+///    range a 0.0 step, so go through validate_viewport for user input.
+pub fn calc_step_size(
     img_width : usize,
     img_height : usize, 
     x_range : (f64, f64), 
@@ -230,14 +978,17 @@ pub fn calc_box_height_width(
 ///    calc_step_size ->\
 ///    gen_burning_ship_fractal\
 ///    To return a frame with each burning_ship fractal orbit calculated for some frame.
-pub fn build_frame(
+///    Fails with a RenderError when the zoomed viewport cannot be sampled,
+///    see validate_viewport.
+pub fn build_frame<T : EscapeCount>(
     img_width : usize,
     img_height : usize,
     starting_x_range : (f64, f64),
     starting_y_range : (f64, f64),
-    frame_number : u16,
+    frame_number : u32,
     zoom_rate : f64,
-) -> Fractal
+    params : &OrbitParams
+) -> Result<Fractal<T>, RenderError>
 {
     //manual composition
     let (starting_width, starting_height) = calc_box_height_width(
@@ -254,27 +1005,842 @@ pub fn build_frame(
         zoom_rate
     );
     // ⬇
-    let (x_step_size, y_step_size) = calc_step_size(
+    let (final_frame, max_escape) = build_frame_in_viewport(
+        img_width,
+        img_height,
+        x_range,
+        y_range,
+        params
+    )?;
+
+    log::debug!("~~~Finished building frame {}, max escape {}~~~", frame_number, max_escape);
+
+    Ok(final_frame)
+}
+
+
+///    ### (PURE)
+///    Checks that an img_width x img_height image of the viewport x_range
+///    by y_range has pixels, finite increasing bounds and positive steps,
+///    returning the steps from calc_step_size.
+pub fn validate_viewport(
+    img_width : usize,
+    img_height : usize,
+    x_range : Range,
+    y_range : Range
+) -> Result<(f64, f64), RenderError>
+{
+    if img_width == 0 || img_height == 0 {
+        return Err(RenderError::EmptyImage { width : img_width, height : img_height });
+    }
+    let bounds = [x_range.0, x_range.1, y_range.0, y_range.1];
+    if !bounds.iter().all(|bound| bound.is_finite()) {
+        return Err(RenderError::NonFiniteViewport { x_range, y_range });
+    }
+    if x_range.0 >= x_range.1 || y_range.0 >= y_range.1 {
+        return Err(RenderError::EmptyViewport { x_range, y_range });
+    }
+    let (x_step_size, y_step_size) = calc_step_size(img_width, img_height, x_range, y_range);
+    if x_step_size <= 0.0 || y_step_size <= 0.0 {
+        return Err(RenderError::DegenerateStep { x_step_size, y_step_size });
+    }
+    Ok((x_step_size, y_step_size))
+}
+
+
+///    ### (PURE) 
+///    Composes functions:\ 
+///    validate_viewport ->\
+///    gen_burning_ship_fractal\
+///    To return the frame covering the viewport x_range by y_range, with
+///    the largest orbit rate reached in it. A max equal to
+///    params.max_iterations means the frame holds interior points, one far
+///    below it means max_iterations could be lowered.
+pub fn build_frame_in_viewport<T : EscapeCount>(
+    img_width : usize,
+    img_height : usize,
+    x_range : Range,
+    y_range : Range,
+    params : &OrbitParams
+) -> Result<(Fractal<T>, u32), RenderError>
+{
+    let (x_step_size, y_step_size) = validate_viewport(
         img_width, 
         img_height, 
         x_range, 
         y_range
-    );
+    )?;
     // ⬇
-    let final_frame = gen_burning_ship_fractal(
+    Ok(gen_fractal_with_fallback(
         img_width,
         img_height, 
         x_range, 
         y_range,
         x_step_size,
-        y_step_size
+        y_step_size,
+        params
+    ))
+}
+
+
+///    ### (PURE)
+///    Scales the viewport x_range by y_range by zoom_rate^zoom_position
+///    about focus, so the point focus stays put on screen while
+///    everything else zooms in towards it. zoom_position is a frame
+///    number, fractional positions fall between frames.
+pub fn calc_focused_ranges(
+    starting_x_range : Range,
+    starting_y_range : Range,
+    zoom_position : f64,
+    zoom_rate : f64,
+    focus : (f64, f64)
+) -> (Range, Range)
+{
+    let scale = zoom_rate.powf(zoom_position);
+    let towards = |bound : f64, center : f64| center + scale * (bound - center);
+    (
+        (towards(starting_x_range.0, focus.0), towards(starting_x_range.1, focus.0)),
+        (towards(starting_y_range.0, focus.1), towards(starting_y_range.1, focus.1)),
+    )
+}
+
+
+///    ### (PURE)
+///    Viewport of the starting box x_range by y_range magnified magnify
+///    times about center: the box scaled by 1/magnify, then moved so its
+///    middle is center. Independent of any frame number or zoom rate.
+pub fn calc_magnified_ranges(
+    starting_x_range : Range,
+    starting_y_range : Range,
+    magnify : f64,
+    center : (f64, f64)
+) -> (Range, Range)
+{
+    let (starting_width, starting_height) = calc_box_height_width(
+        starting_x_range,
+        starting_y_range
     );
+    // ⬇
+    let half_width = starting_width / magnify / 2.0;
+    let half_height = starting_height / magnify / 2.0;
+    (
+        (center.0 - half_width, center.0 + half_width),
+        (center.1 - half_height, center.1 + half_height),
+    )
+}
+
 
-    if DEBUG_MODULE{
-        println!("\n~~~Finished building frame {}~~~", frame_number);
+///    ### (PURE)
+///    Viewport x and y ranges of frame_number of the animation described
+///    by config, zooming towards config.zoom_center when it is set.
+///    DoubleDouble frames give the ranges relative to
+///    RenderConfig::frame_origin instead, which stay exact in f64 however
+///    deep the zoom goes.
+pub fn frame_ranges(config : &RenderConfig, frame_number : u32) -> (Range, Range) {
+    let origin = config.frame_origin();
+    if origin != (0.0, 0.0) {
+        let shift = |range : Range, by : f64| (range.0 - by, range.1 - by);
+        return calc_focused_ranges(
+            shift(config.starting_x_range, origin.0),
+            shift(config.starting_y_range, origin.1),
+            config.zoom_frame(frame_number) as f64,
+            config.zoom_rate,
+            (0.0, 0.0)
+        );
+    }
+    if let Some(focus) = config.zoom_center {
+        return calc_focused_ranges(
+            config.starting_x_range,
+            config.starting_y_range,
+            config.zoom_frame(frame_number) as f64,
+            config.zoom_rate,
+            focus
+        );
     }
+    let (starting_width, starting_height) = calc_box_height_width(
+        config.starting_x_range,
+        config.starting_y_range
+    );
+    // ⬇
+    calc_zoomed_ranges(
+        starting_width,
+        starting_height,
+        config.starting_x_range,
+        config.starting_y_range,
+        config.zoom_frame(frame_number),
+        config.zoom_rate
+    )
+}
+
+
+///    ### (PURE)
+///    (Fractional) pixel position of the complex point in frame_number of
+///    config, complex_to_pixel with the viewport and orbit params of that
+///    frame.
+pub fn frame_pixel(config : &RenderConfig, frame_number : u32, point : (f64, f64)) -> (f64, f64) {
+    let (x_range, y_range) = frame_ranges(config, frame_number);
+    let steps = calc_step_size(config.img_width, config.img_height, x_range, y_range);
+    complex_to_pixel(point, (x_range, y_range), steps, &config.orbit_params(frame_number))
+}
+
+
+///    ### (PURE)
+///    Predicate to determine if frame_number zooms past what the float type
+///    of config.precision can resolve.
+///    Once a pixel step is smaller than the spacing of float values around the
+///    viewport, neighbouring pixels collapse onto the same complex point and
+///    the animation freezes into a solid frame (see the range math tests).
+pub fn precision_exhausted(config : &RenderConfig, frame_number : u32) -> bool {
+    let (x_range, y_range) = frame_ranges(config, frame_number);
+    let (x_step_size, y_step_size) = calc_step_size(
+        config.img_width,
+        config.img_height,
+        x_range,
+        y_range
+    );
+    let epsilon = match config.precision {
+        Precision::F64 => f64::EPSILON,
+        Precision::F32 => f32::EPSILON as f64,
+        Precision::DoubleDouble => DoubleDouble::EPSILON,
+    };
+    let origin = config.frame_origin();
+    let x_spacing = epsilon * (origin.0.abs() + x_range.0.abs().max(x_range.1.abs()));
+    let y_spacing = epsilon * (origin.1.abs() + y_range.0.abs().max(y_range.1.abs()));
+    x_step_size <= x_spacing || y_step_size <= y_spacing
+}
+
+
+///    ### (PURE)
+///    Returns the raw escape-time grid for frame_number of the animation
+///    described by config, without painting it. Each cell holds the orbit
+///    rate \[0 to config.max_iterations\] of the corresponding pixel,
+///    row-major with row 0 at the top of the image. Cells saturate at
+///    T::MAX_COUNT, so pick T wide enough for config.max_iterations.
+pub fn render_escape_grid<T : EscapeCount>(
+    config : &RenderConfig,
+    frame_number : u32
+) -> Result<Fractal<T>, RenderError>
+{
+    let (x_range, y_range) = frame_ranges(config, frame_number);
+    // ⬇
+    let (final_frame, max_escape) = build_frame_in_viewport(
+        config.img_width,
+        config.img_height,
+        x_range,
+        y_range,
+        &config.orbit_params(frame_number)
+    )?;
+
+    log::debug!("~~~Finished building frame {}, max escape {}~~~", frame_number, max_escape);
+
+    Ok(final_frame)
+}
+
+
+
+///    ### (PURE)
+///    Validated viewport of frame_number of config: the pixel step sizes,
+///    the x and y ranges and the orbit params every grid of the frame is
+///    built from.
+fn frame_viewport(
+    config : &RenderConfig,
+    frame_number : u32
+) -> Result<FrameViewport, RenderError>
+{
+    let (x_range, y_range) = frame_ranges(config, frame_number);
+    let steps = validate_viewport(config.img_width, config.img_height, x_range, y_range)?;
+    Ok((steps, (x_range, y_range), config.orbit_params(frame_number)))
+}
+
+
+///    ### (PURE)
+///    Builds the grid of frame_number of config cell by cell, rows in
+///    parallel on the rayon CPU path. cell is handed the column and row
+///    of a pixel along with the step sizes, ranges and orbit params of
+///    the frame and returns the value stored for it.
+pub fn render_grid_with<T, F>(
+    config : &RenderConfig,
+    frame_number : u32,
+    cell : F
+) -> Result<Fractal<T>, RenderError>
+where
+    T : Send,
+    F : Fn(usize, usize, (f64, f64), (Range, Range), &OrbitParams) -> T + Sync,
+{
+    let (steps, ranges, params) = frame_viewport(config, frame_number)?;
+    // ⬇
+    Ok((0..config.img_height).into_par_iter().map(|row_index|{
+        (0..config.img_width).map(|col_index| cell(col_index, row_index, steps, ranges, &params)).collect()
+    }).collect())
+}
+
+
+///    ### (PURE)
+///    render_escape_grid writing into frame instead of a new grid, for
+///    callers rendering many frames in a row. frame is only reallocated
+///    when its size differs from config's, rows and cells are otherwise
+///    overwritten in place. Rows are built on the rayon CPU path (whole
+///    rows, so SIMD builds keep their vector path), mirror symmetric
+///    frames computing only their left half. Returns the largest orbit
+///    rate of the frame.
+pub fn render_into<T : EscapeCount>(
+    frame : &mut Fractal<T>,
+    config : &RenderConfig,
+    frame_number : u32
+) -> Result<u32, RenderError>
+{
+    let ((x_step_size, y_step_size), (x_range, y_range), params) = frame_viewport(config, frame_number)?;
+    let img_width = config.img_width;
+    let mirrored = params.exploit_symmetry && img_width > 2 && mirror_symmetric(&params, x_range);
+    let computed_width = if mirrored { img_width / 2 + 1 } else { img_width };
+    frame.resize_with(config.img_height, Vec::new);
+    // ⬇
+    Ok(frame.par_iter_mut().enumerate().map(|(row_index, row)|{
+        row.resize(img_width, T::default());
+        fill_row(row_index, &mut row[..computed_width], x_step_size, y_step_size, x_range, y_range, &params);
+        for j in computed_width..img_width {
+            row[j] = row[img_width - j];
+        }
+        row.iter().map(|cell| (*cell).into()).max().unwrap_or(0)
+    }).max().unwrap_or(0))
+}
+
 
-    final_frame
+///    ### (PURE)
+///    Cancellable counterpart of render_escape_grid. Whole rows are built
+///    on the rayon CPU path as in render_into, cancel is checked before
+///    each one, returning None as soon as it is set.
+pub fn render_escape_grid_until<T : EscapeCount>(
+    config : &RenderConfig,
+    frame_number : u32,
+    cancel : &AtomicBool
+) -> Result<Option<Fractal<T>>, RenderError>
+{
+    let ((x_step_size, y_step_size), (x_range, y_range), params) = frame_viewport(config, frame_number)?;
+    // ⬇
+    Ok((0..config.img_height).into_par_iter().map(|row_index|{
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        Some(map_row(
+            (row_index, vec![T::default(); config.img_width]),
+            x_step_size,
+            y_step_size,
+            x_range,
+            y_range,
+            &params
+        ))
+    }).collect())
 }
 
+///    ### (PURE)
+///    Smooth counterpart of render_escape_grid, each cell holds
+///    get_smooth_orbit_rate of its pixel. Always runs on the rayon CPU
+///    path and costs 8 bytes per pixel (73.6 MB for a 4000x2300 frame).
+pub fn render_smooth_grid(config : &RenderConfig, frame_number : u32) -> Result<Fractal<f64>, RenderError> {
+    render_grid_with(config, frame_number, |x, y, steps, ranges, params|{
+        get_smooth_orbit_rate(x, y, steps.0, steps.1, ranges.0.0, ranges.1.0, params)
+    })
+}
+
+///    ### (PURE)
+///    Counterpart of render_smooth_grid keeping the orbit rate and the
+///    interior flag next to the smooth escape value of every pixel, all
+///    from the same orbit, see get_escape_sample.
+pub fn render_tensor_grid(config : &RenderConfig, frame_number : u32) -> Result<Fractal<EscapeSample>, RenderError> {
+    render_grid_with(config, frame_number, |x, y, steps, ranges, params|{
+        get_escape_sample(x, y, steps.0, steps.1, ranges.0.0, ranges.1.0, params)
+    })
+}
+
+///    ### (PURE)
+///    Counterpart of render_escape_grid iterating kernel instead of the
+///    burning ship, so a map of your own reuses the zoom, paint and output
+///    pipeline. params.kind still picks C and Z\[0\] (the pixel for both,
+///    or the Julia constant). Always runs on the rayon CPU path, without
+///    the SIMD, GPU or mirror symmetry shortcuts of the built in map.
+pub fn render_kernel_grid<T : EscapeCount, K : IterationKernel>(
+    config : &RenderConfig,
+    frame_number : u32,
+    kernel : &K
+) -> Result<Fractal<T>, RenderError>
+{
+    render_grid_with(config, frame_number, |x, y, steps, ranges, params|{
+        let pixel = pixel_offset(x as f64, y as f64, steps.0, steps.1, ranges.0.0, ranges.1.0, params);
+        T::from_count(get_orbit_rate_with(kernel, pixel, params))
+    })
+}
+
+///    ### (PURE)
+///    Burning ship and kernel iterated over the same viewport in one pass,
+///    each cell holding the mix (1 - weight) * ship + weight * other of
+///    the two orbit rates. weight \[0 to 1\] of 0.0 gives the burning
+///    ship, 1.0 the kernel. The values are fractional, so the grid paints
+///    like the f64 grids of --edge-aa. Runs on the CPU path as
+///    render_kernel_grid does.
+pub fn render_blended_kernel_grid<K : IterationKernel>(
+    config : &RenderConfig,
+    frame_number : u32,
+    kernel : &K,
+    weight : f64
+) -> Result<Fractal<f64>, RenderError>
+{
+    render_grid_with(config, frame_number, |x, y, steps, ranges, params|{
+        let pixel = pixel_offset(x as f64, y as f64, steps.0, steps.1, ranges.0.0, ranges.1.0, params);
+        // ⬇
+        let ship_rate = get_orbit_rate_with(&params.kernel(), pixel, params) as f64;
+        let other_rate = get_orbit_rate_with(kernel, pixel, params) as f64;
+        (1.0 - weight) * ship_rate + weight * other_rate
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn range_strategy() -> impl Strategy<Value = Range> {
+        (-4.0..4.0f64, 0.01..4.0f64).prop_map(|(floor, width)| (floor, floor + width))
+    }
+
+    fn zoomed_ranges(x_range : Range, y_range : Range, frame_number : u32, zoom_rate : f64) -> (Range, Range) {
+        let (width, height) = calc_box_height_width(x_range, y_range);
+        calc_zoomed_ranges(width, height, x_range, y_range, frame_number, zoom_rate)
+    }
+
+    fn center(range : Range) -> f64 {
+        (range.0 + range.1) / 2.0
+    }
+
+    proptest! {
+        #[test]
+        fn zoom_keeps_viewport_center_fixed(
+            x_range in range_strategy(),
+            y_range in range_strategy(),
+            frame_number in 0u32..2000,
+            zoom_rate in 0.5..0.999f64
+        ) {
+            let (zoomed_x, zoomed_y) = zoomed_ranges(x_range, y_range, frame_number, zoom_rate);
+            prop_assert!((center(zoomed_x) - center(x_range)).abs() < 1e-9);
+            prop_assert!((center(zoomed_y) - center(y_range)).abs() < 1e-9);
+        }
+
+        #[test]
+        fn zoom_shrinks_viewport_monotonically(
+            x_range in range_strategy(),
+            y_range in range_strategy(),
+            frame_number in 0u32..2000,
+            zoom_rate in 0.5..0.999f64
+        ) {
+            let (curr_x, curr_y) = zoomed_ranges(x_range, y_range, frame_number, zoom_rate);
+            let (next_x, next_y) = zoomed_ranges(x_range, y_range, frame_number + 1, zoom_rate);
+            let (curr_width, curr_height) = calc_box_height_width(curr_x, curr_y);
+            let (next_width, next_height) = calc_box_height_width(next_x, next_y);
+            prop_assert!(next_width <= curr_width);
+            prop_assert!(next_height <= curr_height);
+        }
+
+        #[test]
+        fn step_sizes_stay_positive(
+            x_range in range_strategy(),
+            y_range in range_strategy(),
+            frame_number in 0u32..250,
+            zoom_rate in 0.9..0.999f64,
+            img_width in 1usize..4000,
+            img_height in 1usize..4000
+        ) {
+            // past this depth the viewport collapses in f64, see below
+            prop_assume!(zoom_rate.powf(frame_number as f64) > 1e-12);
+            let (zoomed_x, zoomed_y) = zoomed_ranges(x_range, y_range, frame_number, zoom_rate);
+            let (x_step_size, y_step_size) = calc_step_size(img_width, img_height, zoomed_x, zoomed_y);
+            prop_assert!(x_step_size > 0.0);
+            prop_assert!(y_step_size > 0.0);
+        }
+    }
+
+    // With the default animation the viewport never reaches zero width.
+    // Once the width shrinks to the f64 spacing around its center the range
+    // bounds stop moving and every later frame renders the same ~1 ulp wide
+    // box, so the animation freezes around frame 914, long before
+    // zoom_rate.powf(frame_number) underflows to 0.0 around frame 18254.
+    #[test]
+    fn default_zoom_freezes_at_f64_spacing_before_scale_underflows() {
+        let x_range = (-3.45, 0.05);
+        let y_range = (-0.99, 0.99);
+        let zoom_rate = 0.96;
+        let width_at = |frame_number : u32| {
+            let (zoomed_x, _) = zoomed_ranges(x_range, y_range, frame_number, zoom_rate);
+            zoomed_x.1 - zoomed_x.0
+        };
+        let freeze_frame = (0..u32::MAX)
+            .find(|frame_number| width_at(*frame_number) <= 2.0 * f64::EPSILON)
+            .unwrap();
+        let underflow_frame = (0..u32::MAX)
+            .find(|frame_number| zoom_rate.powf(*frame_number as f64) == 0.0)
+            .unwrap();
+        assert!((900..930).contains(&freeze_frame));
+        assert!((18000..18500).contains(&underflow_frame));
+        assert!(width_at(u32::MAX) > 0.0);
+    }
+
+    // The hand-rolled arithmetic calculate_next_z replaced, kept as a
+    // reference for the num_complex version.
+    fn reference_next_z(constant : (f64, f64), prev : (f64, f64)) -> (f64, f64) {
+        let sqr_a = prev.0 * prev.0;
+        let sqr_b = prev.1 * prev.1;
+        (sqr_a - sqr_b + constant.0, (2.0 * prev.0 * prev.1).abs() + constant.1)
+    }
+
+    fn reference_orbit_rate(constant : (f64, f64), seed : (f64, f64), max_iterations : u32) -> u32 {
+        let mut i = 0;
+        let mut z = seed;
+        while i < max_iterations && (z.0 * z.0 + z.1 * z.1) < 4.0 {
+            z = reference_next_z(constant, z);
+            i += 1;
+        }
+        i
+    }
+
+    fn grid_points() -> impl Iterator<Item = (f64, f64)> {
+        (0..=80).flat_map(|i| (0..=80).map(move |j| {
+            (-2.5 + i as f64 * 0.0625 + 1e-3, -2.5 + j as f64 * 0.0625 - 7e-4)
+        }))
+    }
+
+    #[test]
+    fn next_z_matches_hand_rolled_arithmetic() {
+        let constant = (-1.755, -0.028);
+        for point in grid_points() {
+            let z = calculate_next_z(
+                Complex64::new(constant.0, constant.1),
+                Complex64::new(point.0, point.1),
+                DEFAULT_COEFFICIENTS
+            );
+            let (a, b) = reference_next_z(constant, point);
+            assert_eq!(z.re.to_bits(), a.to_bits(), "re at {:?}", point);
+            assert_eq!(z.im.to_bits(), b.to_bits(), "im at {:?}", point);
+        }
+    }
+
+    #[test]
+    fn orbit_rates_match_hand_rolled_arithmetic() {
+        let julia = OrbitParams { kind : FractalKind::Julia { c : (-1.2, -0.3) }, ..OrbitParams::default() };
+        for (i, j) in (0..=80).flat_map(|i| (0..=80).map(move |j| (i, j))) {
+            let point = (-2.5 + i as f64 * 0.0625, -2.5 + j as f64 * 0.0625);
+            let ship = get_orbit_rate(i, j, 0.0625, 0.0625, -2.5, -2.5, &OrbitParams::default());
+            assert_eq!(ship, reference_orbit_rate(point, point, MAX_ITERATIONS), "at {:?}", point);
+            let julia_rate = get_orbit_rate(i, j, 0.0625, 0.0625, -2.5, -2.5, &julia);
+            assert_eq!(julia_rate, reference_orbit_rate((-1.2, -0.3), point, MAX_ITERATIONS), "at {:?}", point);
+        }
+    }
+
+    // Orbit rate of the single point (a, b) under the default params.
+    fn orbit_rate_at(a : f64, b : f64) -> u32 {
+        get_orbit_rate(0, 0, 0.0, 0.0, a, b, &OrbitParams::default())
+    }
+
+    #[test]
+    fn known_interior_points_never_escape() {
+        // (0, 0) is a fixed point, (-0.5, -0.5) lies deep in the main body
+        // and (-1, -0.2) in its left lobe
+        for (a, b) in [(0.0, 0.0), (-0.5, -0.5), (-1.0, -0.2)] {
+            assert_eq!(orbit_rate_at(a, b), MAX_ITERATIONS, "at ({}, {})", a, b);
+        }
+    }
+
+    #[test]
+    fn known_exterior_points_escape_quickly() {
+        // (1, 1) and (-0.5, 0.5) sit outside the set above and right of the
+        // ship, (3, 0) starts outside the bailout radius
+        for ((a, b), max_rate) in [((1.0, 1.0), 2), ((-0.5, 0.5), 4), ((3.0, 0.0), 0)] {
+            let rate = orbit_rate_at(a, b);
+            assert!(rate <= max_rate, "({}, {}) took {} iterations", a, b, rate);
+        }
+    }
+
+    #[test]
+    fn mirrored_julia_frame_matches_full_compute() {
+        // a step of 1/16 keeps every column coordinate exact, so column j
+        // and column width - j sample exactly x and -x
+        let x_range = (-2.0, 2.0);
+        let y_range = (-1.5, 1.5);
+        let (width, height) = (64, 48);
+        let (x_step_size, y_step_size) = calc_step_size(width, height, x_range, y_range);
+        let full_params = OrbitParams { kind : FractalKind::Julia { c : (-1.3, -0.2) }, ..OrbitParams::default() };
+        let mirrored_params = OrbitParams { exploit_symmetry : true, ..full_params };
+        assert!(mirror_symmetric(&mirrored_params, x_range));
+        let full : (Fractal<u8>, u32) = gen_burning_ship_fractal(width, height, x_range, y_range, x_step_size, y_step_size, &full_params);
+        let mirrored : (Fractal<u8>, u32) = gen_burning_ship_fractal(width, height, x_range, y_range, x_step_size, y_step_size, &mirrored_params);
+        assert_eq!(mirrored, full);
+        assert!(!mirror_symmetric(&OrbitParams::default(), x_range));
+    }
+
+    #[test]
+    fn precision_exhausted_before_viewport_freezes() {
+        let config = RenderConfig::default();
+        assert!(!precision_exhausted(&config, 0));
+        assert!(!precision_exhausted(&config, 600));
+        assert!(precision_exhausted(&config, 914));
+        assert!(precision_exhausted(&config, u32::MAX));
+    }
+
+    #[test]
+    fn build_frame_rejects_degenerate_viewports() {
+        let params = OrbitParams::default();
+        let build = |width, height, x_range, y_range| {
+            build_frame::<u8>(width, height, x_range, y_range, 0, 0.96, &params)
+        };
+        assert!(matches!(build(0, 10, (-2.0, 1.0), (-1.0, 1.0)), Err(RenderError::EmptyImage { .. })));
+        assert!(matches!(build(10, 10, (f64::NAN, 1.0), (-1.0, 1.0)), Err(RenderError::NonFiniteViewport { .. })));
+        assert!(matches!(build(10, 10, (1.0, -2.0), (-1.0, 1.0)), Err(RenderError::EmptyViewport { .. })));
+        assert!(matches!(build(10, 10, (1.0, 1.0), (-1.0, 1.0)), Err(RenderError::EmptyViewport { .. })));
+        assert!(matches!(build(10, 10, (0.0, 5e-324), (-1.0, 1.0)), Err(RenderError::DegenerateStep { .. })));
+        assert_eq!(build(10, 10, (-2.0, 1.0), (-1.0, 1.0)).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn potential_matches_final_z() {
+        let params = OrbitParams::default();
+        for (a, b) in [(1.0, 1.0), (-0.5, 0.5), (0.3, -0.9), (-1.8, -0.05)] {
+            let (n, z) = iterate_orbit(&params.kernel(), Complex64::new(a, b), &params, SMOOTH_BAILOUT);
+            let smooth = get_smooth_orbit_rate(0, 0, 0.0, 0.0, a, b, &params);
+            let expected = z.norm().ln() / 2f64.powi(n as i32);
+            assert!((potential(smooth, params.max_iterations) - expected).abs() < 1e-12);
+        }
+        assert_eq!(potential(MAX_ITERATIONS as f64, MAX_ITERATIONS), 0.0);
+    }
+
+    #[test]
+    fn smooth_bailout_removes_band_edge_jumps() {
+        // a line through the exterior crossing several bands, where the
+        // smooth value should change without steps
+        let params = OrbitParams::default();
+        let samples = 2000;
+        let max_jump = |bailout_radius| {
+            let values : Vec<f64> = (0..=samples).map(|k| {
+                let a = -1.0 + 1.5 * k as f64 / samples as f64;
+                smooth_orbit_rate(Complex64::new(a, 0.5), &params, bailout_radius)
+            }).collect();
+            values.windows(2).map(|pair| (pair[1] - pair[0]).abs()).fold(0.0, f64::max)
+        };
+        let jump_at_2 = max_jump(ESCAPE_RADIUS);
+        let jump_at_256 = max_jump(SMOOTH_BAILOUT);
+        assert!(jump_at_256 < 0.05, "jump {} at radius 256", jump_at_256);
+        assert!(jump_at_256 * 2.0 < jump_at_2, "jump {} at radius 256 vs {} at radius 2", jump_at_256, jump_at_2);
+    }
+
+    #[test]
+    fn f32_orbits_match_f64_on_shallow_frames() {
+        let config = RenderConfig { img_width : 160, img_height : 92, ..RenderConfig::default() };
+        let f32_config = RenderConfig { precision : Precision::F32, ..config.clone() };
+        let frame : Fractal<u8> = render_escape_grid(&config, 0).unwrap();
+        let f32_frame : Fractal<u8> = render_escape_grid(&f32_config, 0).unwrap();
+        let mismatches = frame.iter().flatten().zip(f32_frame.iter().flatten()).filter(|(a, b)| a != b).count();
+        // only chaotic pixels near the boundary may round to another count
+        assert!(mismatches * 20 < 160 * 92, "{} of {} pixels differ", mismatches, 160 * 92);
+        let exhausted = |config : &RenderConfig| (0..u32::MAX).find(|frame| precision_exhausted(config, *frame));
+        assert!(exhausted(&f32_config) < exhausted(&config));
+    }
+
+    #[test]
+    fn trace_orbit_steps_match_orbit_rate() {
+        let params = OrbitParams::default();
+        for (a, b) in [(1.0, 1.0), (-0.5, 0.5), (-1.8, -0.05), (-0.5, -0.5)] {
+            let orbit = trace_orbit((a, b), &params);
+            assert_eq!(orbit[0], (a, b));
+            assert_eq!(orbit.len() as u32 - 1, get_orbit_rate(0, 0, 0.0, 0.0, a, b, &params));
+        }
+    }
 
+    #[test]
+    fn magnified_ranges_shrink_the_box_about_center() {
+        let (x_range, y_range) = calc_magnified_ranges((-2.5, 1.5), (-2.0, 1.0), 500.0, (-1.75, -0.03));
+        let (width, height) = calc_box_height_width(x_range, y_range);
+        assert!((width - 4.0 / 500.0).abs() < 1e-12);
+        assert!((height - 3.0 / 500.0).abs() < 1e-12);
+        assert!(((x_range.0 + x_range.1) / 2.0 + 1.75).abs() < 1e-12);
+        assert!(((y_range.0 + y_range.1) / 2.0 + 0.03).abs() < 1e-12);
+    }
+
+    #[test]
+    fn validate_viewport_rejects_zero_dimensions_and_widths() {
+        assert!(calc_step_size(0, 10, (-2.0, 1.0), (-1.0, 1.0)).0.is_infinite());
+        assert_eq!(calc_step_size(10, 10, (1.0, 1.0), (-1.0, 1.0)).0, 0.0);
+        let validate = |width, height, x_range, y_range| validate_viewport(width, height, x_range, y_range);
+        assert!(matches!(validate(0, 10, (-2.0, 1.0), (-1.0, 1.0)), Err(RenderError::EmptyImage { width : 0, height : 10 })));
+        assert!(matches!(validate(10, 0, (-2.0, 1.0), (-1.0, 1.0)), Err(RenderError::EmptyImage { width : 10, height : 0 })));
+        assert!(matches!(validate(0, 0, (1.0, 1.0), (1.0, 1.0)), Err(RenderError::EmptyImage { .. })));
+        assert!(matches!(validate(10, 10, (1.0, 1.0), (-1.0, 1.0)), Err(RenderError::EmptyViewport { .. })));
+        assert!(matches!(validate(10, 10, (-2.0, 1.0), (0.5, 0.5)), Err(RenderError::EmptyViewport { .. })));
+        assert!(matches!(validate(10, 10, (-2.0, 1.0), (0.0, 5e-324)), Err(RenderError::DegenerateStep { .. })));
+        assert_eq!(validate(30, 20, (-2.0, 1.0), (-1.0, 1.0)).unwrap(), (0.1, 0.1));
+    }
+
+    #[test]
+    fn complex_to_pixel_inverts_pixel_to_complex() {
+        let viewport = ((-2.5, 1.5), (-2.0, 1.0));
+        let step = calc_step_size(400, 300, viewport.0, viewport.1);
+        let rotated = OrbitParams { rotation : 0.7, pivot : (-0.5, -0.5), ..OrbitParams::default() };
+        for params in [OrbitParams::default(), rotated] {
+            for pixel in [(0.0, 0.0), (123.0, 45.0), (399.5, 299.5)] {
+                let (x, y) = complex_to_pixel(pixel_to_complex(pixel, viewport, step, &params), viewport, step, &params);
+                assert!((x - pixel.0).abs() < 1e-9 && (y - pixel.1).abs() < 1e-9);
+            }
+        }
+        let (a, b) = pixel_to_complex((10.0, 20.0), viewport, step, &OrbitParams::default());
+        assert_eq!(pixel_coordinate(10.0, 20.0, step.0, step.1, -2.5, -2.0, &OrbitParams::default()), Complex64::new(a, b));
+    }
+
+    #[test]
+    fn zoom_pivot_stays_on_its_pixel() {
+        for precision in [Precision::F64, Precision::DoubleDouble] {
+            let config = RenderConfig { zoom_center : Some((-1.762, -0.028)), precision, ..RenderConfig::default() };
+            let start = frame_pixel(&config, 0, config.zoom_pivot());
+            for frame_number in [1, 50, 300] {
+                let (x, y) = frame_pixel(&config, frame_number, config.zoom_pivot());
+                assert!((x - start.0).abs() < 1e-6 && (y - start.1).abs() < 1e-6, "{:?} frame {}", precision, frame_number);
+            }
+        }
+    }
+
+    #[test]
+    fn tensor_grid_matches_smooth_grid() {
+        let config = RenderConfig { img_width : 40, img_height : 23, ..RenderConfig::default() };
+        let tensor = render_tensor_grid(&config, 0).unwrap();
+        let smooth = render_smooth_grid(&config, 0).unwrap();
+        for (sample, value) in tensor.iter().flatten().zip(smooth.iter().flatten()) {
+            assert_eq!(sample.smooth, *value);
+            let [_, _, interior] = sample.channels();
+            assert_eq!(interior == 1.0, *value >= config.max_iterations as f64);
+        }
+        let npy = crate::npy::encode_npy(&tensor);
+        assert!(String::from_utf8_lossy(&npy[..128]).contains("'shape': (23, 40, 3)"));
+        assert_eq!(npy.len(), 128 + 23 * 40 * 3 * 4);
+    }
+
+    #[test]
+    fn smooth_orbit_rate_is_monotone_for_fast_escapes() {
+        // a ray leaving the set, from points escaping after a few
+        // iterations out to points escaping at once or overflowing
+        let samples = 4000;
+        for precision in [Precision::F64, Precision::F32] {
+            let params = OrbitParams { precision, ..OrbitParams::default() };
+            let values : Vec<f64> = (0..=samples).map(|k| {
+                let radius = 2.0 * 10f64.powf(300.0 * k as f64 / samples as f64);
+                smooth_orbit_rate(Complex64::from_polar(radius, 0.3), &params, SMOOTH_BAILOUT)
+            }).collect();
+            assert!(values.iter().all(|value| value.is_finite() && *value >= 0.0), "{:?}", precision);
+            assert!(values[0] > 0.0);
+            assert!(values.windows(2).all(|pair| pair[1] <= pair[0] + 1e-9), "{:?} not monotone", precision);
+        }
+    }
+
+    #[test]
+    fn double_double_matches_f64_and_zooms_past_it() {
+        let config = RenderConfig { img_width : 160, img_height : 92, ..RenderConfig::default() };
+        let dd_config = RenderConfig { precision : Precision::DoubleDouble, ..config.clone() };
+        let frame : Fractal<u8> = render_escape_grid(&config, 0).unwrap();
+        let dd_frame : Fractal<u8> = render_escape_grid(&dd_config, 0).unwrap();
+        let mismatches = frame.iter().flatten().zip(dd_frame.iter().flatten()).filter(|(a, b)| a != b).count();
+        assert!(mismatches * 100 < 160 * 92, "{} of {} pixels differ", mismatches, 160 * 92);
+        // past the f64 precision wall the double-double frame keeps distinct pixels
+        let deep = (0..u32::MAX).find(|frame| precision_exhausted(&config, *frame)).unwrap() + 100;
+        assert!(!precision_exhausted(&dd_config, deep));
+        let (x_range, _) = frame_ranges(&dd_config, deep);
+        let (x_step_size, _) = calc_step_size(dd_config.img_width, dd_config.img_height, x_range, x_range);
+        let params = dd_config.orbit_params(deep);
+        let left = pixel_offset(0.0, 0.0, x_step_size, x_step_size, x_range.0, x_range.0, &params);
+        let right = pixel_offset(1.0, 0.0, x_step_size, x_step_size, x_range.0, x_range.0, &params);
+        let origin = DoubleDouble::from_f64(params.origin.0);
+        assert!(origin + DoubleDouble::from_f64(left.re) != origin + DoubleDouble::from_f64(right.re));
+        assert_eq!(params.origin.0 + left.re, params.origin.0 + right.re);
+    }
+
+    #[test]
+    fn kernels_render_their_own_maps() {
+        let config = RenderConfig { img_width : 64, img_height : 37, ..RenderConfig::default() };
+        let ship : Fractal<u8> = render_escape_grid(&config, 5).unwrap();
+        assert_eq!(render_kernel_grid::<u8, _>(&config, 5, &BurningShipKernel::default()).unwrap(), ship);
+        let params = OrbitParams::default();
+        let i = Complex64::new(0.0, 1.0);
+        assert_eq!(get_orbit_rate_with(&MandelbrotKernel, i, &params), params.max_iterations);
+        assert!(get_orbit_rate_with(&BurningShipKernel::default(), i, &params) < params.max_iterations);
+        assert!(get_orbit_rate_with(&TricornKernel, i, &params) < params.max_iterations);
+        assert_eq!(get_orbit_rate_with(&TricornKernel, Complex64::new(-0.2, 0.0), &params), params.max_iterations);
+    }
+
+    #[test]
+    fn interior_threshold_only_reclassifies_late_escapes() {
+        let full = OrbitParams { max_iterations : 200, ..OrbitParams::default() };
+        let early = OrbitParams { interior_threshold : Some(20), ..full };
+        let pixels = (0..400).map(|k| Complex64::new(-2.0 + 0.01 * k as f64, -0.04));
+        let (mut reclassified, mut kept) = (0, 0);
+        for pixel in pixels {
+            let rate = get_orbit_rate_with(&full.kernel(), pixel, &full);
+            let early_rate = get_orbit_rate_with(&early.kernel(), pixel, &early);
+            if rate < 20 {
+                assert_eq!(early_rate, rate);
+                kept += 1;
+            } else {
+                assert_eq!(early_rate, full.max_iterations);
+                reclassified += (rate < full.max_iterations) as u32;
+            }
+        }
+        assert!(kept > 0 && reclassified > 0);
+    }
+
+    #[test]
+    fn render_into_and_paint_into_reuse_buffers_of_any_size() {
+        use crate::painter::{paint_frame, paint_into, PaintOptions};
+        use image::{ImageBuffer, Rgb};
+        let palette = [Rgb([0, 0, 0]), Rgb([255, 128, 0]), Rgb([255, 255, 255])];
+        let options = PaintOptions::default();
+        let mut frame : Fractal<u32> = vec![vec![7; 3]; 90];
+        let mut buffer = ImageBuffer::from_pixel(5, 5, Rgb([1, 2, 3]));
+        for (img_width, img_height) in [(40, 30), (24, 16), (24, 16)] {
+            let config = RenderConfig { img_width, img_height, ..RenderConfig::default() };
+            let max_escape = render_into(&mut frame, &config, 2).unwrap();
+            let expected : Fractal<u32> = render_escape_grid(&config, 2).unwrap();
+            assert_eq!(frame, expected);
+            assert_eq!(max_escape, expected.iter().flatten().copied().max().unwrap());
+            let (width, height) = (img_width as u32, img_height as u32);
+            paint_into(&mut buffer, width, height, &frame, &palette, &options, 0);
+            assert_eq!(buffer, paint_frame(width, height, &expected, &palette, &options, 0));
+        }
+    }
+
+    #[test]
+    fn flip_y_renders_the_vertical_mirror_of_the_frame() {
+        let config = RenderConfig { img_width : 48, img_height : 37, ..RenderConfig::default() };
+        let flipped = RenderConfig { flip_y : true, ..config.clone() };
+        let mut mirrored : Vec<Vec<u32>> = render_escape_grid(&config, 4).unwrap();
+        mirrored.reverse();
+        assert_eq!(render_escape_grid::<u32>(&flipped, 4).unwrap(), mirrored);
+        let mut mirrored = render_smooth_grid(&config, 4).unwrap();
+        mirrored.reverse();
+        assert_eq!(render_smooth_grid(&flipped, 4).unwrap(), mirrored);
+        let (x, y) = frame_pixel(&config, 4, (-1.7, -0.02));
+        let (flipped_x, flipped_y) = frame_pixel(&flipped, 4, (-1.7, -0.02));
+        assert_eq!((flipped_x, flipped_y), (x, 36.0 - y));
+    }
+
+    #[test]
+    fn blended_maps_mix_escape_counts_by_weight() {
+        let config = RenderConfig { img_width : 48, img_height : 27, ..RenderConfig::default() };
+        let ship : Fractal<u32> = render_escape_grid(&config, 3).unwrap();
+        let mandelbrot : Fractal<u32> = render_kernel_grid(&config, 3, &MandelbrotKernel).unwrap();
+        let as_f64 = |grid : &Fractal<u32>| -> Fractal<f64> {
+            grid.iter().map(|row| row.iter().map(|cell| *cell as f64).collect()).collect()
+        };
+        let blend = |weight| BuiltinMap::Mandelbrot.render_blended_grid(&config, 3, weight).unwrap();
+        assert_eq!(blend(0.0), as_f64(&ship));
+        assert_eq!(blend(1.0), as_f64(&mandelbrot));
+        let half = blend(0.5);
+        assert_ne!(ship, mandelbrot);
+        for ((half_row, ship_row), mandelbrot_row) in half.iter().zip(&ship).zip(&mandelbrot) {
+            for ((cell, a), b) in half_row.iter().zip(ship_row).zip(mandelbrot_row) {
+                assert_eq!(*cell, (*a as f64 + *b as f64) / 2.0);
+            }
+        }
+    }
+}
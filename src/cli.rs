@@ -0,0 +1,990 @@
+//!   Module contains the command line interface of the
+//!   frame generator binary.
+//!   @author Van Gouache
+
+use std::num::NonZeroU32;
+use std::ops::Range;
+use clap::Parser;
+use image::Rgb;
+use burning_ship_fractal::antialias::{ReconstructionFilter, FILTER_NAMES};
+use burning_ship_fractal::reproject::{ReprojectQuality, QUALITY_NAMES};
+use burning_ship_fractal::burning_ship_frac::{calc_magnified_ranges, BuiltinMap, Precision, MAP_NAMES, MAX_ITERATIONS};
+use burning_ship_fractal::config::{zoom_rate_for_magnification, AnimationMode, JuliaPath, PrintSize};
+use burning_ship_fractal::painter::{BlendMode, ColoringMode, ContrastStretch, EdgeStyle, PaletteCurve, PngCompression, Vignette, BLEND_NAMES, PRESET_NAMES};
+use burning_ship_fractal::RenderConfig;
+
+//frames per burst counted by the BURSTS argument
+pub const CHUNK_SIZE : u32 = 4;
+//bytes of the painted RGB buffer per pixel
+const RGB_BYTES : usize = 3;
+//bytes per pixel of the u32 channel sums motion blur accumulates
+const BLUR_SUM_BYTES : usize = 12;
+
+//x and y range of a --viewport box
+type Viewport = ((f64, f64), (f64, f64));
+//low and high orbit rate of --contrast-bounds
+type EscapeBounds = (u32, u32);
+//red, green and blue frequencies and phase of --trig-palette
+type TrigPalette = (f64, f64, f64, f64);
+//seeds of the --blend-seeds palettes
+type SeedList = Vec<u64>;
+
+
+///    Generates frames of a zoom into the burning ship fractal.
+#[derive(Parser, Debug)]
+#[clap(name = "burning_ship_fractal")]
+pub struct Args {
+    ///    Number of bursts of frames to generate, counted as 4 frames each,
+    ///    so this is equivalent to --frames BURSTS*4.
+    #[clap(
+        required_unless_present_any = &["frames", "frame-range", "show-palette", "recolor", "trace", "magnify", "viewport", "cycle-palette", "shotlist", "sample-center", "insets"],
+        parse(try_from_str = parse_bursts)
+    )]
+    pub bursts : Option<u32>,
+
+    ///    Total number of frames to render, frames 0..FRAMES. Takes
+    ///    precedence over BURSTS.
+    #[clap(long)]
+    pub frames : Option<u32>,
+
+    ///    Render only frames START..END of the animation, e.g. 300..600 to
+    ///    split a long zoom across machines. The animation keeps the length
+    ///    given by --frames or BURSTS, which END may not exceed, otherwise it
+    ///    ends at END.
+    #[clap(long, value_name = "START..END", parse(try_from_str = parse_frame_range))]
+    pub frame_range : Option<Range<u32>>,
+
+    ///    Magnification of the last frame relative to the first, e.g. 1e6.
+    ///    The zoom rate is solved from it and the number of frames.
+    #[clap(long, value_name = "MAGNIFICATION")]
+    pub target_zoom : Option<f64>,
+
+    ///    Render one frame of the starting viewport magnified MAGNIFICATION
+    ///    times, e.g. 500, instead of an animation.
+    #[clap(
+        long,
+        value_name = "MAGNIFICATION",
+        parse(try_from_str = parse_magnification),
+        conflicts_with_all = &["bursts", "frames", "frame-range", "target-zoom", "julia-sweep", "auto-center"]
+    )]
+    pub magnify : Option<f64>,
+
+    ///    Point the --magnify frame is centered on, defaults to the middle
+    ///    of the starting viewport.
+    #[clap(long, value_name = "X,Y", allow_hyphen_values = true, requires = "magnify", parse(try_from_str = parse_point))]
+    pub center : Option<(f64, f64)>,
+
+    ///    Render one frame of exactly the viewport from X0 to X1 and Y0 to
+    ///    Y1, e.g. copied from another fractal tool, instead of an
+    ///    animation.
+    #[clap(
+        long,
+        value_name = "X0,X1,Y0,Y1",
+        allow_hyphen_values = true,
+        parse(try_from_str = parse_viewport),
+        conflicts_with_all = &["bursts", "frames", "frame-range", "target-zoom", "julia-sweep", "auto-center", "magnify"]
+    )]
+    pub viewport : Option<Viewport>,
+
+    ///    Cap the number of worker threads used for rendering.
+    ///    Defaults to one per core.
+    #[clap(long)]
+    pub threads : Option<usize>,
+
+    ///    Run every frame, row and painting map one after the other on the
+    ///    main thread, for steady timings and stepping through a debugger.
+    ///    Frames come out identical to a parallel run.
+    #[clap(long, conflicts_with = "threads")]
+    pub single_thread : bool,
+
+    ///    Report the first frame of each burst, slowed by thread pool
+    ///    spin-up and cold caches, as warmup and leave it out of the frame
+    ///    time summary and slow frame warnings.
+    #[clap(long)]
+    pub separate_warmup : bool,
+
+    ///    Memory in MB the frames of a burst may hold. The number of frames
+    ///    rendered together is sized from it and the frame dimensions.
+    #[clap(long, value_name = "MB", default_value_t = 1024)]
+    pub mem_budget : usize,
+
+    ///    Time the first bursts at 1/4, 1/2 and all of the frames that fit
+    ///    --mem-budget and render the rest at the size that saved frames
+    ///    fastest.
+    #[clap(long)]
+    pub tune_bursts : bool,
+
+    ///    Benchmark the fractal math alone: build the frames and report how
+    ///    long that took, without painting or saving anything.
+    #[clap(long, alias = "no-save", conflicts_with_all = &["motion-blur", "on-frame-done"])]
+    pub compute_only : bool,
+
+    ///    After rendering, cross-dissolve COUNT in-between frames after each
+    ///    saved frame into frames/interpolated/, multiplying the frame rate
+    ///    by COUNT + 1 without computing more frames.
+    #[clap(
+        long,
+        value_name = "COUNT",
+        conflicts_with_all = &["compute-only", "npy", "heightmap", "alpha-mask"]
+    )]
+    pub interpolate : Option<NonZeroU32>,
+
+    ///    After rendering, save frames/title_card.png summarising the run:
+    ///    frame count, final magnification, iterations, highest escape and
+    ///    total time over a strip of the palette.
+    #[clap(long)]
+    pub title_card : bool,
+
+    ///    Max length of a burning_ship sequence. Frames are stored one byte
+    ///    per pixel up to 255, four bytes per pixel above that.
+    #[clap(long, default_value_t = MAX_ITERATIONS)]
+    pub max_iterations : u32,
+
+    ///    Raise max-iterations with zoom depth, adding GROWTH times
+    ///    --max-iterations for every tenfold magnification past frame 0,
+    ///    so deep frames keep their edge detail.
+    #[clap(long, value_name = "GROWTH", parse(try_from_str = parse_iteration_growth))]
+    pub iteration_growth : Option<f64>,
+
+    ///    Call orbits still inside after THRESHOLD iterations interior and
+    ///    stop iterating them, a faster preview that only blurs the edge
+    ///    of the set. Escaped pixels keep their counts and interior pixels
+    ///    stay at max-iterations, so colors do not shift.
+    #[clap(long, value_name = "THRESHOLD")]
+    pub interior_threshold : Option<u32>,
+
+    ///    Draw frames with +i pointing up, as in mathematical plots. By
+    ///    default row 0 is the low imaginary edge, which shows the ship
+    ///    upright.
+    #[clap(long)]
+    pub flip_y : bool,
+
+    ///    Only print errors.
+    #[clap(long, conflicts_with = "verbose")]
+    pub quiet : bool,
+
+    ///    Also print per-frame debug logs.
+    #[clap(long)]
+    pub verbose : bool,
+
+    ///    Keep the viewport fixed and sweep the Julia constant C once
+    ///    around the circle X,Y,RADIUS over the animation.
+    #[clap(long, value_name = "X,Y,RADIUS", allow_hyphen_values = true, parse(try_from_str = parse_circle))]
+    pub julia_sweep : Option<(f64, f64, f64)>,
+
+    ///    Start burning ship orbits at Z[0] = X,Y instead of at the pixel.
+    #[clap(long, value_name = "X,Y", allow_hyphen_values = true, parse(try_from_str = parse_point))]
+    pub z0 : Option<(f64, f64)>,
+
+    ///    Scale the real and imaginary parts of the squared term by KA and
+    ///    KB, Z = KA(Re^2 - Im^2) + |2 KB Re Im|i + C. 1,1 is the burning ship.
+    #[clap(long, value_name = "KA,KB", default_value = "1,1", allow_hyphen_values = true, parse(try_from_str = parse_point))]
+    pub coefficients : (f64, f64),
+
+    ///    Color with a built in colormap instead of random colors.
+    #[clap(long, value_name = "NAME", possible_values = &PRESET_NAMES)]
+    pub palette : Option<String>,
+
+    ///    Seed of the random palette used without --palette, as printed
+    ///    and recorded in frames/render.json by an earlier render, to get
+    ///    the same colors again.
+    #[clap(long, value_name = "SEED", conflicts_with = "palette")]
+    pub palette_seed : Option<u64>,
+
+    ///    Color with the hex list palette at PATH, one #RRGGBB line per
+    ///    color, like the frames/palette.hex --show-palette exports.
+    #[clap(long, value_name = "PATH", conflicts_with_all = &["palette", "palette-seed"])]
+    pub palette_file : Option<String>,
+
+    ///    Blend the random palettes of several seeds into one, e.g.
+    ///    "7,42,1999", for richer gradients than a single seed gives. The
+    ///    same seeds in the same order give the same colors.
+    #[clap(
+        long,
+        value_name = "SEED,SEED,...",
+        parse(try_from_str = parse_seeds),
+        conflicts_with_all = &["palette", "palette-seed", "palette-file"]
+    )]
+    pub blend_seeds : Option<SeedList>,
+
+    ///    How --blend-seeds combines its palettes: average mixes every
+    ///    band of all of them, per-band takes each band from the palettes
+    ///    in turn.
+    #[clap(long = "palette-blend", value_name = "MODE", default_value = "average", possible_values = &BLEND_NAMES, requires = "blend-seeds")]
+    pub palette_blend_name : String,
+
+    ///    Curve escape values are mapped through before they index the
+    ///    palette: linear, log, sqrt or power=P. The non-linear curves
+    ///    spread the crowded low escape values over more of the palette.
+    #[clap(long, value_name = "CURVE", default_value = "linear", parse(try_from_str = parse_palette_curve))]
+    pub palette_curve : PaletteCurve,
+
+    ///    Dither between neighbouring palette colors with a 4x4 Bayer
+    ///    pattern, by the fraction of each point's smooth escape value,
+    ///    to break up visible bands.
+    #[clap(long, conflicts_with_all = &["potential", "trig-palette", "contours"])]
+    pub dither : bool,
+
+    ///    Rotate the palette by SLOTS colors per frame, so colors flow
+    ///    through the bands over an animation. 0 keeps them still.
+    #[clap(long, value_name = "SLOTS", default_value_t = 0)]
+    pub cycle_speed : usize,
+
+    ///    Paint only the outline of the set: points where the escape
+    ///    values change by at least THRESHOLD (a Sobel gradient, try 1) in
+    ///    --edge-color over --edge-background, instead of the palette.
+    #[clap(
+        long,
+        value_name = "THRESHOLD",
+        parse(try_from_str = parse_edge_threshold),
+        conflicts_with_all = &["hybrid", "shaded", "potential", "trig-palette", "contours", "dither"]
+    )]
+    pub edges : Option<f64>,
+
+    ///    Color of the outline drawn by --edges.
+    #[clap(long, value_name = "R,G,B", default_value = "255,255,255", parse(try_from_str = parse_color))]
+    pub edge_color : Rgb<u8>,
+
+    ///    Color around the outline drawn by --edges.
+    #[clap(long, value_name = "R,G,B", default_value = "0,0,0", parse(try_from_str = parse_color))]
+    pub edge_background : Rgb<u8>,
+
+    ///    Shade each palette band by the smooth escape value, darkening
+    ///    the band by up to WEIGHT (0 to 1) towards its outer edge.
+    #[clap(long, value_name = "WEIGHT")]
+    pub hybrid : Option<f64>,
+
+    ///    Emboss the frame, lighting the smooth escape values as a height
+    ///    field from direction X,Y,Z (x right, y down, z towards the viewer).
+    #[clap(long, value_name = "X,Y,Z", allow_hyphen_values = true, parse(try_from_str = parse_vector), conflicts_with = "hybrid")]
+    pub shaded : Option<(f64, f64, f64)>,
+
+    ///    Anti-alias edges only: after one sample per pixel, pixels whose
+    ///    escape count jumps against a neighbour are sampled 4 more times.
+    #[clap(long, conflicts_with_all = &["hybrid", "shaded", "potential", "preview-scale"])]
+    pub edge_aa : bool,
+
+    ///    Reconstruction filter combining the --edge-aa samples: box
+    ///    averages 4 samples, tent and gaussian weight 16 samples by their
+    ///    distance from the pixel center for sharper edges.
+    #[clap(long = "aa-filter", value_name = "NAME", default_value = "box", possible_values = &FILTER_NAMES, requires = "edge-aa")]
+    pub aa_filter_name : String,
+
+    ///    Shift each --edge-aa sample by a random amount within its share
+    ///    of the pixel instead of keeping to a regular grid, trading edge
+    ///    aliasing for fine noise. Samples are drawn from SEED and the
+    ///    pixel, so renders with the same SEED are identical.
+    #[clap(
+        long,
+        value_name = "SEED",
+        min_values = 0,
+        max_values = 1,
+        require_equals = true,
+        default_missing_value = "0",
+        requires = "edge-aa"
+    )]
+    pub aa_jitter : Option<u64>,
+
+    ///    Average each frame over SAMPLES sub-frames spread between it and
+    ///    the next frame of the zoom, blurring the zoom motion. 1 is off.
+    #[clap(long, value_name = "SAMPLES", default_value = "1", conflicts_with = "npy")]
+    pub motion_blur : NonZeroU32,
+
+    ///    Color by the electrostatic potential ln|Z|/2^n, blending between
+    ///    neighbouring palette colors for continuous equipotential gradients.
+    #[clap(long, conflicts_with_all = &["hybrid", "shaded"])]
+    pub potential : bool,
+
+    ///    Draw topographic contour lines every K orbit rates in their
+    ///    palette color over a flat paper background.
+    #[clap(long, value_name = "K", conflicts_with_all = &["hybrid", "shaded", "potential"])]
+    pub contours : Option<NonZeroU32>,
+
+    ///    False color each pixel with one sinusoid per channel of its
+    ///    escape value v, sin(FREQ v + PHASE) mapped to 0..255, instead of
+    ///    a palette. --cycle-palette turns the phase once over its frames.
+    #[clap(
+        long,
+        value_name = "R_FREQ,G_FREQ,B_FREQ,PHASE",
+        allow_hyphen_values = true,
+        parse(try_from_str = parse_trig_palette),
+        conflicts_with_all = &["hybrid", "shaded", "potential", "contours"]
+    )]
+    pub trig_palette : Option<TrigPalette>,
+
+    ///    Blend and darken colors of --hybrid, --shaded and --potential in
+    ///    linear light, decoding the palette with display gamma GAMMA (2.2,
+    ///    close to sRGB, when given as plain --gamma) and encoding the result
+    ///    back, so mid-gradients are not too dark.
+    #[clap(
+        long,
+        value_name = "GAMMA",
+        min_values = 0,
+        max_values = 1,
+        require_equals = true,
+        default_missing_value = "2.2",
+        parse(try_from_str = parse_gamma)
+    )]
+    pub gamma : Option<f64>,
+
+    ///    Stretch the orbit rates of each frame, from the lowest to the
+    ///    highest escaping one, over the whole palette.
+    #[clap(long, conflicts_with = "potential")]
+    pub contrast_stretch : bool,
+
+    ///    Stretch the orbit rates from LOW to HIGH over the whole palette in
+    ///    every frame, so colors stay put across an animation unlike
+    ///    --contrast-stretch.
+    #[clap(
+        long,
+        value_name = "LOW..HIGH",
+        parse(try_from_str = parse_escape_bounds),
+        conflicts_with_all = &["potential", "contrast-stretch"]
+    )]
+    pub contrast_bounds : Option<EscapeBounds>,
+
+    ///    Quick look: compute frames at 1/FACTOR of the output resolution
+    ///    and bilinearly upscale them instead of computing every pixel.
+    #[clap(long, value_name = "FACTOR")]
+    pub preview_scale : Option<NonZeroU32>,
+
+    ///    Build each frame of a burst from the one before it: cells in flat
+    ///    areas of the previous frame are copied and only cells near detail
+    ///    are iterated. hybrid checks 3x3 neighbourhoods and keeps edges
+    ///    exact, fast checks 2x2 and reuses more. The first frame of every
+    ///    burst is rendered in full. Escape count frames only.
+    #[clap(
+        long = "reproject",
+        value_name = "QUALITY",
+        possible_values = &QUALITY_NAMES,
+        conflicts_with_all = &["preview-scale", "edge-aa", "tensor", "motion-blur"]
+    )]
+    pub reproject_name : Option<String>,
+
+    ///    Iterate MAP over the same viewport as the burning ship and paint
+    ///    the mix of the two escape counts, weighted by --blend-weight.
+    ///    Escape count coloring only.
+    #[clap(
+        long = "blend-map",
+        value_name = "MAP",
+        possible_values = &MAP_NAMES,
+        conflicts_with_all = &["hybrid", "shaded", "potential", "preview-scale", "edge-aa", "tensor", "reproject-name"]
+    )]
+    pub blend_map_name : Option<String>,
+
+    ///    Share of --blend-map in the blend, 0 giving the burning ship
+    ///    alone and 1 the other map alone.
+    #[clap(long, value_name = "WEIGHT", default_value_t = 0.5, parse(try_from_str = parse_weight), requires = "blend-map-name")]
+    pub blend_weight : f64,
+
+    ///    Paint the main body of the ship (the largest connected interior
+    ///    region) in this color, apart from smaller satellite interiors.
+    #[clap(long, value_name = "R,G,B", parse(try_from_str = parse_color))]
+    pub body_color : Option<Rgb<u8>>,
+
+    ///    Color of points that never escape.
+    #[clap(long, value_name = "R,G,B", default_value = "0,0,0", parse(try_from_str = parse_color))]
+    pub interior_color : Rgb<u8>,
+
+    ///    Paint points escaping in fewer than FLOOR iterations in
+    ///    --floor-color, cleaning up speckle in the far exterior.
+    #[clap(long, value_name = "FLOOR", default_value_t = 0)]
+    pub escape_floor : u32,
+
+    ///    Color of points below --escape-floor.
+    #[clap(long, value_name = "R,G,B", default_value = "0,0,0", parse(try_from_str = parse_color))]
+    pub floor_color : Rgb<u8>,
+
+    ///    Shade points that never escape with a built in colormap by their
+    ///    distance to the nearest escaping point, instead of interior-color.
+    #[clap(long, value_name = "NAME", possible_values = &PRESET_NAMES)]
+    pub interior_palette : Option<String>,
+
+    ///    Fade frames into interior-color past RADIUS from the center, over
+    ///    a band SOFTNESS wide, for a telescope-view look. Both run from 0
+    ///    at the center to 1 at the corners.
+    #[clap(long, value_name = "RADIUS,SOFTNESS", parse(try_from_str = parse_vignette))]
+    pub vignette : Option<Vignette>,
+
+    ///    Overlay complex-plane coordinate ticks and a scale bar.
+    #[clap(long)]
+    pub annotate : bool,
+
+    ///    Stamp the frame number and magnification into the top left
+    ///    corner of every frame.
+    #[clap(long)]
+    pub stamp : bool,
+
+    ///    Save each frame's raw escape grid as frames/NNNNNNNNNN.npy (uint8,
+    ///    uint32 or float64 for smooth and preview grids) instead of a PNG.
+    #[clap(long)]
+    pub npy : bool,
+
+    ///    With --npy, save a float32 tensor of shape (height, width, 3) per
+    ///    frame instead: the orbit rate and smooth escape value, both over
+    ///    max_iterations, and an interior mask, all from the same orbit.
+    #[clap(long, requires = "npy", conflicts_with_all = &["edge-aa", "preview-scale"])]
+    pub tensor : bool,
+
+    ///    Save each frame's smooth escape values as a 16-bit grayscale PNG
+    ///    heightmap, stretched over the full range, instead of painting it.
+    #[clap(long, conflicts_with_all = &["npy", "recolor", "motion-blur", "annotate", "stamp"])]
+    pub heightmap : bool,
+
+    ///    Save frames as RGBA with the interior opaque and escaped pixels
+    ///    transparent, fading in over the last SOFT_EDGE iterations before
+    ///    the interior. 0 gives a hard edge.
+    #[clap(long, value_name = "SOFT_EDGE", conflicts_with_all = &["npy", "heightmap", "motion-blur"])]
+    pub alpha_mask : Option<f64>,
+
+    ///    Encode PNGs with the fastest compression, for quick preview batches.
+    #[clap(long, conflicts_with = "png-small")]
+    pub png_fast : bool,
+
+    ///    Encode PNGs with the strongest compression, for final delivery.
+    #[clap(long)]
+    pub png_small : bool,
+
+    ///    Make the interior transparent and the exterior opaque instead.
+    #[clap(long, requires = "alpha-mask")]
+    pub invert_mask : bool,
+
+    ///    Re-render from the render config recorded in a previous run's
+    ///    sidecar JSON (frames/render.json) instead of the config flags.
+    ///    Its random palette (seed or blend seeds) is reused too, unless a
+    ///    --palette* or --palette-seed flag picks other colors.
+    #[clap(long, value_name = "FILE")]
+    pub from : Option<String>,
+
+    ///    Render the shots listed in FILE back to back as one frame
+    ///    sequence. Each line is "CONFIG FRAMES": a sidecar JSON like
+    ///    --from takes, relative to FILE, and the number of frames to render
+    ///    from it. Shots keep their recorded random palette unless a
+    ///    --palette* or --palette-seed flag picks one, painting flags apply
+    ///    to every shot.
+    #[clap(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = &["from", "npy", "heightmap", "recolor", "thumbnail-gif", "difference", "cycle-palette", "motion-blur", "edge-aa", "preview-scale", "blend-map-name", "reproject-name", "tensor", "interpolate", "title-card"]
+    )]
+    pub shotlist : Option<String>,
+
+    ///    Paint the .npy escape grids saved by --npy in DIR again with a
+    ///    new palette and coloring flags, writing PNGs to frames/ without
+    ///    recomputing anything. The render config is read from DIR's
+    ///    sidecar unless --from is given.
+    #[clap(long, value_name = "DIR", conflicts_with = "npy")]
+    pub recolor : Option<String>,
+
+    ///    Compute only half of frames that are exactly mirror symmetric
+    ///    (Julia sets centered on Re = 0) and mirror the other half.
+    ///    The burning ship itself is not symmetric and is computed in full.
+    #[clap(long)]
+    pub exploit_symmetry : bool,
+
+    ///    Iterate orbits in f32 instead of f64. Faster on shallow zooms but
+    ///    turns blocky after a few hundred frames of the default zoom.
+    #[clap(long)]
+    pub f32 : bool,
+
+    ///    Iterate orbits in double-double arithmetic (about 32 digits)
+    ///    instead of f64. Roughly 7x slower, but zooms about 1e16 times
+    ///    deeper before turning blocky. Always renders on the CPU.
+    #[clap(long, conflicts_with = "f32")]
+    pub double_double : bool,
+
+    ///    Turn the viewport DEGREES counter-clockwise about the zoom center.
+    #[clap(long, value_name = "DEGREES", default_value_t = 0.0, allow_hyphen_values = true, conflicts_with = "annotate")]
+    pub rotate : f64,
+
+    ///    Scout the starting viewport with a coarse render and zoom towards
+    ///    its most detailed region instead of its center.
+    #[clap(long)]
+    pub auto_center : bool,
+
+    ///    Probe the starting viewport with a coarse render and set
+    ///    max_iterations so 99% of its escaping pixels escape before the
+    ///    cap. Overrides --max-iterations.
+    #[clap(long)]
+    pub auto_iter : bool,
+
+    ///    Print the orbit of the point X,Y of the first frame's map, every Z
+    ///    until it escapes, plot it to frames/orbit.png and exit.
+    #[clap(long, value_name = "X,Y", allow_hyphen_values = true, parse(try_from_str = parse_point))]
+    pub trace : Option<(f64, f64)>,
+
+    ///    Save the palette as a strip of color bands to frames/palette.png
+    ///    and as a hex list to frames/palette.hex, and exit without
+    ///    rendering.
+    #[clap(long)]
+    pub show_palette : bool,
+
+    ///    Render every EVERY-th frame of the animation at thumbnail size into
+    ///    frames/zoom.gif and exit, a quick look at the whole zoom path.
+    #[clap(long, value_name = "EVERY")]
+    pub thumbnail_gif : Option<NonZeroU32>,
+
+    ///    Render COUNT evenly spaced frames of the animation at thumbnail
+    ///    size side by side into frames/strip.png, shallow to deep, and
+    ///    exit. A one image summary of the whole zoom.
+    #[clap(long, value_name = "COUNT", conflicts_with = "thumbnail-gif")]
+    pub strip : Option<NonZeroU32>,
+
+    ///    Draw the starting viewport and the nested viewports of COUNT
+    ///    evenly spaced frames, labelled with their magnification, into
+    ///    frames/viewport.svg and exit. A vector overview of the zoom path.
+    #[clap(long, value_name = "COUNT")]
+    pub viewport_svg : Option<NonZeroU32>,
+
+    ///    Instead of the frames themselves, save frame k as a heatmap of how
+    ///    much each escape value changes from frame k to frame k+1.
+    #[clap(long, conflicts_with_all = &["npy", "recolor", "thumbnail-gif"])]
+    pub difference : bool,
+
+    ///    Render the first frame once and save FRAMES copies of it with the
+    ///    palette rotated a little further each time, one full turn over
+    ///    all of them, for a looping color-cycling animation.
+    #[clap(
+        long,
+        value_name = "FRAMES",
+        conflicts_with_all = &["npy", "heightmap", "recolor", "thumbnail-gif", "difference", "motion-blur"]
+    )]
+    pub cycle_palette : Option<NonZeroU32>,
+
+    ///    Run the shell command CMD after each rendered frame is saved, with
+    ///    the frame number as $1 and the saved file as $2, e.g. to upload
+    ///    frames while the rest of the sequence renders.
+    #[clap(
+        long,
+        value_name = "CMD",
+        conflicts_with_all = &["trace", "show-palette", "thumbnail-gif", "difference", "cycle-palette", "shotlist", "recolor"]
+    )]
+    pub on_frame_done : Option<String>,
+
+    ///    Render only frame 0 with a crosshair on the point the animation
+    ///    zooms towards, the configured center or the one --auto-center
+    ///    finds, to check the target before a long render.
+    #[clap(
+        long,
+        conflicts_with_all = &["npy", "heightmap", "trace", "show-palette", "thumbnail-gif", "difference", "cycle-palette", "shotlist", "recolor"]
+    )]
+    pub sample_center : bool,
+
+    ///    Render only frame 0 with LEVELS (1 or 2) nested insets in the
+    ///    bottom right corner, each a view of the zoom target magnified
+    ///    --inset-zoom times more than the view around it.
+    #[clap(
+        long,
+        value_name = "LEVELS",
+        possible_values = &["1", "2"],
+        conflicts_with_all = &["npy", "heightmap", "trace", "show-palette", "thumbnail-gif", "difference", "cycle-palette", "shotlist", "recolor", "sample-center"]
+    )]
+    pub insets : Option<u32>,
+
+    ///    Magnification of each --insets inset relative to the view it
+    ///    sits in.
+    #[clap(long, value_name = "MAGNIFICATION", default_value_t = 10.0, parse(try_from_str = parse_magnification), requires = "insets")]
+    pub inset_zoom : f64,
+
+    ///    Size frames for printing, e.g. 8x10in@300dpi renders 2400x3000
+    ///    pixels and records 300 dpi in the PNGs.
+    #[clap(long, value_name = "WxHin@DPIdpi", parse(try_from_str = parse_print_size))]
+    pub print : Option<PrintSize>,
+}
+
+
+///    ### (PURE)
+///    Parses comma separated floats, e.g. "-1.7,0.03".
+fn parse_floats(arg : &str) -> Result<Vec<f64>, String> {
+    arg
+    .split(',')
+    .map(|part| part.trim().parse::<f64>().map_err(|err| format!("'{}': {}", part, err)))
+    .collect()
+}
+
+
+///    ### (PURE)
+///    Parses comma separated palette seeds, e.g. "7,42,1999".
+fn parse_seeds(arg : &str) -> Result<SeedList, String> {
+    arg.split(',')
+        .map(|seed| seed.trim().parse::<u64>().map_err(|err| format!("invalid seed '{}': {}", seed, err)))
+        .collect()
+}
+
+///    ### (PURE)
+///    Parses "X0,X1,Y0,Y1" into an x and a y range, each running from
+///    low to high.
+fn parse_viewport(arg : &str) -> Result<Viewport, String> {
+    match parse_floats(arg)?.as_slice() {
+        [x0, x1, y0, y1] if x0 < x1 && y0 < y1 => Ok(((*x0, *x1), (*y0, *y1))),
+        [_, _, _, _] => Err(format!("expected X0 < X1 and Y0 < Y1, got '{}'", arg)),
+        _ => Err(format!("expected X0,X1,Y0,Y1, got '{}'", arg)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses "R_FREQ,G_FREQ,B_FREQ,PHASE".
+fn parse_trig_palette(arg : &str) -> Result<TrigPalette, String> {
+    match parse_floats(arg)?.as_slice() {
+        [r_freq, g_freq, b_freq, phase] => Ok((*r_freq, *g_freq, *b_freq, *phase)),
+        _ => Err(format!("expected R_FREQ,G_FREQ,B_FREQ,PHASE, got '{}'", arg)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses "RADIUS,SOFTNESS", neither below 0.
+fn parse_vignette(arg : &str) -> Result<Vignette, String> {
+    match parse_floats(arg)?.as_slice() {
+        [radius, softness] if *radius >= 0.0 && *softness >= 0.0 => Ok(Vignette { radius : *radius, softness : *softness }),
+        [_, _] => Err(format!("expected RADIUS and SOFTNESS of at least 0, got '{}'", arg)),
+        _ => Err(format!("expected RADIUS,SOFTNESS, got '{}'", arg)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses "X,Y,RADIUS".
+fn parse_circle(arg : &str) -> Result<(f64, f64, f64), String> {
+    match parse_floats(arg)?.as_slice() {
+        [x, y, radius] => Ok((*x, *y, *radius)),
+        _ => Err(format!("expected X,Y,RADIUS, got '{}'", arg)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses "X,Y,Z".
+fn parse_vector(arg : &str) -> Result<(f64, f64, f64), String> {
+    match parse_floats(arg)?.as_slice() {
+        [x, y, z] => Ok((*x, *y, *z)),
+        _ => Err(format!("expected X,Y,Z, got '{}'", arg)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses "X,Y".
+fn parse_point(arg : &str) -> Result<(f64, f64), String> {
+    match parse_floats(arg)?.as_slice() {
+        [x, y] => Ok((*x, *y)),
+        _ => Err(format!("expected X,Y, got '{}'", arg)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses a magnification, a finite number above 0.
+fn parse_magnification(arg : &str) -> Result<f64, String> {
+    match arg.trim().parse::<f64>() {
+        Ok(magnify) if magnify.is_finite() && magnify > 0.0 => Ok(magnify),
+        Ok(_) => Err(format!("expected a magnification above 0, got '{}'", arg)),
+        Err(err) => Err(format!("'{}': {}", arg, err)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses an iteration growth, a finite number of at least 0.
+fn parse_iteration_growth(arg : &str) -> Result<f64, String> {
+    match arg.trim().parse::<f64>() {
+        Ok(growth) if growth.is_finite() && growth >= 0.0 => Ok(growth),
+        Ok(_) => Err(format!("expected a growth of at least 0, got '{}'", arg)),
+        Err(err) => Err(format!("'{}': {}", arg, err)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses an edge threshold, a finite number above 0.
+fn parse_edge_threshold(arg : &str) -> Result<f64, String> {
+    match arg.trim().parse::<f64>() {
+        Ok(threshold) if threshold.is_finite() && threshold > 0.0 => Ok(threshold),
+        Ok(_) => Err(format!("expected a threshold above 0, got '{}'", arg)),
+        Err(err) => Err(format!("'{}': {}", arg, err)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses a palette curve, see PaletteCurve::from_name.
+fn parse_palette_curve(arg : &str) -> Result<PaletteCurve, String> {
+    PaletteCurve::from_name(arg.trim())
+        .ok_or_else(|| format!("expected linear, log, sqrt or power=P with P above 0, got '{}'", arg))
+}
+
+
+///    ### (PURE)
+///    Parses a blend weight, a number from 0 to 1.
+fn parse_weight(arg : &str) -> Result<f64, String> {
+    match arg.trim().parse::<f64>() {
+        Ok(weight) if (0.0..=1.0).contains(&weight) => Ok(weight),
+        Ok(_) => Err(format!("expected a weight from 0 to 1, got '{}'", arg)),
+        Err(err) => Err(format!("'{}': {}", arg, err)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses a display gamma, a finite number above 0.
+fn parse_gamma(arg : &str) -> Result<f64, String> {
+    match arg.trim().parse::<f64>() {
+        Ok(gamma) if gamma.is_finite() && gamma > 0.0 => Ok(gamma),
+        Ok(_) => Err(format!("expected a gamma above 0, got '{}'", arg)),
+        Err(err) => Err(format!("'{}': {}", arg, err)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses "R,G,B" with channels 0 to 255.
+fn parse_color(arg : &str) -> Result<Rgb<u8>, String> {
+    let channels = arg
+        .split(',')
+        .map(|part| part.trim().parse::<u8>().map_err(|err| format!("'{}': {}", part, err)))
+        .collect::<Result<Vec<u8>, String>>()?;
+    match channels.as_slice() {
+        [r, g, b] => Ok(Rgb([*r, *g, *b])),
+        _ => Err(format!("expected R,G,B, got '{}'", arg)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses "START..END" with START < END.
+fn parse_frame_range(arg : &str) -> Result<Range<u32>, String> {
+    let invalid = || format!("expected START..END, got '{}'", arg);
+    let (start, end) = arg.split_once("..").ok_or_else(invalid)?;
+    let start : u32 = start.trim().parse().map_err(|_| invalid())?;
+    let end : u32 = end.trim().parse().map_err(|_| invalid())?;
+    match start < end {
+        true => Ok(start..end),
+        false => Err(format!("START must be below END, got '{}'", arg)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses a burst count, rejecting counts whose CHUNK_SIZE frames
+///    each would not fit a u32 frame number.
+fn parse_bursts(arg : &str) -> Result<u32, String> {
+    let bursts : u32 = arg.trim().parse().map_err(|err| format!("'{}': {}", arg, err))?;
+    match bursts.checked_mul(CHUNK_SIZE) {
+        Some(_) => Ok(bursts),
+        None => Err(format!("at most {} bursts of {} frames fit the frame numbers, got {}", u32::MAX / CHUNK_SIZE, CHUNK_SIZE, bursts)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses "LOW..HIGH" orbit rates, LOW below HIGH.
+fn parse_escape_bounds(arg : &str) -> Result<EscapeBounds, String> {
+    let invalid = || format!("expected LOW..HIGH, got '{}'", arg);
+    let (low, high) = arg.split_once("..").ok_or_else(invalid)?;
+    let low : u32 = low.trim().parse().map_err(|_| invalid())?;
+    let high : u32 = high.trim().parse().map_err(|_| invalid())?;
+    match low < high {
+        true => Ok((low, high)),
+        false => Err(format!("LOW must be below HIGH, got '{}'", arg)),
+    }
+}
+
+
+///    ### (PURE)
+///    Parses "WxHin@DPIdpi", e.g. "8x10in@300dpi".
+fn parse_print_size(arg : &str) -> Result<PrintSize, String> {
+    let invalid = || format!("expected WxHin@DPIdpi, got '{}'", arg);
+    let (size, dpi) = arg.split_once('@').ok_or_else(invalid)?;
+    let (width, height) = size.strip_suffix("in").and_then(|size| size.split_once('x')).ok_or_else(invalid)?;
+    let dpi = dpi.strip_suffix("dpi").ok_or_else(invalid)?;
+    let print = PrintSize {
+        width_inches : width.trim().parse().map_err(|_| invalid())?,
+        height_inches : height.trim().parse().map_err(|_| invalid())?,
+        dpi : dpi.trim().parse().map_err(|_| invalid())?,
+    };
+    let positive = print.width_inches > 0.0 && print.height_inches > 0.0 && print.dpi > 0;
+    match positive && print.width_inches.is_finite() && print.height_inches.is_finite() {
+        true => Ok(print),
+        false => Err(format!("print size and dpi must be positive, got '{}'", arg)),
+    }
+}
+
+
+impl Args {
+    ///    ### (PURE)
+    ///    Checks clap cannot express across arguments: --frame-range must
+    ///    end within the frames given by --frames or BURSTS.
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.frame_range {
+            Some(range) if range.end > self.total_frames() => Err(format!(
+                "--frame-range {}..{} ends past the {} frames of the animation",
+                range.start,
+                range.end,
+                self.total_frames()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    ///    ### (PURE)
+    ///    Number of frames in the animation, frames 0..total_frames. A
+    ///    --magnify or --viewport render is the single frame 0.
+    pub fn total_frames(&self) -> u32 {
+        if self.magnify.is_some() || self.viewport.is_some() {
+            return 1;
+        }
+        self.frames
+        .or(self.bursts.map(|bursts| bursts * CHUNK_SIZE))
+        .or(self.frame_range.as_ref().map(|range| range.end))
+        .unwrap_or(0)
+    }
+
+    ///    ### (PURE)
+    ///    Reprojection quality picked by --reproject, None renders every
+    ///    frame in full.
+    pub fn reproject(&self) -> Option<ReprojectQuality> {
+        self.reproject_name.as_deref().map(|name|{
+            ReprojectQuality::from_name(name).expect("clap restricts --reproject to QUALITY_NAMES")
+        })
+    }
+
+    ///    ### (PURE)
+    ///    Outline style picked by --edges, None paints with the palette.
+    pub fn edge_style(&self) -> Option<EdgeStyle> {
+        self.edges.map(|threshold| EdgeStyle {
+            threshold,
+            edge_color : self.edge_color,
+            background : self.edge_background,
+        })
+    }
+
+    ///    ### (PURE)
+    ///    Map picked by --blend-map, None renders the burning ship alone.
+    pub fn blend_map(&self) -> Option<BuiltinMap> {
+        self.blend_map_name.as_deref().map(|name|{
+            BuiltinMap::from_name(name).expect("clap restricts --blend-map to MAP_NAMES")
+        })
+    }
+
+    ///    ### (PURE)
+    ///    Blend mode named by --palette-blend.
+    pub fn palette_blend(&self) -> BlendMode {
+        BlendMode::from_name(&self.palette_blend_name).expect("clap restricts --palette-blend to BLEND_NAMES")
+    }
+
+    ///    ### (PURE)
+    ///    Reconstruction filter named by --aa-filter.
+    pub fn aa_filter(&self) -> ReconstructionFilter {
+        ReconstructionFilter::from_name(&self.aa_filter_name).expect("clap restricts --aa-filter to FILTER_NAMES")
+    }
+
+    ///    ### (PURE)
+    ///    Contrast stretch picked by --contrast-stretch or
+    ///    --contrast-bounds.
+    pub fn contrast(&self) -> Option<ContrastStretch> {
+        match (self.contrast_stretch, self.contrast_bounds) {
+            (_, Some((low, high))) => Some(ContrastStretch::Fixed { low, high }),
+            (true, None) => Some(ContrastStretch::PerFrame),
+            (false, None) => None,
+        }
+    }
+
+    ///    ### (PURE)
+    ///    PNG compression picked by --png-fast or --png-small.
+    pub fn png_compression(&self) -> PngCompression {
+        match (self.png_fast, self.png_small) {
+            (true, _) => PngCompression::Fast,
+            (_, true) => PngCompression::Best,
+            _ => PngCompression::Default,
+        }
+    }
+
+    ///    ### (PURE)
+    ///    Frames to render, --frame-range or else the whole animation.
+    pub fn frame_range(&self) -> Range<u32> {
+        self.frame_range.clone().unwrap_or(0..self.total_frames())
+    }
+
+    ///    ### (PURE)
+    ///    Number of frames of config to render and hold in memory at once,
+    ///    as many as fit mem_budget at cell_bytes per grid cell plus the
+    ///    painted buffer (and motion blur sums), at least 1.
+    pub fn burst_size(&self, config : &RenderConfig, cell_bytes : usize) -> u32 {
+        let blur_bytes = if config.motion_blur_samples > 1 { BLUR_SUM_BYTES } else { 0 };
+        let frame_bytes = config.img_width * config.img_height * (cell_bytes + RGB_BYTES + blur_bytes);
+        let budget_bytes = self.mem_budget.saturating_mul(1024 * 1024);
+        (budget_bytes / frame_bytes.max(1)).clamp(1, u32::MAX as usize) as u32
+    }
+
+    ///    ### (PURE)
+    ///    Builds the render config described by the flags.
+    pub fn render_config(&self) -> RenderConfig {
+        let animation = match self.julia_sweep {
+            Some((x, y, radius)) => AnimationMode::JuliaSweep {
+                path : JuliaPath::Circle {
+                    center : (x, y),
+                    radius,
+                    frames : self.total_frames(),
+                },
+            },
+            None => AnimationMode::Zoom,
+        };
+        let defaults = RenderConfig::default();
+        let (img_width, img_height) = self.print
+            .map(|print| print.pixel_dimensions())
+            .unwrap_or((defaults.img_width, defaults.img_height));
+        let (starting_x_range, starting_y_range) = match self.magnify {
+            Some(magnify) => calc_magnified_ranges(
+                defaults.starting_x_range,
+                defaults.starting_y_range,
+                magnify,
+                self.center.unwrap_or((
+                    (defaults.starting_x_range.0 + defaults.starting_x_range.1) / 2.0,
+                    (defaults.starting_y_range.0 + defaults.starting_y_range.1) / 2.0,
+                ))
+            ),
+            None => self.viewport.unwrap_or((defaults.starting_x_range, defaults.starting_y_range)),
+        };
+        RenderConfig {
+            img_width,
+            img_height,
+            zoom_rate : self.target_zoom
+                .map(|target| zoom_rate_for_magnification(target, self.total_frames()))
+                .unwrap_or(defaults.zoom_rate),
+            max_iterations : self.max_iterations,
+            iteration_growth : self.iteration_growth,
+            interior_threshold : self.interior_threshold,
+            flip_y : self.flip_y,
+            z0 : self.z0,
+            exploit_symmetry : self.exploit_symmetry,
+            rotation : self.rotate.to_radians(),
+            motion_blur_samples : self.motion_blur.get(),
+            coefficients : self.coefficients,
+            precision : match (self.f32, self.double_double) {
+                (true, _) => Precision::F32,
+                (_, true) => Precision::DoubleDouble,
+                _ => Precision::F64,
+            },
+            animation,
+            starting_x_range,
+            starting_y_range,
+            ..defaults
+        }
+    }
+
+    ///    ### (PURE)
+    ///    Coloring mode selected by the flags.
+    pub fn coloring(&self) -> ColoringMode {
+        match (self.hybrid, self.shaded) {
+            (Some(weight), _) => ColoringMode::Hybrid { weight },
+            (None, Some(light_dir)) => ColoringMode::Shaded { light_dir },
+            (None, None) if self.potential => ColoringMode::Potential,
+            (None, None) => match (self.contours, self.trig_palette) {
+                (Some(interval), _) => ColoringMode::Contours { interval : interval.get() },
+                (None, Some((r_freq, g_freq, b_freq, phase))) => ColoringMode::TrigPalette { r_freq, g_freq, b_freq, phase },
+                (None, None) => ColoringMode::Bands,
+            },
+        }
+    }
+}
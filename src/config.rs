@@ -0,0 +1,359 @@
+//!   Module contains the render configuration shared by
+//!   the frame generator and validation of its parameters.
+//!   @author Van Gouache
+
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::burning_ship_frac::{frame_ranges, FractalKind, OrbitParams, Precision, Range, DEFAULT_COEFFICIENTS, MAX_ITERATIONS};
+
+
+///    Parameters describing a zoom animation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderConfig {
+    pub img_width : usize,
+    pub img_height : usize,
+    pub starting_x_range : Range,
+    pub starting_y_range : Range,
+    ///    Per-frame scale applied to the viewport, must lie in (0, 1).
+    pub zoom_rate : f64,
+    ///    Max length of a burning_ship sequence before a pixel counts as
+    ///    interior.
+    pub max_iterations : u32,
+    ///    Map iterated by Zoom animations.
+    pub kind : FractalKind,
+    ///    Seed point Z\[0\] of BurningShip orbits, None starts at the pixel.
+    pub z0 : Option<(f64, f64)>,
+    ///    Point the animation zooms towards, None zooms into the center of
+    ///    the starting viewport.
+    #[serde(default)]
+    pub zoom_center : Option<(f64, f64)>,
+    ///    Angle in radians the viewport is turned by about the zoom center,
+    ///    counter-clockwise. 0.0 keeps it axis aligned.
+    #[serde(default)]
+    pub rotation : f64,
+    ///    Coefficients (k_a, k_b) of the generalized map, see
+    ///    OrbitParams::coefficients.
+    #[serde(default = "default_coefficients")]
+    pub coefficients : (f64, f64),
+    ///    Sub-frames averaged into each frame of a zoom, 1 turns motion
+    ///    blur off. See motion_blur::render_blurred_frame.
+    #[serde(default = "default_motion_blur_samples")]
+    pub motion_blur_samples : u32,
+    ///    Float type orbits are iterated in, see Precision.
+    #[serde(default)]
+    pub precision : Precision,
+    ///    See OrbitParams::exploit_symmetry.
+    #[serde(default)]
+    pub exploit_symmetry : bool,
+    ///    Multiples of max_iterations added per tenfold magnification past
+    ///    frame 0, see max_iterations_at. None keeps max_iterations
+    ///    constant.
+    #[serde(default)]
+    pub iteration_growth : Option<f64>,
+    ///    See OrbitParams::interior_threshold. None iterates every orbit
+    ///    up to max_iterations.
+    #[serde(default)]
+    pub interior_threshold : Option<u32>,
+    ///    Draw frames with +i pointing up, as in mathematical plots, see
+    ///    OrbitParams::flip_y. false keeps row 0 at the low imaginary edge.
+    #[serde(default)]
+    pub flip_y : bool,
+    pub animation : AnimationMode,
+}
+
+
+///    What changes from one frame of the animation to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum AnimationMode {
+    ///    The viewport shrinks by zoom_rate every frame.
+    #[default]
+    Zoom,
+    ///    The viewport stays at the starting ranges while the Julia
+    ///    constant C follows path, morphing the set.
+    JuliaSweep { path : JuliaPath },
+}
+
+
+///    Path traced by the Julia constant during a JuliaSweep.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum JuliaPath {
+    ///    C goes once around the circle every `frames` frames.
+    Circle { center : (f64, f64), radius : f64, frames : u32 },
+}
+
+impl JuliaPath {
+    ///    ### (PURE)
+    ///    Julia constant C at frame_number.
+    pub fn c_at(&self, frame_number : u32) -> (f64, f64) {
+        match self {
+            JuliaPath::Circle { center, radius, frames } => {
+                let angle = std::f64::consts::TAU * frame_number as f64 / *frames as f64;
+                (center.0 + radius * angle.cos(), center.1 + radius * angle.sin())
+            }
+        }
+    }
+}
+
+///    Physical print size of a frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrintSize {
+    pub width_inches : f64,
+    pub height_inches : f64,
+    pub dpi : u32,
+}
+
+impl PrintSize {
+    ///    ### (PURE)
+    ///    Pixel width and height of the print at its dpi, at least 1x1.
+    pub fn pixel_dimensions(&self) -> (usize, usize) {
+        let pixels = |inches : f64| ((inches * self.dpi as f64).round() as usize).max(1);
+        (pixels(self.width_inches), pixels(self.height_inches))
+    }
+}
+
+
+fn default_motion_blur_samples() -> u32 {
+    1
+}
+
+fn default_coefficients() -> (f64, f64) {
+    DEFAULT_COEFFICIENTS
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        RenderConfig {
+            img_width : 4000,
+            img_height : 2300,
+            starting_x_range : (-3.45, 0.05),
+            starting_y_range : (-0.99, 0.99),
+            zoom_rate : 0.96,
+            max_iterations : MAX_ITERATIONS,
+            kind : FractalKind::default(),
+            z0 : None,
+            zoom_center : None,
+            rotation : 0.0,
+            coefficients : DEFAULT_COEFFICIENTS,
+            motion_blur_samples : 1,
+            precision : Precision::default(),
+            exploit_symmetry : false,
+            iteration_growth : None,
+            interior_threshold : None,
+            flip_y : false,
+            animation : AnimationMode::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    InvalidZoomRate(f64),
+    ZeroMaxIterations,
+    InvalidJuliaPath,
+    NonFiniteSeed,
+    NonFiniteRotation,
+    ZeroMotionBlurSamples,
+    NonFiniteCoefficients,
+    InvalidIterationGrowth(f64),
+    ZeroInteriorThreshold,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::InvalidZoomRate(rate) => write!(
+                f,
+                "zoom_rate must satisfy 0.0 < zoom_rate < 1.0 for a zoom-in animation, got {}",
+                rate
+            ),
+            ConfigError::ZeroMaxIterations => write!(
+                f,
+                "max_iterations must be at least 1"
+            ),
+            ConfigError::InvalidJuliaPath => write!(
+                f,
+                "julia sweep needs a finite center and radius and at least 1 frame per loop"
+            ),
+            ConfigError::NonFiniteSeed => write!(
+                f,
+                "z0 must be a finite point"
+            ),
+            ConfigError::NonFiniteRotation => write!(
+                f,
+                "rotation must be a finite angle"
+            ),
+            ConfigError::ZeroMotionBlurSamples => write!(
+                f,
+                "motion_blur_samples must be at least 1"
+            ),
+            ConfigError::NonFiniteCoefficients => write!(
+                f,
+                "coefficients must be finite"
+            ),
+            ConfigError::InvalidIterationGrowth(growth) => write!(
+                f,
+                "iteration_growth must be a finite number of at least 0, got {}",
+                growth
+            ),
+            ConfigError::ZeroInteriorThreshold => write!(
+                f,
+                "interior_threshold must be at least 1"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+
+///    ### (PURE)
+///    Checks that zoom_rate shrinks the viewport every frame.
+///    A rate >= 1.0 zooms out (or stands still) forever and a rate <= 0.0
+///    flips or collapses the viewport in calc_zoomed_ranges.
+pub fn validate_zoom_rate(zoom_rate : f64) -> Result<(), ConfigError> {
+    if zoom_rate > 0.0 && zoom_rate < 1.0 {
+        Ok(())
+    } else {
+        Err(ConfigError::InvalidZoomRate(zoom_rate))
+    }
+}
+
+///    ### (PURE)
+///    Solves for the zoom_rate that magnifies the last of frames frames,
+///    frame frames - 1, target times, rate = target^(-1 / (frames - 1)).
+///    A target <= 1.0 gives a rate validate_zoom_rate rejects.
+pub fn zoom_rate_for_magnification(target : f64, frames : u32) -> f64 {
+    let steps = frames.saturating_sub(1).max(1) as f64;
+    target.powf(-1.0 / steps)
+}
+
+impl RenderConfig {
+    ///    ### (PURE)
+    ///    Validates every parameter of the config.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        validate_zoom_rate(self.zoom_rate)?;
+        if self.max_iterations == 0 {
+            return Err(ConfigError::ZeroMaxIterations);
+        }
+        if self.motion_blur_samples == 0 {
+            return Err(ConfigError::ZeroMotionBlurSamples);
+        }
+        if !self.coefficients.0.is_finite() || !self.coefficients.1.is_finite() {
+            return Err(ConfigError::NonFiniteCoefficients);
+        }
+        if !self.rotation.is_finite() {
+            return Err(ConfigError::NonFiniteRotation);
+        }
+        if self.interior_threshold == Some(0) {
+            return Err(ConfigError::ZeroInteriorThreshold);
+        }
+        if let Some(growth) = self.iteration_growth {
+            if !growth.is_finite() || growth < 0.0 {
+                return Err(ConfigError::InvalidIterationGrowth(growth));
+            }
+        }
+        if let Some((a, b)) = self.z0 {
+            if !a.is_finite() || !b.is_finite() {
+                return Err(ConfigError::NonFiniteSeed);
+            }
+        }
+        if let AnimationMode::JuliaSweep { path : JuliaPath::Circle { center, radius, frames } } = self.animation {
+            let finite = center.0.is_finite() && center.1.is_finite() && radius.is_finite();
+            if !finite || frames == 0 {
+                return Err(ConfigError::InvalidJuliaPath);
+            }
+        }
+        Ok(())
+    }
+
+    ///    ### (PURE)
+    ///    Frame number fed to calc_zoomed_ranges for frame_number, the
+    ///    viewport only zooms in Zoom animations.
+    pub fn zoom_frame(&self, frame_number : u32) -> u32 {
+        match self.animation {
+            AnimationMode::Zoom => frame_number,
+            AnimationMode::JuliaSweep { .. } => 0,
+        }
+    }
+
+    ///    ### (PURE)
+    ///    Iteration cap of frame_number, max_iterations * (1 + growth * d)
+    ///    for d tenfold magnifications since frame 0 with iteration_growth
+    ///    set, so deep frames keep their edge detail. Only Zoom animations
+    ///    grow, saturating at u32::MAX.
+    pub fn max_iterations_at(&self, frame_number : u32) -> u32 {
+        let Some(growth) = self.iteration_growth else {
+            return self.max_iterations;
+        };
+        let decades = -self.zoom_rate.log10() * self.zoom_frame(frame_number) as f64;
+        (self.max_iterations as f64 * (1.0 + growth * decades)).round() as u32
+    }
+
+    ///    ### (PURE)
+    ///    Iteration parameters of frame_number.
+    pub fn orbit_params(&self, frame_number : u32) -> OrbitParams {
+        let kind = match self.animation {
+            AnimationMode::Zoom => self.kind,
+            AnimationMode::JuliaSweep { path } => FractalKind::Julia { c : path.c_at(frame_number) },
+        };
+        OrbitParams {
+            max_iterations : self.max_iterations_at(frame_number),
+            interior_threshold : self.interior_threshold,
+            kind,
+            z0 : self.z0,
+            exploit_symmetry : self.exploit_symmetry,
+            rotation : self.rotation,
+            pivot : if self.precision == Precision::DoubleDouble { (0.0, 0.0) } else { self.zoom_pivot() },
+            origin : self.frame_origin(),
+            coefficients : self.coefficients,
+            precision : self.precision,
+            flip_y : self.flip_y.then_some(self.img_height),
+        }
+    }
+
+    ///    ### (PURE)
+    ///    Magnification of frame_number relative to the default starting
+    ///    viewport, by the width of its x range.
+    pub fn magnification(&self, frame_number : u32) -> f64 {
+        let default_x_range = RenderConfig::default().starting_x_range;
+        let (x_range, _) = frame_ranges(self, frame_number);
+        (default_x_range.1 - default_x_range.0) / (x_range.1 - x_range.0)
+    }
+
+    ///    ### (PURE)
+    ///    Point pixel coordinates are offsets from, see
+    ///    OrbitParams::origin: the zoom pivot for DoubleDouble precision,
+    ///    so frames deep into its zoom keep exact pixel steps, otherwise
+    ///    (0.0, 0.0).
+    pub fn frame_origin(&self) -> (f64, f64) {
+        match self.precision {
+            Precision::DoubleDouble => self.zoom_pivot(),
+            Precision::F64 | Precision::F32 => (0.0, 0.0),
+        }
+    }
+
+    ///    ### (PURE)
+    ///    Point every frame zooms and rotates about, zoom_center or else
+    ///    the center of the starting viewport.
+    pub fn zoom_pivot(&self) -> (f64, f64) {
+        self.zoom_center.unwrap_or((
+            (self.starting_x_range.0 + self.starting_x_range.1) / 2.0,
+            (self.starting_y_range.0 + self.starting_y_range.1) / 2.0,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iteration_cap_grows_per_decade_of_zoom() {
+        let config = RenderConfig { zoom_rate : 0.1, max_iterations : 100, ..RenderConfig::default() };
+        assert_eq!(config.max_iterations_at(3), 100);
+        let growing = RenderConfig { iteration_growth : Some(0.5), ..config };
+        assert_eq!(growing.max_iterations_at(0), 100);
+        assert_eq!(growing.max_iterations_at(2), 200);
+        assert_eq!(growing.orbit_params(4).max_iterations, 300);
+    }
+}
@@ -0,0 +1,104 @@
+//!   Module contains funcs comparing consecutive frames of a
+//!   zoom, painting how much each pixel's escape value changes
+//!   from frame k to frame k+1 as a heatmap. Useful for
+//!   debugging the zoom math.
+//!   @author Van Gouache
+
+use image::{ImageBuffer, Rgb};
+use crate::parallel::*;
+use crate::burning_ship_frac::{calc_step_size, frame_ranges, render_escape_grid, EscapeValue, Fractal, Range, RenderError};
+use crate::config::RenderConfig;
+use crate::painter;
+
+//preset colormap and color count of the difference heatmap
+const HEATMAP_PRESET : &str = "magma";
+const HEATMAP_LEN : usize = 256;
+
+
+///    ### (PURE)
+///    Resamples frame, covering the viewport from_x_range by from_y_range,
+///    onto a width x height grid covering to_x_range by to_y_range. Each
+///    cell takes the nearest cell of frame, clamped at its border.
+pub fn remap_nearest<T : Copy + Send + Sync>(
+    frame : &[Vec<T>],
+    from_x_range : Range,
+    from_y_range : Range,
+    to_x_range : Range,
+    to_y_range : Range,
+    width : usize,
+    height : usize
+) -> Fractal<T>
+{
+    let source_height = frame.len();
+    let source_width = frame.first().map_or(0, |row| row.len());
+    let (from_x_step, from_y_step) = calc_step_size(source_width, source_height, from_x_range, from_y_range);
+    let (to_x_step, to_y_step) = calc_step_size(width, height, to_x_range, to_y_range);
+    let nearest = |index : usize, to_step : f64, to_floor : f64, from_step : f64, from_floor : f64, len : usize| {
+        let coordinate = to_floor + index as f64 * to_step;
+        ((coordinate - from_floor) / from_step).round().clamp(0.0, (len - 1) as f64) as usize
+    };
+    (0..height).into_par_iter().map(|i|{
+        let row = &frame[nearest(i, to_y_step, to_y_range.0, from_y_step, from_y_range.0, source_height)];
+        (0..width)
+        .map(|j| row[nearest(j, to_x_step, to_x_range.0, from_x_step, from_x_range.0, source_width)])
+        .collect()
+    }).collect()
+}
+
+
+///    ### (PURE)
+///    Absolute difference of the orbit rates of two equally sized frames.
+pub fn escape_difference<T : EscapeValue>(a : &[Vec<T>], b : &[Vec<T>]) -> Fractal<u32> {
+    a.par_iter().zip(b.par_iter()).map(|(row_a, row_b)|{
+        row_a.iter().zip(row_b.iter())
+        .map(|(cell_a, cell_b)| (cell_a.escape_value() - cell_b.escape_value()).abs() as u32)
+        .collect()
+    }).collect()
+}
+
+
+///    ### (PURE)
+///    Composes functions:\
+///    render_escape_grid (frames frame_number and frame_number + 1) ->\
+///    remap_nearest ->\
+///    escape_difference\
+///    To return how much each pixel of frame_number + 1 changed since
+///    frame_number, with frame_number remapped onto the viewport of the
+///    next frame. Rotation is shared by both frames, so aligning the
+///    unrotated ranges is enough.
+pub fn render_difference_grid(
+    config : &RenderConfig,
+    frame_number : u32
+) -> Result<Fractal<u32>, RenderError>
+{
+    let next_frame = frame_number.saturating_add(1);
+    let frame : Fractal<u32> = render_escape_grid(config, frame_number)?;
+    let next : Fractal<u32> = render_escape_grid(config, next_frame)?;
+    let (x_range, y_range) = frame_ranges(config, frame_number);
+    let (next_x_range, next_y_range) = frame_ranges(config, next_frame);
+    // ⬇
+    let aligned = remap_nearest(
+        &frame,
+        x_range,
+        y_range,
+        next_x_range,
+        next_y_range,
+        config.img_width,
+        config.img_height
+    );
+    // ⬇
+    Ok(escape_difference(&next, &aligned))
+}
+
+
+///    ### (PURE)
+///    Paints a difference grid through the magma heatmap, scaled so the
+///    largest difference in the frame takes the brightest color.
+pub fn paint_difference(width : u32, height : u32, difference : &[Vec<u32>]) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let heatmap = painter::generate_preset_palette(HEATMAP_PRESET, HEATMAP_LEN).unwrap();
+    let max_difference = difference.iter().flatten().copied().max().unwrap_or(0).max(1) as usize;
+    ImageBuffer::from_fn(width, height, |x, y|{
+        let cell = difference[y as usize][x as usize] as usize;
+        heatmap[cell * (HEATMAP_LEN - 1) / max_difference]
+    })
+}
@@ -0,0 +1,66 @@
+//!   Module contains the crate-level error type, wrapping
+//!   the errors of config validation, rendering and saving
+//!   so callers mixing them can use ? and report any one.
+//!   @author Van Gouache
+
+use std::fmt;
+use image::ImageError;
+use crate::burning_ship_frac::RenderError;
+use crate::config::ConfigError;
+
+
+///    Any failure of the crate. Funcs with a single source of failure keep
+///    returning its own error type, which converts into this one.
+#[derive(Debug)]
+pub enum FractalError {
+    Config(ConfigError),
+    Render(RenderError),
+    Io(std::io::Error),
+    Image(ImageError),
+}
+
+impl fmt::Display for FractalError {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FractalError::Config(err) => write!(f, "invalid render config: {}", err),
+            FractalError::Render(err) => write!(f, "could not render: {}", err),
+            FractalError::Io(err) => write!(f, "I/O error: {}", err),
+            FractalError::Image(err) => write!(f, "could not encode image: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for FractalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FractalError::Config(err) => Some(err),
+            FractalError::Render(err) => Some(err),
+            FractalError::Io(err) => Some(err),
+            FractalError::Image(err) => Some(err),
+        }
+    }
+}
+
+impl From<ConfigError> for FractalError {
+    fn from(err : ConfigError) -> Self {
+        FractalError::Config(err)
+    }
+}
+
+impl From<RenderError> for FractalError {
+    fn from(err : RenderError) -> Self {
+        FractalError::Render(err)
+    }
+}
+
+impl From<std::io::Error> for FractalError {
+    fn from(err : std::io::Error) -> Self {
+        FractalError::Io(err)
+    }
+}
+
+impl From<ImageError> for FractalError {
+    fn from(err : ImageError) -> Self {
+        FractalError::Image(err)
+    }
+}
@@ -0,0 +1,98 @@
+//!   Module contains funcs scouting a viewport with a cheap
+//!   coarse render before committing to a full animation, to pick
+//!   where to zoom and how far to iterate.
+//!   @author Van Gouache
+
+use crate::burning_ship_frac::{calc_step_size, frame_ranges, pixel_coordinate, render_escape_grid, Fractal, RenderError};
+use crate::config::RenderConfig;
+use crate::stats::{escape_histogram, escape_percentile};
+
+//width of the coarse scouting render in pixels
+const COARSE_WIDTH : usize = 128;
+//side of the square tiles the coarse render is scored in
+const TILE_SIZE : usize = 8;
+//iteration cap of the coarse render probing escape times
+const PROBE_ITERATIONS : u32 = 5000;
+//fraction of escaping pixels suggest_max_iterations lets escape
+const ESCAPE_FRACTION : f64 = 0.99;
+
+
+///    ### (PURE)
+///    Coarse render of frame 0 of config COARSE_WIDTH pixels wide, with
+///    every field but the resolution and max_iterations taken from config.
+fn coarse_render(
+    config : &RenderConfig,
+    max_iterations : u32
+) -> Result<(RenderConfig, Fractal<u32>), RenderError>
+{
+    let aspect = config.img_height as f64 / config.img_width.max(1) as f64;
+    let coarse = RenderConfig {
+        img_width : COARSE_WIDTH,
+        img_height : ((COARSE_WIDTH as f64 * aspect).round() as usize).max(1),
+        max_iterations,
+        ..config.clone()
+    };
+    let frame = render_escape_grid(&coarse, 0)?;
+    Ok((coarse, frame))
+}
+
+
+///    ### (PURE)
+///    Variance of the escape values of the tile of frame with top left
+///    cell (x, y), clipped at the frame border.
+fn tile_variance(frame : &Fractal<u32>, x : usize, y : usize) -> f64 {
+    let cells : Vec<f64> = frame[y..(y + TILE_SIZE).min(frame.len())]
+        .iter()
+        .flat_map(|row| row[x..(x + TILE_SIZE).min(row.len())].iter())
+        .map(|cell| *cell as f64)
+        .collect();
+    let count = cells.len().max(1) as f64;
+    let mean = cells.iter().sum::<f64>() / count;
+    cells.iter().map(|cell| (cell - mean) * (cell - mean)).sum::<f64>() / count
+}
+
+
+///    ### (PURE)
+///    Renders frame 0 of config at COARSE_WIDTH pixels wide, scores
+///    TILE_SIZE square tiles by the variance of their escape values and
+///    returns the complex coordinate of the center of the most detailed
+///    tile, a good point to zoom towards.
+pub fn find_interesting_center(config : &RenderConfig) -> Result<(f64, f64), RenderError> {
+    let (coarse, frame) = coarse_render(config, config.max_iterations)?;
+    // ⬇
+    let (x_range, y_range) = frame_ranges(&coarse, 0);
+    let (x_step_size, y_step_size) = calc_step_size(coarse.img_width, coarse.img_height, x_range, y_range);
+    let (best_x, best_y) = (0..coarse.img_height)
+        .step_by(TILE_SIZE)
+        .flat_map(|y| (0..coarse.img_width).step_by(TILE_SIZE).map(move |x| (x, y)))
+        .map(|(x, y)| ((x, y), tile_variance(&frame, x, y)))
+        .fold(((0, 0), f64::MIN), |best, tile| if tile.1 > best.1 { tile } else { best })
+        .0;
+    let tile_center = |start : usize, len : usize| start as f64 + TILE_SIZE.min(len - start) as f64 / 2.0;
+    let center = pixel_coordinate(
+        tile_center(best_x, coarse.img_width),
+        tile_center(best_y, coarse.img_height),
+        x_step_size,
+        y_step_size,
+        x_range.0,
+        y_range.0,
+        &coarse.orbit_params(0)
+    );
+    Ok((center.re, center.im))
+}
+
+
+///    ### (PURE)
+///    Suggests a max_iterations for the starting viewport of config.
+///    Renders frame 0 coarsely with PROBE_ITERATIONS and returns the cap
+///    that ESCAPE_FRACTION of the escaping pixels escape before, so
+///    edges keep their detail without iterating the interior for longer
+///    than needed. Deeper frames of a zoom usually need more. Falls back
+///    to config.max_iterations when no probed pixel escapes.
+pub fn suggest_max_iterations(config : &RenderConfig) -> Result<u32, RenderError> {
+    let (_, frame) = coarse_render(config, PROBE_ITERATIONS)?;
+    // ⬇
+    let histogram = escape_histogram(&frame, PROBE_ITERATIONS);
+    Ok(escape_percentile(&histogram, ESCAPE_FRACTION)
+        .map_or(config.max_iterations, |rate| rate + 1))
+}
@@ -0,0 +1,279 @@
+//!   Module contains an optional wgpu compute path for
+//!   gen_burning_ship_fractal, enabled by the `gpu` feature.
+//!   The shader implements the same (|Re|+|Im|i)^2 + C iteration
+//!   in f32 (WGSL has no portable f64), so it matches the CPU path
+//!   within floating tolerance for shallow zooms but hits the
+//!   precision wall many frames earlier on deep ones.
+//!   @author Van Gouache
+
+use std::sync::OnceLock;
+use wgpu::util::DeviceExt;
+use crate::burning_ship_frac::{EscapeCount, Fractal, FractalKind, OrbitParams, Range};
+
+const WORKGROUP_SIZE : u32 = 8;
+
+const SHADER : &str = r#"
+struct Params {
+    a_floor : f32,
+    b_floor : f32,
+    x_step_size : f32,
+    y_step_size : f32,
+    width : u32,
+    height : u32,
+    max_iterations : u32,
+    julia : u32,
+    c_a : f32,
+    c_b : f32,
+    seeded : u32,
+    z0_a : f32,
+    z0_b : f32,
+    rot_cos : f32,
+    rot_sin : f32,
+    pivot_a : f32,
+    pivot_b : f32,
+    k_a : f32,
+    k_b : f32,
+    _pad0 : u32,
+}
+
+@group(0) @binding(0) var<uniform> params : Params;
+@group(0) @binding(1) var<storage, read_write> orbits : array<u32>;
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id : vec3<u32>) {
+    if (id.x >= params.width || id.y >= params.height) {
+        return;
+    }
+    var pixel = vec2<f32>(
+        params.a_floor + f32(id.x) * params.x_step_size,
+        params.b_floor + f32(id.y) * params.y_step_size
+    );
+    if (params.rot_sin != 0.0 || params.rot_cos != 1.0) {
+        let pivot = vec2<f32>(params.pivot_a, params.pivot_b);
+        let offset = pixel - pivot;
+        pixel = pivot + vec2<f32>(
+            offset.x * params.rot_cos - offset.y * params.rot_sin,
+            offset.x * params.rot_sin + offset.y * params.rot_cos
+        );
+    }
+    var c = pixel;
+    if (params.julia != 0u) {
+        c = vec2<f32>(params.c_a, params.c_b);
+    }
+    var z = pixel;
+    if (params.julia == 0u && params.seeded != 0u) {
+        z = vec2<f32>(params.z0_a, params.z0_b);
+    }
+    var i : u32 = 0u;
+    loop {
+        if (i >= params.max_iterations || dot(z, z) >= 4.0) {
+            break;
+        }
+        z = vec2<f32>(
+            params.k_a * (z.x * z.x - z.y * z.y) + c.x,
+            abs(params.k_b * abs(2.0 * z.x * z.y)) + c.y
+        );
+        i = i + 1u;
+    }
+    orbits[id.y * params.width + id.x] = i;
+}
+"#;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    a_floor : f32,
+    b_floor : f32,
+    x_step_size : f32,
+    y_step_size : f32,
+    width : u32,
+    height : u32,
+    max_iterations : u32,
+    julia : u32,
+    c_a : f32,
+    c_b : f32,
+    seeded : u32,
+    z0_a : f32,
+    z0_b : f32,
+    rot_cos : f32,
+    rot_sin : f32,
+    pivot_a : f32,
+    pivot_b : f32,
+    k_a : f32,
+    k_b : f32,
+    _pad : u32,
+}
+
+struct GpuContext {
+    device : wgpu::Device,
+    queue : wgpu::Queue,
+    pipeline : wgpu::ComputePipeline,
+}
+
+// Adapter lookup and pipeline compilation happen once per process,
+// None records that no adapter was found so we stop asking.
+static CONTEXT : OnceLock<Option<GpuContext>> = OnceLock::new();
+
+
+///    ### (I/O)
+///    Requests an adapter and compiles the escape-time pipeline.
+fn init_context() -> Option<GpuContext> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle());
+    let adapter = pollster::block_on(
+        instance.request_adapter(&wgpu::RequestAdapterOptions::default())
+    ).ok()?;
+    let (device, queue) = pollster::block_on(
+        adapter.request_device(&wgpu::DeviceDescriptor::default())
+    ).ok()?;
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label : Some("burning_ship"),
+        source : wgpu::ShaderSource::Wgsl(SHADER.into()),
+    });
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label : Some("burning_ship"),
+        layout : None,
+        module : &module,
+        entry_point : Some("main"),
+        compilation_options : Default::default(),
+        cache : None,
+    });
+    Some(GpuContext { device, queue, pipeline })
+}
+
+
+///    ### (I/O)
+///    GPU counterpart of gen_burning_ship_fractal. Returns None when no
+///    adapter is available (or the frame exceeds its buffer limits) so the
+///    caller can fall back to the rayon CPU path.
+pub fn gen_burning_ship_fractal<T : EscapeCount>(
+    img_width : usize,
+    img_height : usize,
+    x_range : Range,
+    y_range : Range,
+    x_step_size : f64,
+    y_step_size : f64,
+    orbit_params : &OrbitParams
+) -> Option<Fractal<T>>
+{
+    let context = CONTEXT.get_or_init(init_context).as_ref()?;
+    let cell_count = img_width * img_height;
+    let buffer_size = (cell_count * std::mem::size_of::<u32>()) as u64;
+    let limits = context.device.limits();
+    if buffer_size == 0
+        || buffer_size > limits.max_storage_buffer_binding_size
+        || buffer_size > limits.max_buffer_size {
+        return None;
+    }
+
+    let c = match orbit_params.kind {
+        FractalKind::BurningShip => (0.0, 0.0),
+        FractalKind::Julia { c } => c,
+    };
+    let z0 = orbit_params.z0.unwrap_or((0.0, 0.0));
+    let params = Params {
+        a_floor : x_range.0 as f32,
+        b_floor : y_range.0 as f32,
+        x_step_size : x_step_size as f32,
+        y_step_size : y_step_size as f32,
+        width : img_width as u32,
+        height : img_height as u32,
+        max_iterations : orbit_params.iteration_cap(),
+        julia : matches!(orbit_params.kind, FractalKind::Julia { .. }) as u32,
+        c_a : c.0 as f32,
+        c_b : c.1 as f32,
+        seeded : orbit_params.z0.is_some() as u32,
+        z0_a : z0.0 as f32,
+        z0_b : z0.1 as f32,
+        rot_cos : orbit_params.rotation.cos() as f32,
+        rot_sin : orbit_params.rotation.sin() as f32,
+        pivot_a : orbit_params.pivot.0 as f32,
+        pivot_b : orbit_params.pivot.1 as f32,
+        k_a : orbit_params.coefficients.0 as f32,
+        k_b : orbit_params.coefficients.1 as f32,
+        _pad : 0,
+    };
+    let device = &context.device;
+    let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label : Some("params"),
+        contents : bytemuck::bytes_of(&params),
+        usage : wgpu::BufferUsages::UNIFORM,
+    });
+    let orbit_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label : Some("orbits"),
+        size : buffer_size,
+        usage : wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation : false,
+    });
+    let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label : Some("readback"),
+        size : buffer_size,
+        usage : wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation : false,
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label : Some("burning_ship"),
+        layout : &context.pipeline.get_bind_group_layout(0),
+        entries : &[
+            wgpu::BindGroupEntry { binding : 0, resource : params_buffer.as_entire_binding() },
+            wgpu::BindGroupEntry { binding : 1, resource : orbit_buffer.as_entire_binding() },
+        ],
+    });
+
+    let mut encoder = device.create_command_encoder(&Default::default());
+    {
+        let mut pass = encoder.begin_compute_pass(&Default::default());
+        pass.set_pipeline(&context.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(
+            (img_width as u32).div_ceil(WORKGROUP_SIZE),
+            (img_height as u32).div_ceil(WORKGROUP_SIZE),
+            1
+        );
+    }
+    encoder.copy_buffer_to_buffer(&orbit_buffer, 0, &readback_buffer, 0, buffer_size);
+    context.queue.submit(Some(encoder.finish()));
+
+    let slice = readback_buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |_| {});
+    device.poll(wgpu::PollType::wait_indefinitely()).ok()?;
+    let orbits : Vec<u32> = bytemuck::cast_slice(&slice.get_mapped_range().ok()?).to_vec();
+    readback_buffer.unmap();
+
+    let mut frame : Fractal<T> = orbits
+        .chunks(img_width)
+        .map(|row| row.iter().map(|i| T::from_count(orbit_params.interior_clamp(*i))).collect())
+        .collect();
+    // the shader samples in image orientation
+    if orbit_params.flip_y.is_some() {
+        frame.reverse();
+    }
+    Some(frame)
+}
+
+#[cfg(all(test, feature = "gpu"))]
+mod tests {
+    use super::*;
+    use crate::burning_ship_frac::{calc_step_size, get_orbit_rate};
+
+    //share of cells of a shallow frame allowed to differ from the CPU,
+    //f32 orbits may escape a step apart near the set boundary
+    const MAX_MISMATCH_FRACTION : f64 = 0.05;
+
+    ///    Bounds the cells of a shallow GPU frame that differ from the CPU
+    ///    path. A no-op on machines without a GPU adapter.
+    #[test]
+    fn gpu_frame_matches_the_cpu_on_a_shallow_frame() {
+        let (img_width, img_height) = (96, 64);
+        let (x_range, y_range) = ((-2.2, 1.2), (-2.0, 1.0));
+        let (x_step_size, y_step_size) = calc_step_size(img_width, img_height, x_range, y_range);
+        let params = OrbitParams { max_iterations : 100, ..OrbitParams::default() };
+        let Some(gpu) = gen_burning_ship_fractal::<u32>(img_width, img_height, x_range, y_range, x_step_size, y_step_size, &params) else {
+            return;
+        };
+        let mismatched = (0..img_height).flat_map(|y| (0..img_width).map(move |x| (x, y))).filter(|&(x, y)|{
+            gpu[y][x] != get_orbit_rate(x, y, x_step_size, y_step_size, x_range.0, y_range.0, &params)
+        }).count();
+        let fraction = mismatched as f64 / (img_width * img_height) as f64;
+        assert!(fraction <= MAX_MISMATCH_FRACTION, "{} of {} cells differ", mismatched, img_width * img_height);
+    }
+}
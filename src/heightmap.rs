@@ -0,0 +1,38 @@
+//!   Module contains funcs writing escape grids as 16-bit
+//!   grayscale PNGs, heightmaps terrain and 3D tools can read
+//!   directly instead of a painted image.
+//!   @author Van Gouache
+
+use image::{ImageBuffer, ImageError, ImageFormat, ImageResult, Luma};
+use crate::burning_ship_frac::EscapeValue;
+use crate::painter;
+
+
+///    ### (PURE)
+///    Maps the escape values of frame linearly onto 0 to u16::MAX, the
+///    lowest value in the frame to black and the highest (interior
+///    plateaus when the frame has any) to white. A flat frame is black.
+pub fn heightmap<T : EscapeValue>(frame : &[Vec<T>]) -> ImageBuffer<Luma<u16>, Vec<u16>> {
+    let height = frame.len() as u32;
+    let width = frame.first().map_or(0, |row| row.len()) as u32;
+    let (low, high) = frame.iter().flatten().fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), cell|{
+        (low.min(cell.escape_value()), high.max(cell.escape_value()))
+    });
+    let span = if high > low { high - low } else { 1.0 };
+    ImageBuffer::from_fn(width, height, |x, y|{
+        let value = frame[y as usize][x as usize].escape_value();
+        Luma([((value - low) / span * u16::MAX as f64).round() as u16])
+    })
+}
+
+
+///    ### (I/O)
+///    Writes the heightmap of frame to "{OUTPUT_DIR}/{frame_number}.png",
+///    through a temporary file like painter::save_img_buff.
+pub fn save_heightmap<T : EscapeValue>(frame : &[Vec<T>], frame_number : u32) -> ImageResult<()> {
+    let path = painter::frame_path(frame_number, "png");
+    let tmp_path = format!("{}.tmp", path);
+    heightmap(frame).save_with_format(&tmp_path, ImageFormat::Png)?;
+    // ⬇
+    std::fs::rename(&tmp_path, &path).map_err(ImageError::IoError)
+}
@@ -0,0 +1,119 @@
+//!   Module contains a "Droste" view of a zoom: its first frame
+//!   with a smaller inset of a deeper magnification in the
+//!   bottom right corner, and optionally a still deeper inset
+//!   inside that one, composited into a single image.
+//!   @author Van Gouache
+
+use image::{ImageBuffer, Rgb};
+use crate::burning_ship_frac::{calc_magnified_ranges, render_escape_grid, render_smooth_grid, Fractal, RenderError};
+use crate::config::RenderConfig;
+use crate::painter::{paint_frame, ColoringMode, PaintOptions};
+
+//each inset is this many times narrower than the view it sits in
+pub const INSET_SHRINK : usize = 3;
+//gap between an inset and the corner of its view, and its border, in
+//pixels of the outermost view per 400 pixels of height
+const INSET_MARGIN : u32 = 8;
+const INSET_BORDER : u32 = 2;
+const BORDER_COLOR : Rgb<u8> = Rgb([255, 255, 255]);
+
+
+///    ### (PURE)
+///    Configs of the main view, config itself, and of levels insets,
+///    inset k centered on the zoom pivot of config, magnified zoom^k
+///    times and INSET_SHRINK^k times smaller. Each renders the view as
+///    its frame 0.
+pub fn inset_configs(config : &RenderConfig, levels : u32, zoom : f64) -> Vec<RenderConfig> {
+    let pivot = config.zoom_pivot();
+    let insets = (1..=levels).map(|level|{
+        let shrink = INSET_SHRINK.pow(level);
+        let (starting_x_range, starting_y_range) = calc_magnified_ranges(
+            config.starting_x_range,
+            config.starting_y_range,
+            zoom.powi(level as i32),
+            pivot
+        );
+        RenderConfig {
+            img_width : (config.img_width / shrink).max(1),
+            img_height : (config.img_height / shrink).max(1),
+            starting_x_range,
+            starting_y_range,
+            zoom_center : Some(pivot),
+            ..config.clone()
+        }
+    });
+    std::iter::once(config.clone()).chain(insets).collect()
+}
+
+
+///    ### (PURE)
+///    Pastes each view into the bottom right corner of the one before it,
+///    innermost first, framed by a BORDER_COLOR border. views run from
+///    the main view to the deepest inset.
+pub fn composite_insets(mut views : Vec<ImageBuffer<Rgb<u8>, Vec<u8>>>) -> Option<ImageBuffer<Rgb<u8>, Vec<u8>>> {
+    let scale = (views.first()?.height() / 400).max(1);
+    let (margin, border) = (INSET_MARGIN * scale, INSET_BORDER * scale);
+    while views.len() > 1 {
+        let inset = views.pop()?;
+        let view = views.last_mut()?;
+        let x = view.width().saturating_sub(margin + border + inset.width());
+        let y = view.height().saturating_sub(margin + border + inset.height());
+        let framed = ImageBuffer::from_pixel(inset.width() + 2 * border, inset.height() + 2 * border, BORDER_COLOR);
+        image::imageops::replace(view, &framed, x as i64 - border as i64, y as i64 - border as i64);
+        image::imageops::replace(view, &inset, x as i64, y as i64);
+    }
+    views.pop()
+}
+
+
+///    ### (PURE)
+///    Composes functions:\
+///    inset_configs ->\
+///    render_escape_grid (render_smooth_grid for smooth coloring) ->\
+///    paint_frame ->\
+///    composite_insets\
+///    To return frame 0 of config with levels nested insets, each zoom
+///    times deeper than the view around it.
+pub fn render_insets(
+    config : &RenderConfig,
+    palette : &[Rgb<u8>],
+    options : &PaintOptions,
+    levels : u32,
+    zoom : f64
+) -> Result<ImageBuffer<Rgb<u8>, Vec<u8>>, RenderError>
+{
+    let views = inset_configs(config, levels, zoom).iter().map(|view_config|{
+        let (width, height) = (view_config.img_width as u32, view_config.img_height as u32);
+        let offset = options.palette_offset(0);
+        if options.coloring != ColoringMode::Bands {
+            let frame = render_smooth_grid(view_config, 0)?;
+            return Ok(paint_frame(width, height, &frame, palette, options, offset));
+        }
+        let frame : Fractal<u32> = render_escape_grid(view_config, 0)?;
+        // ⬇
+        Ok(paint_frame(width, height, &frame, palette, options, offset))
+    }).collect::<Result<Vec<_>, RenderError>>()?;
+    // ⬇
+    Ok(composite_insets(views).expect("inset_configs returns the main view"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::burning_ship_frac::frame_ranges;
+
+    #[test]
+    fn insets_shrink_and_center_on_the_zoom_target() {
+        let config = RenderConfig { zoom_center : Some((-1.76, -0.03)), ..RenderConfig::default() };
+        let views = inset_configs(&config, 2, 10.0);
+        assert_eq!(views.len(), 3);
+        assert_eq!(views[0], config);
+        for (level, view) in views.iter().enumerate().skip(1) {
+            assert_eq!(view.img_width, config.img_width / INSET_SHRINK.pow(level as u32));
+            let (x_range, y_range) = frame_ranges(view, 0);
+            assert!(((x_range.0 + x_range.1) / 2.0 + 1.76).abs() < 1e-12);
+            assert!(((y_range.0 + y_range.1) / 2.0 + 0.03).abs() < 1e-12);
+            assert!((config.magnification(0) * 10f64.powi(level as i32) / view.magnification(0) - 1.0).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//!   Module contains cheap frame interpolation: painted frames
+//!   of a zoom are cross-dissolved into in-between frames, so
+//!   a video can run at a multiple of the rendered frame rate
+//!   without computing more fractal frames.
+//!   @author Van Gouache
+
+use std::ops::Range;
+use image::error::{ImageError, ImageResult, ParameterError, ParameterErrorKind};
+use image::{ImageBuffer, Rgb};
+use crate::parallel::*;
+use crate::painter::{blend_colors, frame_path, save_img_buff_at, PaintOptions, OUTPUT_DIR};
+
+//directory inside OUTPUT_DIR the interpolated sequence is saved to
+pub const INTERPOLATED_DIR : &str = "interpolated";
+
+
+///    ### (PURE)
+///    Mixes from and to pixel by pixel, t = 0 giving from and t = 1
+///    giving to, in linear light when gamma is given, see blend_colors.
+///    Fails when the frames differ in size.
+pub fn cross_dissolve(
+    from : &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    to : &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    t : f64,
+    gamma : Option<f64>
+) -> ImageResult<ImageBuffer<Rgb<u8>, Vec<u8>>>
+{
+    if from.dimensions() != to.dimensions() {
+        return Err(ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::DimensionMismatch)));
+    }
+    Ok(ImageBuffer::from_fn(from.width(), from.height(), |x, y|{
+        blend_colors(*from.get_pixel(x, y), *to.get_pixel(x, y), t, gamma)
+    }))
+}
+
+
+///    ### (PURE)
+///    The count frames evenly spaced between from and to, the k-th
+///    cross-dissolved at t = k / (count + 1).
+pub fn in_between_frames(
+    from : &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    to : &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    count : u32,
+    gamma : Option<f64>
+) -> ImageResult<Vec<ImageBuffer<Rgb<u8>, Vec<u8>>>>
+{
+    (1..=count).map(|k| cross_dissolve(from, to, k as f64 / (count + 1) as f64, gamma)).collect()
+}
+
+
+///    ### (PURE)
+///    Path of frame frame_number of the interpolated sequence,
+///    "{OUTPUT_DIR}/{INTERPOLATED_DIR}/NNNNNNNNNN.png".
+pub fn interpolated_path(frame_number : u32) -> String {
+    format!("{}/{}/{:010}.png", OUTPUT_DIR, INTERPOLATED_DIR, frame_number)
+}
+
+
+///    ### (I/O)
+///    Reads the saved frames of frame_range and writes them to the
+///    interpolated sequence with count in-between frames after each but
+///    the last, frame k becoming frame k * (count + 1). Pairs are
+///    processed in parallel. Returns the number of frames written, or the
+///    first error reading, blending or saving a frame.
+pub fn interpolate_saved_frames(frame_range : Range<u32>, count : u32, options : &PaintOptions) -> ImageResult<u32> {
+    std::fs::create_dir_all(format!("{}/{}", OUTPUT_DIR, INTERPOLATED_DIR)).map_err(ImageError::IoError)?;
+    let load = |frame_number : u32| image::open(frame_path(frame_number, "png")).map(|image| image.to_rgb8());
+    let stride = count.checked_add(1).filter(|stride| frame_range.end.checked_mul(*stride).is_some()).ok_or_else(||{
+        ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::Generic(
+            format!("frame {} with {} in-betweens overflows the frame numbers", frame_range.end, count)
+        )))
+    })?;
+    let last_frame = frame_range.end.saturating_sub(1);
+    let written : Vec<u32> = frame_range.clone().into_par_iter().map(|frame_number|{
+        let from = load(frame_number)?;
+        let in_betweens = if frame_number < last_frame {
+            in_between_frames(&from, &load(frame_number + 1)?, count, options.gamma)?
+        } else {
+            Vec::new()
+        };
+        // ⬇
+        let written = 1 + in_betweens.len() as u32;
+        let frames = std::iter::once(from).chain(in_betweens);
+        for (output_number, frame) in (frame_number * stride..).zip(frames) {
+            save_img_buff_at(frame, &interpolated_path(output_number), options)?;
+        }
+        Ok(written)
+    }).collect::<ImageResult<_>>()?;
+    Ok(written.iter().sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_betweens_dissolve_evenly() {
+        let black = ImageBuffer::from_pixel(4, 3, Rgb([0u8, 0, 0]));
+        let white = ImageBuffer::from_pixel(4, 3, Rgb([255u8, 255, 255]));
+        let frames = in_between_frames(&black, &white, 2, None).unwrap();
+        let levels : Vec<u8> = frames.iter().map(|frame| frame.get_pixel(3, 2)[0]).collect();
+        assert_eq!(levels, [85, 170]);
+        assert_eq!(cross_dissolve(&black, &white, 0.0, None).unwrap(), black);
+        assert!(cross_dissolve(&black, &ImageBuffer::new(3, 3), 0.5, None).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+//!   Module contains a render job running the frame
+//!   generator on a background thread, so interactive
+//!   front-ends can stop a render midway through a shared
+//!   cancel flag checked between frames and between rows.
+//!   @author Van Gouache
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use image::Rgb;
+use crate::parallel::*;
+use crate::annotate;
+use crate::burning_ship_frac::{self, EscapeCount, Fractal};
+use crate::config::RenderConfig;
+use crate::error::FractalError;
+use crate::painter::{self, PaintOptions};
+
+//frames generated and held in memory per burst
+const CHUNK_SIZE : u32 = 4;
+
+
+///    Outcome of a render job that was not stopped by an error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JobReport {
+    ///    First frame not saved. Frames before it are complete files on
+    ///    disk, a cancelled job can be resumed from here.
+    pub next_frame : u32,
+    pub cancelled : bool,
+}
+
+
+///    Handle of a render running on a background thread.
+pub struct RenderJob {
+    cancel : Arc<AtomicBool>,
+    handle : JoinHandle<Result<JobReport, FractalError>>,
+}
+
+impl RenderJob {
+    ///    ### (I/O)
+    ///    Starts rendering frames first_frame..last_frame of config into
+    ///    OUTPUT_DIR on a new thread. Like the binary, the job stops early at
+    ///    the first frame past float precision.
+    pub fn spawn(
+        config : RenderConfig,
+        palette : Vec<Rgb<u8>>,
+        options : PaintOptions,
+        first_frame : u32,
+        last_frame : u32
+    ) -> RenderJob
+    {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancel);
+        let handle = thread::spawn(move ||{
+            run(&config, &palette, &options, first_frame, last_frame, &flag)
+        });
+        RenderJob { cancel, handle }
+    }
+
+    ///    ### (I/O)
+    ///    Asks the job to stop. The frame burst in flight is dropped, frames
+    ///    already saved stay on disk.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    ///    ### (PURE)
+    ///    Shared cancel flag, for callers that want to signal the job
+    ///    without holding the handle.
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.cancel)
+    }
+
+    ///    ### (PURE)
+    ///    True once the background thread has returned.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    ///    ### (I/O)
+    ///    Waits for the job to return, re-raising a panic of its thread.
+    pub fn join(self) -> Result<JobReport, FractalError> {
+        self.handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+
+///    ### (I/O)
+///    Renders and saves bursts of CHUNK_SIZE frames until last_frame,
+///    precision runs out or cancel is set. A burst is only painted once
+///    all of its grids are complete, so saved frames are always a prefix
+///    of first_frame..last_frame.
+fn run(
+    config : &RenderConfig,
+    palette : &[Rgb<u8>],
+    options : &PaintOptions,
+    first_frame : u32,
+    last_frame : u32,
+    cancel : &AtomicBool
+) -> Result<JobReport, FractalError>
+{
+    painter::create_output_dir()?;
+    let last_frame = (first_frame..last_frame)
+        .find(|frame_number| burning_ship_frac::precision_exhausted(config, *frame_number))
+        .unwrap_or(last_frame);
+    // u8 cells unless the deepest frame's iteration cap outgrows them
+    let peak_max_iterations = config.max_iterations_at(last_frame.saturating_sub(1));
+    for burst_start in (first_frame..last_frame).step_by(CHUNK_SIZE as usize) {
+        let burst_end = burst_start.saturating_add(CHUNK_SIZE).min(last_frame);
+        let saved = if peak_max_iterations <= u8::MAX_COUNT {
+            save_burst::<u8>(config, palette, options, burst_start, burst_end, cancel)?
+        } else {
+            save_burst::<u32>(config, palette, options, burst_start, burst_end, cancel)?
+        };
+        if !saved {
+            return Ok(JobReport { next_frame : burst_start, cancelled : true });
+        }
+    }
+    Ok(JobReport { next_frame : last_frame, cancelled : false })
+}
+
+
+///    ### (I/O)
+///    Renders frames first_frame..last_frame in parallel and saves them,
+///    each painted with its own iteration cap (see
+///    RenderConfig::max_iterations_at), returning false without saving
+///    anything if cancel was set.
+fn save_burst<T : EscapeCount>(
+    config : &RenderConfig,
+    palette : &[Rgb<u8>],
+    options : &PaintOptions,
+    first_frame : u32,
+    last_frame : u32,
+    cancel : &AtomicBool
+) -> Result<bool, FractalError>
+{
+    let frames : Option<Vec<Fractal<T>>> = (first_frame..last_frame)
+    .into_par_iter()
+    .map(|frame_number|{
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        burning_ship_frac::render_escape_grid_until(config, frame_number, cancel)
+    }).collect::<Result<_, _>>()?;
+    let Some(frames) = frames else {
+        return Ok(false);
+    };
+    // ⬇
+    frames
+    .par_iter()
+    .zip(first_frame..last_frame)
+    .try_for_each(|(frame, frame_number)|{
+        let options = &*options.with_max_iterations(config.max_iterations_at(frame_number));
+        let mut buffer = painter::paint_frame(
+            config.img_width as u32,
+            config.img_height as u32,
+            frame,
+            palette,
+            options,
+            options.palette_offset(frame_number)
+        );
+        // ⬇
+        annotate::decorate_frame(&mut buffer, config, frame_number, options);
+        // ⬇
+        painter::save_img_buff(buffer, frame_number, options)
+    })?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn tiny_config() -> RenderConfig {
+        RenderConfig { img_width : 16, img_height : 9, ..RenderConfig::default() }
+    }
+
+    fn saved(frame_number : u32) -> bool {
+        Path::new(&painter::frame_path(frame_number, "png")).exists()
+    }
+
+    #[test]
+    fn cancelled_jobs_stop_at_a_burst_boundary_without_partial_frames() {
+        let palette = vec![Rgb([0, 0, 0]), Rgb([255, 255, 255])];
+        // frame numbers no other test writes
+        let (first_frame, last_frame) = (300, 300 + 3 * CHUNK_SIZE);
+
+        let cancel = AtomicBool::new(true);
+        let report = run(&tiny_config(), &palette, &PaintOptions::default(), first_frame, last_frame, &cancel).unwrap();
+        assert_eq!(report, JobReport { next_frame : first_frame, cancelled : true });
+        assert!((first_frame..last_frame).all(|frame_number| !saved(frame_number)));
+
+        let job = RenderJob::spawn(tiny_config(), palette, PaintOptions::default(), first_frame, last_frame);
+        job.cancel();
+        let report = job.join().unwrap();
+        if report.cancelled {
+            assert_eq!((report.next_frame - first_frame) % CHUNK_SIZE, 0);
+        }
+        assert!((first_frame..report.next_frame).all(saved));
+        assert!((report.next_frame..last_frame).all(|frame_number| !saved(frame_number)));
+        for frame_number in first_frame..last_frame {
+            let _ = std::fs::remove_file(painter::frame_path(frame_number, "png"));
+        }
+    }
+}
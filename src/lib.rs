@@ -0,0 +1,45 @@
+//!   Library crate for rendering frames of a zoom into the
+//!   burning ship fractal. The binary in main.rs drives these
+//!   modules; other crates can use them to obtain raw escape
+//!   grids or painted frames directly.
+//!   @author Van Gouache
+
+pub mod annotate;
+pub mod antialias;
+pub mod burning_ship_frac;
+pub mod config;
+pub mod difference;
+pub mod double_double;
+pub mod error;
+pub mod explore;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod heightmap;
+pub mod inset;
+pub mod interpolate;
+pub mod job;
+pub mod mask;
+pub mod metadata;
+pub mod motion_blur;
+pub mod npy;
+pub mod painter;
+pub mod palette_file;
+pub mod parallel;
+pub mod regions;
+pub mod reproject;
+pub mod resample;
+pub mod shotlist;
+pub mod side_by_side;
+#[cfg(feature = "simd")]
+pub mod simd;
+pub mod stats;
+pub mod thumbnail;
+pub mod title_card;
+pub mod trace;
+pub mod tuner;
+pub mod viewport_svg;
+
+pub use burning_ship_frac::{render_escape_grid, render_into, render_smooth_grid, Fractal};
+pub use config::RenderConfig;
+pub use painter::{paint_into, render_to_rgba};
+pub use error::FractalError;
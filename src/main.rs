@@ -3,50 +3,90 @@
 //!   loop for generating fractal frames. 
 //!   @author Van Gouache
 
+use clap::{CommandFactory, Parser};
+use log::{debug, error, info, warn};
 use rayon::prelude::*;
-mod burning_ship_frac;
-mod painter;
+use burning_ship_fractal::{annotate, antialias, burning_ship_frac, difference, explore, heightmap, inset, interpolate, mask, metadata, motion_blur, npy, painter, palette_file, parallel, reproject, resample, shotlist, stats, thumbnail, title_card, trace, tuner, viewport_svg, RenderConfig};
+use burning_ship_frac::{EscapeCount, EscapeValue, Fractal, RenderError};
+use metadata::RecordedPalette;
+use burning_ship_fractal::FractalError;
 use image::*;
-use std::{time::Instant, env};
+use std::{io::Write, ops::Range, path::Path, time::{Duration, Instant}};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+mod cli;
 
 
 type ImgResult = Result<(), ImageError>;
+//called with the frame number and path of every saved frame
+type FrameDone<'a> = Option<&'a (dyn Fn(u32, &Path) + Sync)>;
 static PRINT_ROW: &str = "=============================================";
+//frames slower than this many times the median render time are reported
+const SLOW_FRAME_FACTOR : u32 = 3;
+//colors in the --interior-palette gradient
+const INTERIOR_PALETTE_LEN : usize = 256;
+//frames that could not be saved this run, any makes the binary exit 1
+static FAILED_SAVES : AtomicU32 = AtomicU32::new(0);
+
+
+///    ### (I/O)
+///    Logs that what (e.g. "frame") frame_number could not be saved and
+///    counts it in FAILED_SAVES, so the run carries on with the other
+///    frames but still ends in failure.
+fn report_failed_save(what : &str, frame_number : u32, err : &ImageError) {
+    error!("Could not save {} {}: {}", what, frame_number, err);
+    FAILED_SAVES.fetch_add(1, Ordering::Relaxed);
+}
 
 ///   ### (PURE)
-///    Given a vec of frame_numbers, maps to complete fractal frames.
-fn map_frames_to_fractals(
-    img_width : usize,
-    img_height : usize,
-    starting_x_range : (f64, f64),
-    starting_y_range : (f64, f64), 
-    zoom_rate : f64, 
-    frames : Vec<u16> 
-) -> Vec<burning_ship_frac::Fractal> 
+///    Given a vec of frame_numbers, maps to complete fractal frames
+///    built by render, each with the time it took to build. Fails with
+///    the first RenderError of render.
+fn map_frames_to_fractals<T : EscapeValue>(
+    config : &RenderConfig,
+    frames : Vec<u32>,
+    render : impl Fn(&RenderConfig, u32) -> Result<Fractal<T>, RenderError> + Sync
+) -> Result<Vec<(Fractal<T>, Duration)>, RenderError>
 {
     frames
     .par_iter()
     .map(| i |{
-        burning_ship_frac::build_frame(
-            img_width, 
-            img_height,  
-            starting_x_range, 
-            starting_y_range, 
-            *i, 
-            zoom_rate
-        )
+        let frame_timer = Instant::now();
+        let frame = render(config, *i)?;
+        Ok((frame, frame_timer.elapsed()))
     }).collect()
 }
 
+///    ### (I/O)
+///    Calls on_frame_done with frame_number and its file of extension
+///    inside OUTPUT_DIR when saved is Ok, passing saved on.
+fn notify_frame_done(on_frame_done : FrameDone, frame_number : u32, extension : &str, saved : ImgResult) -> ImgResult {
+    if let (Ok(()), Some(on_frame_done)) = (&saved, on_frame_done) {
+        on_frame_done(frame_number, Path::new(&painter::frame_path(frame_number, extension)));
+    }
+    saved
+}
+
+
 /// ### (I/O)
 /// Given a vec of fractal frames. Generates a list of I/O results
-/// correlated to frame_number.png file
-fn map_fractal_to_img_io_results(
-    img_width : usize,
-    img_height : usize,
-    first_frame : u16,
-    palette : &Vec<Rgb<u8>>,
-    frames: Vec<burning_ship_frac::Fractal>
+/// correlated to frame_number.png file. The palette is rotated by
+/// frame_number * options.cycle_speed so colors flow across frames.
+/// With --annotate or --stamp, each frame is overlaid by decorate_frame
+/// before saving. With --npy the raw grid is saved to
+/// frame_number.npy instead and nothing is painted (a three channel
+/// tensor with --tensor), with --heightmap it
+/// is saved as a 16-bit grayscale frame_number.png. With --alpha-mask
+/// the painted frame gets an alpha channel from the interior mask.
+/// Every frame saved is passed to on_frame_done.
+fn map_fractal_to_img_io_results<T : EscapeValue + npy::NpyElement>(
+    config : &RenderConfig,
+    first_frame : u32,
+    palette : &[Rgb<u8>],
+    options : &painter::PaintOptions,
+    args : &cli::Args,
+    frames: Vec<Fractal<T>>,
+    on_frame_done : FrameDone
 ) -> Vec<ImgResult>
 {
     frames
@@ -54,55 +94,189 @@ fn map_fractal_to_img_io_results(
     .enumerate()
     .map(|fractal_data| {
         let (i, frame) = fractal_data;
-        let frame_number = i + first_frame as usize;
-        painter::paint_and_save_frame(
-            img_width as u32, 
-            img_height as u32, 
-            &frame, 
-            &palette, 
-            frame_number as u16
-        )
+        let frame_number = (i + first_frame as usize) as u32;
+        if args.npy {
+            let saved = npy::save_npy(frame, frame_number).map_err(ImageError::IoError);
+            return notify_frame_done(on_frame_done, frame_number, "npy", saved);
+        }
+        if args.heightmap {
+            return notify_frame_done(on_frame_done, frame_number, "png", heightmap::save_heightmap(frame, frame_number));
+        }
+        let options = &*options.with_max_iterations(config.max_iterations_at(frame_number));
+        let mut buffer = painter::paint_frame(
+            config.img_width as u32, 
+            config.img_height as u32, 
+            frame, 
+            palette, 
+            options,
+            options.palette_offset(frame_number)
+        );
+        // ⬇
+        annotate::decorate_frame(&mut buffer, config, frame_number, options);
+        // ⬇
+        if let Some(soft_edge) = args.alpha_mask {
+            let masked = mask::apply_interior_alpha(&buffer, frame, options.max_iterations, soft_edge, args.invert_mask);
+            return notify_frame_done(on_frame_done, frame_number, "png", painter::save_img_buff(masked, frame_number, options));
+        }
+        notify_frame_done(on_frame_done, frame_number, "png", painter::save_img_buff(buffer, frame_number, options))
     }).collect()
 }
 
 
 ///    ### (I/O)
-///    Composes map_frames_to_fractals -> map_fractal_to_img_io_results
-fn gen_and_save_frames(
-    img_width : usize,
-    img_height : usize,
-    starting_x_range : (f64, f64),
-    starting_y_range : (f64, f64), 
-    zoom_rate : f64, 
-    first_frame : u16,
-    last_frame : u16,
-    palette : &Vec<Rgb<u8>>
-)
+///    Motion blurred counterpart of gen_and_save_frames, each frame is
+///    built, painted and annotated by motion_blur::render_blurred_frame
+///    from sub-frames rendered by render. Returns the time every frame
+///    took, painting included, and no max escapes since the sub-frames
+///    are painted as they are rendered.
+fn gen_and_save_blurred_frames<T : EscapeValue>(
+    config : &RenderConfig,
+    frame_range : Range<u32>,
+    palette : &[Rgb<u8>],
+    options : &painter::PaintOptions,
+    render : impl Fn(&RenderConfig, u32) -> Result<Fractal<T>, RenderError> + Sync,
+    on_frame_done : FrameDone
+) -> Result<(Vec<Duration>, Vec<u32>), RenderError>
+{
+    let (first_frame, last_frame) = (frame_range.start, frame_range.end);
+    info!(
+        "\n\n{}\nGENERATING FRAMES {}-{} ({} SUB-FRAMES EACH)\n{}",
+        PRINT_ROW,
+        first_frame,
+        last_frame-1,
+        motion_blur::effective_samples(config),
+        PRINT_ROW
+    );
+    let prog_timer = Instant::now();
+    let frame_times : Vec<Duration> = (first_frame..last_frame).into_par_iter().map(|frame_number|{
+        let frame_timer = Instant::now();
+        let mut buffer = motion_blur::render_blurred_frame(config, frame_number, palette, options, &render)?;
+        // ⬇
+        annotate::decorate_frame(&mut buffer, config, frame_number, options);
+        // ⬇
+        let saved = painter::save_img_buff(buffer, frame_number, options);
+        if let Err(err) = notify_frame_done(on_frame_done, frame_number, "png", saved) {
+            report_failed_save("frame", frame_number, &err);
+        }
+        Ok(frame_timer.elapsed())
+    }).collect::<Result<_, RenderError>>()?;
+    info!("Total Time: {:?}\n{}", prog_timer.elapsed(), PRINT_ROW);
+    Ok((frame_times, Vec::new()))
+}
+
+
+///    ### (I/O)
+///    Composes map_frames_to_fractals -> save_burst with escape grids of
+///    type T built by render, see map_fractal_to_img_io_results for the
+///    output flags of args.
+///    Motion blurred configs go through gen_and_save_blurred_frames.
+///    Returns the build time and the largest orbit rate of every frame, or
+///    the first RenderError before anything of the burst is saved. With
+///    --compute-only the frames are dropped once built.
+///    on_frame_done, when given, is called from the worker threads with
+///    the number and path of each frame right after it is saved.
+fn gen_and_save_frames<T : EscapeValue + npy::NpyElement>(
+    config : &RenderConfig,
+    frame_range : Range<u32>,
+    palette : &[Rgb<u8>],
+    options : &painter::PaintOptions,
+    args : &cli::Args,
+    render : impl Fn(&RenderConfig, u32) -> Result<Fractal<T>, RenderError> + Sync,
+    on_frame_done : FrameDone
+) -> Result<(Vec<Duration>, Vec<u32>), RenderError>
 {
-    let frames : Vec<u16> = (first_frame..last_frame).collect();
-    println!("\n\n{}\nGENERATING FRAMES {}-{}\n{}", PRINT_ROW, first_frame, last_frame-1, PRINT_ROW);
+    if motion_blur::effective_samples(config) > 1 {
+        return gen_and_save_blurred_frames(config, frame_range, palette, options, render, on_frame_done);
+    }
+    let (first_frame, last_frame) = (frame_range.start, frame_range.end);
+    let frames : Vec<u32> = (first_frame..last_frame).collect();
+    info!("\n\n{}\nGENERATING FRAMES {}-{}\n{}", PRINT_ROW, first_frame, last_frame-1, PRINT_ROW);
     
     let prog_timer = Instant::now();
-    let frames  : Vec<burning_ship_frac::Fractal> = map_frames_to_fractals(
-        img_width, 
-        img_height, 
-        starting_x_range, 
-        starting_y_range, 
-        zoom_rate, 
-        frames
-    );
-    // ⬇    
+    let built = map_frames_to_fractals(
+        config, 
+        frames,
+        render
+    )?;
+    // ⬇
+    Ok(save_burst(config, Burst { first_frame, built, prog_timer }, palette, options, args, on_frame_done))
+}
+
+
+///    ### (I/O)
+///    Counterpart of gen_and_save_frames building the frames of
+///    frame_range one after another, each reprojected from the one before
+///    it with reproject::render_reprojected_frames.
+fn gen_and_save_reprojected_frames<T : EscapeCount + PartialEq + npy::NpyElement>(
+    config : &RenderConfig,
+    frame_range : Range<u32>,
+    palette : &[Rgb<u8>],
+    options : &painter::PaintOptions,
+    args : &cli::Args,
+    quality : reproject::ReprojectQuality,
+    on_frame_done : FrameDone
+) -> Result<(Vec<Duration>, Vec<u32>), RenderError>
+{
+    let first_frame = frame_range.start;
+    info!("\n\n{}\nGENERATING FRAMES {}-{} (REPROJECTED)\n{}", PRINT_ROW, first_frame, frame_range.end-1, PRINT_ROW);
+    let prog_timer = Instant::now();
+    let built = reproject::render_reprojected_frames::<T>(config, frame_range, quality)?;
+    // ⬇
+    Ok(save_burst(config, Burst { first_frame, built, prog_timer }, palette, options, args, on_frame_done))
+}
+
+
+///    Frames of a burst built from first_frame on, each with its build
+///    time, and the timer started when building began.
+struct Burst<T> {
+    first_frame : u32,
+    built : Vec<(Fractal<T>, Duration)>,
+    prog_timer : Instant,
+}
+
+
+///    ### (I/O)
+///    Logs the stats of every frame of burst and, unless --compute-only,
+///    paints and saves them with map_fractal_to_img_io_results. Returns
+///    the build time and the largest orbit rate of every frame.
+fn save_burst<T : EscapeValue + npy::NpyElement>(
+    config : &RenderConfig,
+    burst : Burst<T>,
+    palette : &[Rgb<u8>],
+    options : &painter::PaintOptions,
+    args : &cli::Args,
+    on_frame_done : FrameDone
+) -> (Vec<Duration>, Vec<u32>)
+{
+    let Burst { first_frame, built, prog_timer } = burst;
+    let (frames, frame_times) : (Vec<Fractal<T>>, Vec<Duration>) = built.into_iter().unzip();
     let build_frame_time = prog_timer.elapsed();
-    let _frame_results : Vec<ImgResult> = map_fractal_to_img_io_results(
-        img_width, 
-        img_height, 
+    let max_escapes : Vec<u32> = (first_frame..).zip(&frames).map(|(frame_number, frame)|{
+        let frame_stats = stats::frame_stats(frame, config.max_iterations_at(frame_number));
+        debug!("Frame {}: {}", frame_number, frame_stats);
+        frame_stats.max_escape
+    }).collect();
+    if args.compute_only {
+        info!("Finished generating frames in {:?}, nothing painted or saved\n{}", build_frame_time, PRINT_ROW);
+        return (frame_times, max_escapes);
+    }
+    let frame_results : Vec<ImgResult> = map_fractal_to_img_io_results(
+        config, 
         first_frame, 
         palette, 
-        frames
+        options,
+        args,
+        frames,
+        on_frame_done
     );
+    for (frame_number, saved) in (first_frame..).zip(&frame_results) {
+        if let Err(err) = saved {
+            report_failed_save("frame", frame_number, err);
+        }
+    }
 
     let paint_frame_time = prog_timer.elapsed() - build_frame_time;
-    println!(
+    info!(
         "Finished generating frames in {:?}\n{}\nFinished painting frames in {:?}s\n{}\nTotal Time: {:?}\n{}", 
         build_frame_time,
         PRINT_ROW, 
@@ -111,48 +285,675 @@ fn gen_and_save_frames(
         prog_timer.elapsed(),
         PRINT_ROW
     );
+    (frame_times, max_escapes)
 }
 
-fn main() {
-    let img_width  = 4000;
-    let img_height = 2300;
-    let starting_x_range = (-3.45, 0.05);
-    let starting_y_range = (-0.99,0.99);
-    let zoom_rate = 0.96;
-    let chunk_size = 4;
-    let palette = painter::generate_random_palette(
-        burning_ship_frac::MAX_ITERATIONS
+
+///    ### (I/O)
+///    Paints frame frames times, frame k with the palette rotated by
+///    painter::cycle_offset of k, and saves it as frame k. Returns the
+///    number of frames saved.
+fn save_palette_cycle<T : EscapeValue>(
+    config : &RenderConfig,
+    frame : &[Vec<T>],
+    palette : &[Rgb<u8>],
+    options : &painter::PaintOptions,
+    frames : u32
+) -> usize
+{
+    (0..frames).into_par_iter().filter(|frame_number|{
+        let offset = painter::cycle_offset(palette.len(), *frame_number as usize, frames as usize);
+        let buffer = painter::paint_frame(
+            config.img_width as u32,
+            config.img_height as u32,
+            frame,
+            palette,
+            options,
+            offset
+        );
+        // ⬇
+        painter::save_img_buff(buffer, *frame_number, options).map_err(|err|{
+            report_failed_save("palette cycle frame", *frame_number, &err);
+        }).is_ok()
+    }).count()
+}
+
+
+///    ### (I/O)
+///    Paints frame 0 of config like map_fractal_to_img_io_results with a
+///    crosshair over the zoom pivot, the point the animation zooms
+///    towards, and saves it as frame 0.
+fn save_center_sample<T : EscapeValue>(
+    config : &RenderConfig,
+    frame : &[Vec<T>],
+    palette : &[Rgb<u8>],
+    options : &painter::PaintOptions
+) -> ImgResult
+{
+    let mut buffer = painter::paint_frame(
+        config.img_width as u32,
+        config.img_height as u32,
+        frame,
+        palette,
+        options,
+        options.palette_offset(0)
+    );
+    // ⬇
+    annotate::decorate_frame(&mut buffer, config, 0, options);
+    annotate::draw_crosshair(&mut buffer, burning_ship_frac::frame_pixel(config, 0, config.zoom_pivot()));
+    // ⬇
+    painter::save_img_buff(buffer, 0, options)
+}
+
+
+///    ### (I/O)
+///    Renders frames 0..shot.frames of shot.config with render, paints
+///    them like map_fractal_to_img_io_results and saves frame k as frame
+///    shot.first_output + k. Frames that fail to save are logged.
+fn render_shot<T : EscapeValue>(
+    shot : &shotlist::Shot,
+    palette : &[Rgb<u8>],
+    options : &painter::PaintOptions,
+    args : &cli::Args,
+    render : impl Fn(&RenderConfig, u32) -> Result<Fractal<T>, RenderError> + Sync
+) -> Result<(), RenderError>
+{
+    let config = &shot.config;
+    (0..shot.frames).into_par_iter().try_for_each(|frame_number|{
+        let frame = render(config, frame_number)?;
+        let options = &*options.with_max_iterations(config.max_iterations_at(frame_number));
+        let mut buffer = painter::paint_frame(
+            config.img_width as u32,
+            config.img_height as u32,
+            &frame,
+            palette,
+            options,
+            options.palette_offset(frame_number)
+        );
+        // ⬇
+        annotate::decorate_frame(&mut buffer, config, frame_number, options);
+        // ⬇
+        let output_number = shot.first_output + frame_number;
+        let saved = match args.alpha_mask {
+            Some(soft_edge) => {
+                let masked = mask::apply_interior_alpha(&buffer, &frame, options.max_iterations, soft_edge, args.invert_mask);
+                painter::save_img_buff(masked, output_number, options)
+            },
+            None => painter::save_img_buff(buffer, output_number, options),
+        };
+        if let Err(err) = saved {
+            report_failed_save("frame", output_number, &err);
+        }
+        Ok(())
+    })
+}
+
+
+///    ### (I/O)
+///    Paints every NNNNNNNNNN.npy escape grid in dir with palette and
+///    options and saves it as frame NNNNNNNNNN, overlaid by decorate_frame
+///    with the viewport of config. Returns the number of frames recolored.
+fn recolor_frames(
+    dir : &str,
+    config : &RenderConfig,
+    palette : &[Rgb<u8>],
+    options : &painter::PaintOptions
+) -> std::io::Result<usize>
+{
+    let mut grids : Vec<(u32, String)> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "npy"))
+        .filter_map(|path|{
+            let frame_number = path.file_stem()?.to_str()?.parse().ok()?;
+            Some((frame_number, path.to_str()?.to_string()))
+        }).collect();
+    grids.sort();
+    grids.par_iter().try_for_each(|(frame_number, path)|{
+        let frame = npy::load_npy(path)?;
+        let height = frame.len() as u32;
+        let width = frame.first().map_or(0, |row| row.len()) as u32;
+        let options = &*options.with_max_iterations(config.max_iterations_at(*frame_number));
+        let mut buffer = painter::paint_frame(
+            width,
+            height,
+            &frame,
+            palette,
+            options,
+            options.palette_offset(*frame_number)
+        );
+        // ⬇
+        annotate::decorate_frame(&mut buffer, config, *frame_number, options);
+        // ⬇
+        painter::save_img_buff(buffer, *frame_number, options).map_err(|err|{
+            std::io::Error::other(format!("{}: {}", path, err))
+        })
+    })?;
+    Ok(grids.len())
+}
+
+
+///    ### (I/O)
+///    Runs the --on-frame-done shell command with frame_number and path
+///    as its arguments, warning when it cannot be started or fails.
+fn run_frame_hook(command : &str, frame_number : u32, path : &Path) {
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .arg("sh")
+        .arg(frame_number.to_string())
+        .arg(path)
+        .status();
+    match status {
+        Ok(status) if !status.success() => warn!("--on-frame-done failed for frame {}: {}", frame_number, status),
+        Err(err) => warn!("Could not run --on-frame-done for frame {}: {}", frame_number, err),
+        Ok(_) => (),
+    }
+}
+
+
+///    ### (I/O)
+///    Warns about frames of a burst starting at first_frame that took
+///    more than SLOW_FRAME_FACTOR times the median of all frame times so
+///    far, usually all-interior frames or frames near the precision wall.
+fn warn_slow_frames(first_frame : u32, burst_times : &[Duration], all_times : &[Duration]) {
+    let Some(summary) = stats::timing_summary(all_times) else {
+        return;
+    };
+    for (i, time) in burst_times.iter().enumerate() {
+        if *time > summary.median * SLOW_FRAME_FACTOR {
+            warn!(
+                "Frame {} took {:?}, over {}x the median frame time of {:?}",
+                first_frame as usize + i,
+                time,
+                SLOW_FRAME_FACTOR,
+                summary.median
+            );
+        }
+    }
+}
+
+///    ### (I/O)
+///    Routes log output to stdout via env_logger. RUST_LOG sets the
+///    level, --quiet (errors only) and --verbose (per-frame debug logs)
+///    take precedence over it.
+fn init_logger(args : &cli::Args) {
+    let mut builder = env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or("info")
     );
+    if args.quiet {
+        builder.filter_level(log::LevelFilter::Error);
+    } else if args.verbose {
+        builder.filter_level(log::LevelFilter::Debug);
+    }
+    builder
+    .target(env_logger::Target::Stdout)
+    .format(|buf, record| writeln!(buf, "{}", record.args()))
+    .init();
+}
+
+///    ### (PURE)
+///    Predicate to determine if frames painted with options need smooth
+///    f64 grids rather than escape counts: hybrid, shaded and potential
+///    coloring, dithering, heightmaps and alpha masks.
+fn needs_smooth_grid(options : &painter::PaintOptions, args : &cli::Args) -> bool {
+    options.coloring != painter::ColoringMode::Bands
+        || options.dither
+        || args.heightmap
+        || args.alpha_mask.is_some()
+}
+
+///    ### (PURE)
+///    Prefixes err with the path it concerns, keeping its kind.
+fn with_path(err : std::io::Error, path : &str) -> std::io::Error {
+    std::io::Error::new(err.kind(), format!("'{}': {}", path, err))
+}
 
-    let args: Vec<String> = env::args().collect();
-    let bursts = args
-    .get(1)
-    .unwrap_or_else(||{
-        println!("Did not specifiy burst argument! Program terminating");
+fn main() {
+    let args = cli::Args::parse();
+    if let Err(err) = args.validate() {
+        cli::Args::command().error(clap::ErrorKind::ValueValidation, err).exit();
+    }
+    init_logger(&args);
+    if let Some(builder) = parallel::pool_builder(args.threads, args.single_thread) {
+        // a global pool bounds both the across-frame and within-frame maps
+        builder
+        .build_global()
+        .unwrap_or_else(|err|{
+            error!("Failed to build thread pool: {}", err);
+            std::process::exit(1);
+        });
+    }
+    if let Err(err) = run(&args) {
+        error!("Error: {}", err);
         std::process::exit(1);
-    }).parse::<u16>()
-    .unwrap_or_else(|_|{
-        println!("Failed to parse burst argument!");
+    }
+    let failed_saves = FAILED_SAVES.load(Ordering::Relaxed);
+    if failed_saves > 0 {
+        error!("{} frames could not be saved", failed_saves);
         std::process::exit(1);
-    });
+    }
+}
+
+///    ### (I/O)
+///    Renders, recolors or previews frames as args asks, returning the
+///    first error that stops the run. Errors saving a single frame are
+///    logged and counted in FAILED_SAVES instead.
+fn run(args : &cli::Args) -> Result<(), FractalError> {
+
+    // --recolor defaults to the config recorded next to its grids
+    let from = args.from.clone().or_else(|| args.recolor.as_ref().map(|dir|{
+        format!("{}/{}", dir, metadata::SIDECAR_FILE)
+    }).filter(|path| std::path::Path::new(path).exists()));
+    let recorded = from.as_ref().map(|path| metadata::read_sidecar(path).map_err(|err| with_path(err, path))).transpose()?;
+    let mut config = match &recorded {
+        Some(sidecar) => sidecar.config.clone(),
+        None => args.render_config(),
+    };
+    config.validate()?;
+    if args.auto_center {
+        let center = explore::find_interesting_center(&config)?;
+        info!("Zooming towards ({}, {})", center.0, center.1);
+        config.zoom_center = Some(center);
+    }
+    if args.auto_iter {
+        config.max_iterations = explore::suggest_max_iterations(&config)?;
+        info!("Using max_iterations {}", config.max_iterations);
+    }
+    let paint_options = painter::PaintOptions {
+        max_iterations : config.max_iterations,
+        curve : args.palette_curve,
+        dither : args.dither,
+        cycle_speed : args.cycle_speed,
+        edges : args.edge_style(),
+        coloring : args.coloring(),
+        body_color : args.body_color,
+        interior_color : args.interior_color,
+        escape_floor : args.escape_floor,
+        floor_color : args.floor_color,
+        interior_coloring : match args.interior_palette {
+            Some(_) => painter::InteriorColoring::BoundaryDistance,
+            None => painter::InteriorColoring::Flat,
+        },
+        interior_palette : args.interior_palette.as_ref().map(|name|{
+            painter::generate_preset_palette(name, INTERIOR_PALETTE_LEN)
+                .expect("clap restricts --interior-palette to PRESET_NAMES")
+        }),
+        dpi : args.print.map(|print| print.dpi),
+        compression : args.png_compression(),
+        gamma : args.gamma,
+        contrast : args.contrast(),
+        vignette : args.vignette,
+        annotate : args.annotate,
+        stamp : args.stamp,
+    };
+    let (palette, recorded_palette) = match (&args.palette, &args.palette_file, &args.blend_seeds) {
+        (Some(name), _, _) => (painter::generate_preset_palette(name, config.max_iterations as usize + 1)
+            .expect("clap restricts --palette to PRESET_NAMES"), None),
+        (_, Some(path), _) => (palette_file::load_palette_from_file(path).map_err(|err| with_path(err, path))?, None),
+        (_, _, Some(seeds)) => {
+            let blended = RecordedPalette::Blended { seeds : seeds.clone(), mode : args.palette_blend() };
+            (blended.generate(config.max_iterations), Some(blended))
+        },
+        (None, None, None) => {
+            // --from repeats the recorded colors, --recolor picks fresh ones
+            let from_recorded = match (&args.from, &recorded, args.palette_seed) {
+                (Some(path), Some(sidecar), None) => sidecar.recorded_palette().map_err(|err| with_path(err, path))?,
+                _ => None,
+            };
+            let random = from_recorded.unwrap_or_else(|| RecordedPalette::Seeded(args.palette_seed.unwrap_or_else(rand::random)));
+            if let RecordedPalette::Seeded(seed) = random {
+                info!("Random palette seed {} (repeat these colors with --palette-seed {})", seed, seed);
+            }
+            (random.generate(config.max_iterations), Some(random))
+        },
+    };
+
+    painter::create_output_dir().map_err(|err| with_path(err, painter::OUTPUT_DIR))?;
+    if let Some(point) = args.trace {
+        let orbit = burning_ship_frac::trace_orbit(point, &config.orbit_params(0));
+        for (n, (re, im)) in orbit.iter().enumerate() {
+            info!("Z[{}] = {} {:+}i", n, re, im);
+        }
+        info!("Orbit rate {} of max_iterations {}", orbit.len() - 1, config.max_iterations);
+        let path = trace::save_orbit_plot(&orbit)?;
+        info!("Saved orbit plot to {}", path);
+        return Ok(());
+    }
+    if args.show_palette {
+        let path = painter::save_palette_strip(&palette)?;
+        info!("Saved palette of {} colors to {}", palette.len(), path);
+        let path = palette_file::export_palette(&palette)?;
+        info!("Saved it as a hex list to {}", path);
+        return Ok(());
+    }
+    if args.sample_center {
+        let saved = if paint_options.coloring == painter::ColoringMode::Bands {
+            let grid : Fractal<u32> = burning_ship_frac::render_escape_grid(&config, 0)?;
+            save_center_sample(&config, &grid, &palette, &paint_options)
+        } else {
+            let grid = burning_ship_frac::render_smooth_grid(&config, 0)?;
+            save_center_sample(&config, &grid, &palette, &paint_options)
+        };
+        saved?;
+        let (x, y) = config.zoom_pivot();
+        info!("Saved frame 0 marking the zoom center ({}, {})", x, y);
+        return Ok(());
+    }
+    if let Some(levels) = args.insets {
+        let buffer = inset::render_insets(&config, &palette, &paint_options, levels, args.inset_zoom)?;
+        painter::save_img_buff(buffer, 0, &paint_options)?;
+        info!("Saved frame 0 with {} insets magnified {} times each", levels, args.inset_zoom);
+        return Ok(());
+    }
+    if let Some(count) = args.strip {
+        let strip = thumbnail::render_strip(&config, &palette, &paint_options, args.total_frames(), count.get())?;
+        let path = thumbnail::save_strip(&strip)?;
+        info!("Saved a strip of {} frames to {}", count, path);
+        return Ok(());
+    }
+    if let Some(count) = args.viewport_svg {
+        let frames = thumbnail::strip_frames(args.total_frames(), count.get());
+        let path = viewport_svg::save_viewport_svg(&config, &frames)?;
+        info!("Saved the viewports of {} frames to {}", frames.len(), path);
+        return Ok(());
+    }
+    if let Some(every) = args.thumbnail_gif {
+        let every = every.get();
+        let thumbnails = thumbnail::render_thumbnails(&config, &palette, &paint_options, args.total_frames(), every)
+            ?;
+        let count = thumbnails.len();
+        let path = thumbnail::save_thumbnail_gif(thumbnails)?;
+        info!("Saved {} thumbnail frames to {}", count, path);
+        return Ok(());
+    }
+    if args.difference {
+        // the last frame of the animation has no next frame to compare to
+        let frame_range = args.frame_range();
+        let frames = frame_range.start..frame_range.end.min(args.total_frames().saturating_sub(1));
+        let count = frames.len();
+        frames.into_par_iter().try_for_each(|frame_number|{
+            let grid = difference::render_difference_grid(&config, frame_number)?;
+            let buffer = difference::paint_difference(config.img_width as u32, config.img_height as u32, &grid);
+            if let Err(err) = painter::save_img_buff(buffer, frame_number, &paint_options) {
+                report_failed_save("difference frame", frame_number, &err);
+            }
+            Ok::<(), RenderError>(())
+        })?;
+        info!("Saved {} difference frames", count);
+        return Ok(());
+    }
+    if let Some(frames) = args.cycle_palette {
+        let frames = frames.get();
+        let source_frame = args.frame_range().start;
+        let count = if paint_options.coloring == painter::ColoringMode::Bands {
+            let grid : Fractal<u32> = burning_ship_frac::render_escape_grid(&config, source_frame)?;
+            save_palette_cycle(&config, &grid, &palette, &paint_options, frames)
+        } else {
+            let grid = burning_ship_frac::render_smooth_grid(&config, source_frame)?;
+            save_palette_cycle(&config, &grid, &palette, &paint_options, frames)
+        };
+        info!("Saved {} palette cycle frames of frame {}", count, source_frame);
+        return Ok(());
+    }
+    if let Some(path) = &args.shotlist {
+        let shots = shotlist::read_shotlist(path).map_err(|err| with_path(err, path))?;
+        // palette flags override the palette each shot recorded
+        let palette_flags = args.palette.is_some()
+            || args.palette_file.is_some()
+            || args.blend_seeds.is_some()
+            || args.palette_seed.is_some();
+        for shot in &shots {
+            info!("Shot {}: frames {} to {}", shot.config_path, shot.first_output, shot.first_output + shot.frames);
+            let options = painter::PaintOptions { max_iterations : shot.config.max_iterations, ..paint_options.clone() };
+            let shot_palette = shot.palette.as_ref().filter(|_| !palette_flags).map(|recorded|{
+                recorded.generate(shot.config.max_iterations)
+            });
+            let palette = shot_palette.as_deref().unwrap_or(&palette);
+            let smooth = needs_smooth_grid(&options, args);
+            if smooth {
+                render_shot(shot, palette, &options, args, burning_ship_frac::render_smooth_grid)?;
+            } else {
+                render_shot(shot, palette, &options, args, burning_ship_frac::render_escape_grid::<u32>)?;
+            }
+        }
+        info!("Rendered {} shots", shots.len());
+        return Ok(());
+    }
+    if let Some(dir) = &args.recolor {
+        let count = recolor_frames(dir, &config, &palette, &paint_options).map_err(|err| with_path(err, dir))?;
+        info!("Recolored {} frames from {}", count, dir);
+        return Ok(());
+    }
+    let mut sidecar = metadata::Sidecar {
+        config : config.clone(),
+        max_escapes : Default::default(),
+        iteration_caps : Default::default(),
+        palette_seed : None,
+        blend_seeds : Vec::new(),
+        palette_blend : None,
+    };
+    sidecar.record_palette(recorded_palette.as_ref());
+    let save_sidecar = |sidecar : &metadata::Sidecar| {
+        if args.compute_only {
+            return;
+        }
+        metadata::write_sidecar(sidecar, &metadata::sidecar_path()).unwrap_or_else(|err|{
+            error!("Could not write '{}': {}", metadata::sidecar_path(), err);
+        });
+    };
+    save_sidecar(&sidecar);
+
 
+    let frame_range = args.frame_range();
+    // escape samples for --tensor, upscaled low resolution grids for
+    // previews, edge anti-aliased grids with --edge-aa, blended escape
+    // counts with --blend-map, smooth grids when needs_smooth_grid,
+    // otherwise u8 grids when the orbit rates of the deepest frame fit
+    // in a byte and u32 above that
+    let peak_max_iterations = config.max_iterations_at(frame_range.end.saturating_sub(1));
+    let smooth = needs_smooth_grid(&paint_options, args);
+    let cell_bytes = if args.tensor {
+        std::mem::size_of::<burning_ship_frac::EscapeSample>()
+    } else if args.preview_scale.is_some() || args.edge_aa || args.blend_map().is_some() || smooth {
+        std::mem::size_of::<f64>()
+    } else if peak_max_iterations <= u8::MAX_COUNT {
+        std::mem::size_of::<u8>()
+    } else {
+        std::mem::size_of::<u32>()
+    };
+    if smooth && args.reproject().is_some() {
+        warn!("--reproject only applies to escape count frames, rendering every frame in full");
+    }
+    let burst_size = args.burst_size(&config, cell_bytes);
+    info!("Rendering {} frames per burst within {} MB", burst_size, args.mem_budget);
 
     //main program loop, 
-    //generates and saves frames in burst of chunk_size
+    //generates and saves frames in bursts of burst_size, or of the
+    //sizes the tuner picks with --tune-bursts
+    let frame_hook = args.on_frame_done.as_deref().map(|command|{
+        move |frame_number : u32, path : &Path| run_frame_hook(command, frame_number, path)
+    });
+    let on_frame_done : FrameDone = frame_hook.as_ref().map(|hook| hook as _);
     let total_timer = Instant::now();
-    for i in 0..bursts{
-        let first_frame = i * chunk_size;
-        let last_frame = first_frame + chunk_size;
-        gen_and_save_frames(
-            img_width, 
-            img_height, 
-            starting_x_range, 
-            starting_y_range, 
-            zoom_rate,
-            first_frame,
-            last_frame,
-            &palette
+    let mut frame_times : Vec<Duration> = Vec::new();
+    let mut warmup_times : Vec<Duration> = Vec::new();
+    let mut tuner = args.tune_bursts.then(|| tuner::BurstTuner::new(burst_size));
+    let mut next_frame = frame_range.start;
+    let mut rendered_end = frame_range.end;
+    while next_frame < frame_range.end {
+        let first_frame = next_frame;
+        let burst_size = tuner.as_ref().map_or(burst_size, |tuner| tuner.next_size());
+        let last_frame = first_frame.saturating_add(burst_size).min(frame_range.end);
+        next_frame = last_frame;
+        let burst_timer = Instant::now();
+        let exhausted_frame = (first_frame..last_frame).find(|frame_number|{
+            burning_ship_frac::precision_exhausted(&config, *frame_number)
+        });
+        let last_frame = exhausted_frame.unwrap_or(last_frame);
+        if first_frame < last_frame {
+            let (burst_times, burst_max_escapes) = if args.tensor {
+                gen_and_save_frames(
+                    &config,
+                    first_frame..last_frame,
+                    &palette,
+                    &paint_options,
+                    args,
+                    burning_ship_frac::render_tensor_grid,
+                    on_frame_done
+                )
+            } else if let Some(scale) = args.preview_scale {
+                gen_and_save_frames(
+                    &config,
+                    first_frame..last_frame,
+                    &palette,
+                    &paint_options,
+                    args,
+                    |config, frame_number| resample::render_preview_grid(config, frame_number, scale.get()),
+                    on_frame_done
+                )
+            } else if args.edge_aa {
+                gen_and_save_frames(
+                    &config,
+                    first_frame..last_frame,
+                    &palette,
+                    &paint_options,
+                    args,
+                    |config, frame_number| antialias::render_edge_aa_grid(config, frame_number, args.aa_filter(), args.aa_jitter),
+                    on_frame_done
+                )
+            } else if let Some(map) = args.blend_map() {
+                gen_and_save_frames(
+                    &config,
+                    first_frame..last_frame,
+                    &palette,
+                    &paint_options,
+                    args,
+                    |config, frame_number| map.render_blended_grid(config, frame_number, args.blend_weight),
+                    on_frame_done
+                )
+            } else if smooth {
+                gen_and_save_frames(
+                    &config,
+                    first_frame..last_frame,
+                    &palette,
+                    &paint_options,
+                    args,
+                    burning_ship_frac::render_smooth_grid,
+                    on_frame_done
+                )
+            } else if let Some(quality) = args.reproject() {
+                if peak_max_iterations <= u8::MAX_COUNT {
+                    gen_and_save_reprojected_frames::<u8>(
+                        &config,
+                        first_frame..last_frame,
+                        &palette,
+                        &paint_options,
+                        args,
+                        quality,
+                        on_frame_done
+                    )
+                } else {
+                    gen_and_save_reprojected_frames::<u32>(
+                        &config,
+                        first_frame..last_frame,
+                        &palette,
+                        &paint_options,
+                        args,
+                        quality,
+                        on_frame_done
+                    )
+                }
+            } else if peak_max_iterations <= u8::MAX_COUNT {
+                gen_and_save_frames(
+                    &config,
+                    first_frame..last_frame,
+                    &palette,
+                    &paint_options,
+                    args,
+                    burning_ship_frac::render_escape_grid::<u8>,
+                    on_frame_done
+                )
+            } else {
+                gen_and_save_frames(
+                    &config,
+                    first_frame..last_frame,
+                    &palette,
+                    &paint_options,
+                    args,
+                    burning_ship_frac::render_escape_grid::<u32>,
+                    on_frame_done
+                )
+            }?;
+            let (warmup, steady_times) = stats::split_warmup(&burst_times, args.separate_warmup);
+            if let Some(warmup) = warmup {
+                let steady = stats::timing_summary(steady_times).map_or(String::new(), |summary|{
+                    format!(", steady state mean {:?} over {} frames", summary.mean, steady_times.len())
+                });
+                info!("Warmup frame {} took {:?}{}", first_frame, warmup, steady);
+                warmup_times.push(warmup);
+            }
+            frame_times.extend_from_slice(steady_times);
+            warn_slow_frames(last_frame - steady_times.len() as u32, steady_times, &frame_times);
+            sidecar.max_escapes.extend((first_frame..).zip(burst_max_escapes));
+            if config.iteration_growth.is_some() {
+                sidecar.iteration_caps.extend((first_frame..last_frame).map(|frame_number|{
+                    (frame_number, config.max_iterations_at(frame_number))
+                }));
+            }
+            save_sidecar(&sidecar);
+            if let Some(tuner) = &mut tuner {
+                let settled = tuner.settled();
+                tuner.record(burst_size, last_frame - first_frame, burst_timer.elapsed());
+                if !settled && tuner.settled() {
+                    info!("Tuned burst size to {} frames", tuner.next_size());
+                }
+            }
+        }
+        if let Some(frame_number) = exhausted_frame {
+            info!(
+                "{}\nFrame {} zooms past float precision, neighbouring pixels would map \
+                 to the same point. Stopping the animation.\n{}",
+                PRINT_ROW,
+                frame_number,
+                PRINT_ROW
+            );
+            rendered_end = frame_number;
+            break;
+        }
+    }
+    if let Some(count) = args.interpolate {
+        let written = interpolate::interpolate_saved_frames(frame_range.start..rendered_end, count.get(), &paint_options)?;
+        info!("Saved {} frames with {} in-betweens each to {}/{}", written, count, painter::OUTPUT_DIR, interpolate::INTERPOLATED_DIR);
+    }
+    if let Some(summary) = stats::timing_summary(&frame_times) {
+        info!("{}", summary);
+    }
+    if let Some(summary) = stats::timing_summary(&warmup_times) {
+        info!("Warmup (first frame of each burst, left out above) {}", summary);
+    }
+    if let Some(max_escape) = sidecar.max_escapes.values().max() {
+        let capped = sidecar.max_escapes.iter().any(|(frame_number, max_escape)|{
+            *max_escape >= config.max_iterations_at(*frame_number)
+        });
+        info!(
+            "Highest escape reached: {} of max_iterations {}{}",
+            max_escape,
+            config.max_iterations_at(frame_range.end.saturating_sub(1)),
+            if !capped { ", --max-iterations could be lowered" } else { "" }
         );
     }
-    println!("{}\nTotal Runtime: {:?}\n{}", PRINT_ROW, total_timer.elapsed(), PRINT_ROW)
+    if args.title_card && frame_range.start < rendered_end {
+        let last_frame = rendered_end - 1;
+        let summary = title_card::RenderSummary {
+            frames : rendered_end - frame_range.start,
+            final_magnification : config.magnification(last_frame),
+            max_iterations : config.max_iterations_at(last_frame),
+            peak_escape : sidecar.max_escapes.values().max().copied(),
+            total_time : total_timer.elapsed(),
+        };
+        let path = title_card::save_title_card(&summary, &palette)?;
+        info!("Saved title card to {}", path);
+    }
+    info!("{}\nTotal Runtime: {:?}\n{}", PRINT_ROW, total_timer.elapsed(), PRINT_ROW);
+    Ok(())
 }
@@ -0,0 +1,45 @@
+//!   Module contains funcs deriving an alpha channel from the
+//!   escape grid, so the interior of the ship (or everything
+//!   but it) can be layered over other backgrounds.
+//!   @author Van Gouache
+
+use image::{ImageBuffer, Rgb, Rgba};
+use crate::burning_ship_frac::EscapeValue;
+
+
+///    ### (PURE)
+///    Opacity \[0 to 1\] of a cell with escape value value. Interior cells
+///    are opaque, escaped cells transparent, except that cells within
+///    soft_edge iterations of max_iterations fade in linearly towards the
+///    interior. A soft_edge of 0.0 gives a hard edge.
+pub fn interior_opacity(value : f64, max_iterations : u32, soft_edge : f64) -> f64 {
+    let distance = max_iterations as f64 - value;
+    if distance <= 0.0 {
+        return 1.0;
+    }
+    if soft_edge <= 0.0 {
+        return 0.0;
+    }
+    (1.0 - distance / soft_edge).max(0.0)
+}
+
+
+///    ### (PURE)
+///    Adds an alpha channel from frame to the painted buffer, see
+///    interior_opacity. With invert, the interior is transparent and the
+///    exterior opaque instead.
+pub fn apply_interior_alpha<T : EscapeValue>(
+    buffer : &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    frame : &[Vec<T>],
+    max_iterations : u32,
+    soft_edge : f64,
+    invert : bool
+) -> ImageBuffer<Rgba<u8>, Vec<u8>>
+{
+    ImageBuffer::from_fn(buffer.width(), buffer.height(), |x, y|{
+        let Rgb([r, g, b]) = *buffer.get_pixel(x, y);
+        let opacity = interior_opacity(frame[y as usize][x as usize].escape_value(), max_iterations, soft_edge);
+        let opacity = if invert { 1.0 - opacity } else { opacity };
+        Rgba([r, g, b, (opacity * u8::MAX as f64).round() as u8])
+    })
+}
@@ -0,0 +1,143 @@
+//!   Module contains the JSON sidecar written next to the
+//!   frames of a render, recording what produced them so the
+//!   render can be repeated or continued with --from.
+//!   @author Van Gouache
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Error, ErrorKind};
+use image::Rgb;
+use serde::{Deserialize, Serialize};
+use crate::config::RenderConfig;
+use crate::painter::{self, BlendMode, OUTPUT_DIR};
+
+//file name of the sidecar inside OUTPUT_DIR
+pub const SIDECAR_FILE : &str = "render.json";
+
+
+///    Contents of the sidecar JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sidecar {
+    pub config : RenderConfig,
+    ///    Largest orbit rate reached in each rendered frame, by frame
+    ///    number. Filled in burst by burst as frames are saved.
+    #[serde(default)]
+    pub max_escapes : BTreeMap<u32, u32>,
+    ///    max_iterations of each rendered frame by frame number, filled
+    ///    like max_escapes when config.iteration_growth is set.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub iteration_caps : BTreeMap<u32, u32>,
+    ///    Seed of the random palette, see generate_seeded_palette. None
+    ///    for preset palettes.
+    #[serde(default)]
+    pub palette_seed : Option<u64>,
+    ///    Seeds and mode name of a --blend-seeds palette, see
+    ///    generate_blended_palette. Empty and None otherwise.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blend_seeds : Vec<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette_blend : Option<String>,
+}
+
+impl Sidecar {
+    ///    ### (PURE)
+    ///    Random palette recorded in the sidecar, its blend seeds over
+    ///    palette_seed. None for preset palettes. Fails on a palette_blend
+    ///    that is not one of BLEND_NAMES.
+    pub fn recorded_palette(&self) -> std::io::Result<Option<RecordedPalette>> {
+        if self.blend_seeds.is_empty() {
+            return Ok(self.palette_seed.map(RecordedPalette::Seeded));
+        }
+        let mode = match self.palette_blend.as_deref() {
+            Some(name) => BlendMode::from_name(name).ok_or_else(||{
+                Error::new(ErrorKind::InvalidData, format!("unknown palette blend '{}'", name))
+            })?,
+            None => BlendMode::default(),
+        };
+        Ok(Some(RecordedPalette::Blended { seeds : self.blend_seeds.clone(), mode }))
+    }
+
+    ///    ### (PURE)
+    ///    Records palette in palette_seed, blend_seeds and palette_blend,
+    ///    clearing them for None, the inverse of recorded_palette.
+    pub fn record_palette(&mut self, palette : Option<&RecordedPalette>) {
+        (self.palette_seed, self.blend_seeds, self.palette_blend) = match palette {
+            Some(RecordedPalette::Seeded(seed)) => (Some(*seed), Vec::new(), None),
+            Some(RecordedPalette::Blended { seeds, mode }) => (None, seeds.clone(), Some(mode.name().to_string())),
+            None => (None, Vec::new(), None),
+        };
+    }
+}
+
+
+///    Random palette a sidecar records, so --from and --shotlist can
+///    repeat its colors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedPalette {
+    ///    generate_seeded_palette of the seed.
+    Seeded(u64),
+    ///    generate_blended_palette of seeds under mode.
+    Blended { seeds : Vec<u64>, mode : BlendMode },
+}
+
+impl RecordedPalette {
+    ///    ### (PURE)
+    ///    The palette, sized for max_iterations.
+    pub fn generate(&self, max_iterations : u32) -> Vec<Rgb<u8>> {
+        match self {
+            RecordedPalette::Seeded(seed) => painter::generate_seeded_palette(max_iterations, *seed),
+            RecordedPalette::Blended { seeds, mode } => painter::generate_blended_palette(seeds, max_iterations as usize + 1, *mode),
+        }
+    }
+}
+
+
+///    ### (PURE)
+///    Path of the sidecar of the current render, "{OUTPUT_DIR}/render.json".
+pub fn sidecar_path() -> String {
+    format!("{}/{}", OUTPUT_DIR, SIDECAR_FILE)
+}
+
+
+///    ### (I/O)
+///    Writes sidecar to path as pretty printed JSON.
+pub fn write_sidecar(sidecar : &Sidecar, path : &str) -> std::io::Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(writer, sidecar).map_err(std::io::Error::from)
+}
+
+
+///    ### (I/O)
+///    Reads a sidecar previously written by write_sidecar.
+pub fn read_sidecar(path : &str) -> std::io::Result<Sidecar> {
+    let reader = BufReader::new(File::open(path)?);
+    serde_json::from_reader(reader).map_err(std::io::Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_palettes_round_trip_through_the_sidecar() {
+        let mut sidecar = Sidecar {
+            config : RenderConfig::default(),
+            max_escapes : BTreeMap::new(),
+            iteration_caps : BTreeMap::new(),
+            palette_seed : None,
+            blend_seeds : Vec::new(),
+            palette_blend : None,
+        };
+        assert_eq!(sidecar.recorded_palette().unwrap(), None);
+        for palette in [RecordedPalette::Seeded(7), RecordedPalette::Blended { seeds : vec![1, 2], mode : BlendMode::PerBand }] {
+            sidecar.record_palette(Some(&palette));
+            assert_eq!(sidecar.recorded_palette().unwrap(), Some(palette));
+        }
+        assert_eq!(
+            RecordedPalette::Blended { seeds : vec![1, 2], mode : BlendMode::PerBand }.generate(40),
+            painter::generate_blended_palette(&[1, 2], 41, BlendMode::PerBand)
+        );
+        sidecar.palette_blend = Some("bogus".to_string());
+        assert!(sidecar.recorded_palette().is_err());
+    }
+}
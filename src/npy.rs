@@ -0,0 +1,174 @@
+//!   Module contains a writer and reader for escape grids in the
+//!   NumPy .npy format (version 1.0), so raw orbit rates can be
+//!   analysed with numpy.load instead of a painted image, and
+//!   painted again later without recomputing them.
+//!   @author Van Gouache
+
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use crate::burning_ship_frac::EscapeSample;
+use crate::painter;
+
+const MAGIC : &[u8] = b"\x93NUMPY";
+//magic, version and header length precede the header dict
+const PREAMBLE_LEN : usize = 10;
+
+
+///    Grid cell type with a fixed-size little endian numpy dtype.
+pub trait NpyElement : Copy {
+    ///    numpy dtype descriptor, e.g. "<u4".
+    const DESCR : &'static str;
+    ///    Values per cell, more than one adds a trailing axis of this
+    ///    length to the array shape.
+    const CHANNELS : usize = 1;
+
+    ///    Appends the little endian bytes of the cell to out.
+    fn write_le(self, out : &mut Vec<u8>);
+}
+
+impl NpyElement for u8 {
+    const DESCR : &'static str = "|u1";
+
+    fn write_le(self, out : &mut Vec<u8>) {
+        out.push(self);
+    }
+}
+
+impl NpyElement for u16 {
+    const DESCR : &'static str = "<u2";
+
+    fn write_le(self, out : &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl NpyElement for u32 {
+    const DESCR : &'static str = "<u4";
+
+    fn write_le(self, out : &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl NpyElement for f64 {
+    const DESCR : &'static str = "<f8";
+
+    fn write_le(self, out : &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl NpyElement for EscapeSample {
+    const DESCR : &'static str = "<f4";
+    const CHANNELS : usize = 3;
+
+    fn write_le(self, out : &mut Vec<u8>) {
+        for channel in self.channels() {
+            out.extend_from_slice(&channel.to_le_bytes());
+        }
+    }
+}
+
+
+///    ### (PURE)
+///    Encodes frame as a C-order .npy array of shape (height, width), or
+///    (height, width, channels) for cells of several channels.
+///    The header dict is padded with spaces so the data starts on a 64
+///    byte boundary, as the format requires.
+pub fn encode_npy<T : NpyElement>(frame : &[Vec<T>]) -> Vec<u8> {
+    let height = frame.len();
+    let width = frame.first().map_or(0, |row| row.len());
+    let shape = match T::CHANNELS {
+        1 => format!("({}, {})", height, width),
+        channels => format!("({}, {}, {})", height, width, channels),
+    };
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        T::DESCR,
+        shape
+    );
+    let padding = 63 - (PREAMBLE_LEN + header.len()) % 64;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+
+    let mut out = Vec::with_capacity(PREAMBLE_LEN + header.len() + height * width * std::mem::size_of::<T>());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&[1, 0]);
+    out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    out.extend_from_slice(header.as_bytes());
+    for cell in frame.iter().flatten() {
+        cell.write_le(&mut out);
+    }
+    out
+}
+
+
+///    ### (I/O)
+///    Writes frame to "{OUTPUT_DIR}/{frame_number}.npy".
+pub fn save_npy<T : NpyElement>(frame : &[Vec<T>], frame_number : u32) -> std::io::Result<()> {
+    let path = painter::frame_path(frame_number, "npy");
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(&encode_npy(frame))?;
+    writer.flush()
+}
+
+
+///    ### (PURE)
+///    Value of the header dict entry key, the text between "'key': " and
+///    the next ", '" or closing brace.
+fn header_value<'a>(header : &'a str, key : &str) -> Option<&'a str> {
+    let start = header.find(&format!("'{}':", key))? + key.len() + 3;
+    let rest = header[start..].trim_start();
+    let end = match rest.starts_with('(') {
+        true => rest.find(')')? + 1,
+        false => rest.find([',', '}'])?,
+    };
+    Some(rest[..end].trim())
+}
+
+
+///    ### (PURE)
+///    Decodes a C-order 2D .npy array of any dtype written by encode_npy
+///    into escape values. Integer counts convert to f64 exactly.
+pub fn decode_npy(bytes : &[u8]) -> std::io::Result<Vec<Vec<f64>>> {
+    let invalid = |reason : &str| Error::new(ErrorKind::InvalidData, format!("not an escape grid .npy: {}", reason));
+    if bytes.len() < PREAMBLE_LEN || !bytes.starts_with(MAGIC) || bytes[6] != 1 {
+        return Err(invalid("expected a version 1.0 .npy file"));
+    }
+    let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    let header = bytes.get(PREAMBLE_LEN..PREAMBLE_LEN + header_len)
+        .and_then(|header| std::str::from_utf8(header).ok())
+        .ok_or_else(|| invalid("truncated header"))?;
+    if header_value(header, "fortran_order") != Some("False") {
+        return Err(invalid("expected C order"));
+    }
+    let (cell_len, decode) : (usize, fn(&[u8]) -> f64) = match header_value(header, "descr") {
+        Some("'|u1'") => (1, |cell| cell[0] as f64),
+        Some("'<u2'") => (2, |cell| u16::from_le_bytes([cell[0], cell[1]]) as f64),
+        Some("'<u4'") => (4, |cell| u32::from_le_bytes(cell.try_into().unwrap()) as f64),
+        Some("'<f8'") => (8, |cell| f64::from_le_bytes(cell.try_into().unwrap())),
+        _ => return Err(invalid("unsupported dtype")),
+    };
+    let shape : Vec<usize> = header_value(header, "shape")
+        .map(|shape| shape.trim_matches(['(', ')']).split(',').filter_map(|dim| dim.trim().parse().ok()).collect())
+        .unwrap_or_default();
+    let [height, width] = shape[..] else {
+        return Err(invalid("expected a 2D shape"));
+    };
+    let data = &bytes[PREAMBLE_LEN + header_len..];
+    if data.len() != height * width * cell_len {
+        return Err(invalid("data does not match its shape"));
+    }
+    Ok(data
+        .chunks_exact((width * cell_len).max(1))
+        .take(height)
+        .map(|row| row.chunks_exact(cell_len).map(decode).collect())
+        .collect())
+}
+
+
+///    ### (I/O)
+///    Reads the .npy escape grid at path, see decode_npy.
+pub fn load_npy(path : &str) -> std::io::Result<Vec<Vec<f64>>> {
+    decode_npy(&std::fs::read(path)?)
+}
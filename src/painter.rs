@@ -4,13 +4,475 @@
 //!   matrix and storing image on the hard disk.
 //!   @author Van Gouache
  
+use std::borrow::Cow;
+use std::sync::Once;
 use rand::prelude::*;
+use crate::parallel::*;
 use image::*;
+use crate::burning_ship_frac::{potential, render_escape_grid, render_smooth_grid, EscapeValue, Fractal, RenderError, MAX_ITERATIONS};
+use crate::config::RenderConfig;
+use crate::{regions, stats};
+
+//directory frames are saved to
+pub const OUTPUT_DIR : &str = "frames";
+//file name of the palette preview strip inside OUTPUT_DIR
+pub const PALETTE_FILE : &str = "palette.png";
+//names of the built in colormaps of generate_preset_palette
+pub const PRESET_NAMES : [&str; 3] = ["viridis", "magma", "cividis"];
+//names accepted by BlendMode::from_name
+pub const BLEND_NAMES : [&str; 2] = ["average", "per-band"];
+//evenly spaced control points of the matplotlib colormaps
+const VIRIDIS : [[u8; 3]; 9] = [
+    [68, 1, 84], [71, 45, 123], [59, 82, 139], [44, 114, 142], [33, 145, 140],
+    [40, 174, 128], [94, 201, 98], [173, 220, 48], [253, 231, 37],
+];
+const MAGMA : [[u8; 3]; 9] = [
+    [0, 0, 4], [28, 16, 68], [79, 18, 123], [129, 37, 129], [181, 54, 122],
+    [229, 80, 100], [251, 135, 97], [254, 194, 135], [252, 253, 191],
+];
+const CIVIDIS : [[u8; 3]; 10] = [
+    [0, 34, 78], [18, 53, 112], [59, 73, 108], [87, 93, 109], [112, 113, 115],
+    [138, 135, 121], [166, 157, 117], [196, 181, 108], [228, 207, 91], [254, 232, 56],
+];
+//pixel width of each color band and height of the palette preview strip
+const STRIP_BAND_WIDTH : u32 = 4;
+const STRIP_HEIGHT : u32 = 64;
+//paper color between the lines of Contours coloring
+const CONTOUR_PAPER : Rgb<u8> = Rgb([246, 242, 230]);
+
+
+///    Trade-off between PNG encoding speed and file size of saved frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PngCompression {
+    ///    Fastest deflate level and no row filters, for preview batches.
+    Fast,
+    #[default]
+    Default,
+    ///    Strongest deflate level with adaptive row filters, for delivery.
+    Best,
+}
+
+impl PngCompression {
+    ///    ### (PURE)
+    ///    Settings of image's PngEncoder for this level.
+    pub fn encoder_settings(&self) -> (codecs::png::CompressionType, codecs::png::FilterType) {
+        use codecs::png::{CompressionType, FilterType};
+        match self {
+            PngCompression::Fast => (CompressionType::Fast, FilterType::NoFilter),
+            PngCompression::Default => (CompressionType::Default, FilterType::Adaptive),
+            PngCompression::Best => (CompressionType::Best, FilterType::Adaptive),
+        }
+    }
+}
+
+
+///    Curve the escape value is mapped through before it indexes the
+///    palette. Escape counts cluster near low values, so the non-linear
+///    curves spread them over more of the palette.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PaletteCurve {
+    ///    Escape value indexes the palette directly.
+    #[default]
+    Linear,
+    ///    ln(1 + v) / ln(1 + max_iterations)
+    Log,
+    ///    sqrt(v / max_iterations)
+    Sqrt,
+    ///    (v / max_iterations)^p
+    Power(f64),
+}
+
+impl PaletteCurve {
+    ///    ### (PURE)
+    ///    Curve called name: "linear", "log", "sqrt" or "power=P" with P
+    ///    a finite exponent above 0.
+    pub fn from_name(name : &str) -> Option<Self> {
+        match name {
+            "linear" => Some(PaletteCurve::Linear),
+            "log" => Some(PaletteCurve::Log),
+            "sqrt" => Some(PaletteCurve::Sqrt),
+            _ => name
+                .strip_prefix("power=")
+                .and_then(|p| p.parse::<f64>().ok())
+                .filter(|p| p.is_finite() && *p > 0.0)
+                .map(PaletteCurve::Power),
+        }
+    }
+
+    ///    ### (PURE)
+    ///    Maps a (possibly smooth) escape value \[0 to max_iterations\]
+    ///    to a fractional position in a palette of palette_len colors.
+    pub fn palette_position(&self, escape : f64, max_iterations : u32, palette_len : usize) -> f64 {
+        let max = max_iterations as f64;
+        let t = escape / max;
+        let curved = match self {
+            PaletteCurve::Linear => return escape,
+            PaletteCurve::Log => escape.ln_1p() / max.ln_1p(),
+            PaletteCurve::Sqrt => t.sqrt(),
+            PaletteCurve::Power(p) => t.powf(*p),
+        };
+        curved * palette_len.saturating_sub(1) as f64
+    }
+
+    ///    ### (PURE)
+    ///    Maps a (possibly smooth) escape value \[0 to max_iterations\]
+    ///    to an index into a palette of palette_len colors.
+    pub fn palette_index(&self, escape : f64, max_iterations : u32, palette_len : usize) -> usize {
+        self.palette_position(escape, max_iterations, palette_len).round() as usize
+    }
+}
+
+
+///    Contrast stretch of the orbit rates before they index the palette:
+///    rates from low to high are spread linearly over 0 to
+///    max_iterations - 1, so frames using a narrow band of rates still
+///    use the whole palette. Rates outside the bounds are clamped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContrastStretch {
+    ///    Bounds of each frame, the lowest and highest orbit rate of its
+    ///    escaping cells. Adapts to every frame but colors can shift from
+    ///    one frame to the next.
+    PerFrame,
+    ///    The same bounds for every frame, keeping an animation's colors
+    ///    stable.
+    Fixed { low : u32, high : u32 },
+}
+
+impl ContrastStretch {
+    ///    ### (PURE)
+    ///    Bounds stretched over the palette for frame, None when the
+    ///    frame has no escaping cells to take them from.
+    pub fn bounds<T : EscapeValue>(&self, frame : &[Vec<T>], max_iterations : u32) -> Option<(u32, u32)> {
+        match self {
+            ContrastStretch::PerFrame => stats::escape_bounds(frame, max_iterations),
+            ContrastStretch::Fixed { low, high } => Some((*low, *high)),
+        }
+    }
+}
+
+
+///    ### (PURE)
+///    Orbit rate escape stretched from bounds (low, high) to 0 to
+///    max_iterations - 1, see ContrastStretch. Bounds holding a single
+///    rate map it to 0.
+pub fn stretch_escape(escape : u32, (low, high) : (u32, u32), max_iterations : u32) -> u32 {
+    if high <= low {
+        return 0;
+    }
+    let t = (escape.clamp(low, high) - low) as f64 / (high - low) as f64;
+    (t * max_iterations.saturating_sub(1) as f64).round() as u32
+}
+
+
+//4x4 Bayer matrix, thresholds 0 to 15
+const BAYER_MATRIX : [[u8; 4]; 4] = [
+    [0, 8, 2, 10],
+    [12, 4, 14, 6],
+    [3, 11, 1, 9],
+    [15, 7, 13, 5],
+];
+
+///    ### (PURE)
+///    Ordered dither offset in \[-0.5, 0.5) for the pixel at (x, y).
+fn bayer_offset(x : usize, y : usize) -> f64 {
+    (BAYER_MATRIX[y % 4][x % 4] as f64 + 0.5) / 16.0 - 0.5
+}
+
+
+///    How interior cells (escape value of max_iterations) are colored.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum InteriorColoring {
+    ///    Every interior cell gets PaintOptions::interior_color.
+    #[default]
+    Flat,
+    ///    The distance of the cell to the nearest escaped cell, see
+    ///    regions::boundary_distance, normalised by the deepest interior
+    ///    cell of the frame, picks a color of PaintOptions::interior_palette.
+    BoundaryDistance,
+}
+
+
+///    How a cell's escape value is turned into a color.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ColoringMode {
+    ///    The orbit rate picks a palette color, giving crisp bands.
+    #[default]
+    Bands,
+    ///    The orbit rate picks the palette color (hue) and the fractional
+    ///    part of a smooth escape value darkens it by up to weight
+    ///    \[0 to 1\], adding a soft gradient inside every band. Needs a
+    ///    grid from render_smooth_grid, integer grids paint as Bands.
+    Hybrid { weight : f64 },
+    ///    The palette color is lit like a height field of escape values:
+    ///    the escape value gradient gives a surface normal and brightness
+    ///    is its Lambertian dot product with light_dir (x right, y down,
+    ///    z out of the image). Best on a grid from render_smooth_grid.
+    Shaded { light_dir : (f64, f64, f64) },
+    ///    The electrostatic potential of each pixel, see
+    ///    burning_ship_frac::potential, picks a position between two
+    ///    neighbouring palette colors and blends them, giving continuous
+    ///    equipotential gradients. Needs a grid from render_smooth_grid,
+    ///    ignores the palette curve and dithering.
+    Potential,
+    ///    Topographic contour lines every interval orbit rates, in their
+    ///    palette color, over a flat paper background. A cell is on a line
+    ///    when a neighbour above, below or beside it lies in a lower
+    ///    interval, see on_contour.
+    Contours { interval : u32 },
+    ///    False color without a color list, each channel a sinusoid of the
+    ///    escape value v, 255 (sin(freq v + phase) + 1) / 2 with r_freq,
+    ///    g_freq and b_freq, see trig_color. Smooth grids give continuous
+    ///    rainbows. Ignores the palette and its curve, palette offsets
+    ///    shift the phase, a whole palette length by 2 pi.
+    TrigPalette { r_freq : f64, g_freq : f64, b_freq : f64, phase : f64 },
+}
+
+
+///    ### (PURE)
+///    Lambertian brightness \[0 to 1\] of the escape value height field of
+///    frame at (x, y) lit from light_dir.
+fn lambert<T : EscapeValue>(frame : &[Vec<T>], x : usize, y : usize, light_dir : (f64, f64, f64)) -> f64 {
+    let (gx, gy) = sobel_gradient(frame, x, y);
+    // the Sobel kernels weigh 8 neighbour differences
+    let normal = (-gx / 8.0, -gy / 8.0, 1.0);
+    let normal_length = (normal.0 * normal.0 + normal.1 * normal.1 + 1.0).sqrt();
+    let (lx, ly, lz) = light_dir;
+    let light_length = (lx * lx + ly * ly + lz * lz).sqrt();
+    if light_length == 0.0 {
+        return 1.0;
+    }
+    let dot = (normal.0 * lx + normal.1 * ly + normal.2 * lz) / (normal_length * light_length);
+    dot.max(0.0)
+}
+
+
+///    ### (PURE)
+///    Color of a pixel with smooth escape value under Potential coloring,
+///    -log2 of its potential walks the palette (rotated by offset), one
+///    color per halving, blending the two colors it falls between
+///    under gamma, see blend_colors. An empty palette gives black.
+fn potential_color(
+    palette : &[Rgb<u8>],
+    smooth_escape : f64,
+    max_iterations : u32,
+    offset : usize,
+    gamma : Option<f64>
+) -> Rgb<u8>
+{
+    if palette.is_empty() {
+        return Rgb([0, 0, 0]);
+    }
+    let position = (-potential(smooth_escape, max_iterations).log2()).max(0.0);
+    let low = position.floor() as usize + offset;
+    let t = position.fract();
+    blend_colors(palette[low % palette.len()], palette[(low + 1) % palette.len()], t, gamma)
+}
+
+
+///    ### (PURE)
+///    Channel \[0 to 255\] as linear light \[0 to 1\] under gamma.
+fn to_linear(channel : u8, gamma : f64) -> f64 {
+    (channel as f64 / 255.0).powf(gamma)
+}
+
+
+///    ### (PURE)
+///    Linear light \[0 to 1\] encoded back to a channel under gamma.
+fn from_linear(light : f64, gamma : f64) -> u8 {
+    (light.clamp(0.0, 1.0).powf(1.0 / gamma) * 255.0).round() as u8
+}
+
+
+///    ### (PURE)
+///    Mixes from and to, t = 0 giving from and t = 1 giving to. With a
+///    gamma the channels are decoded to linear light first and the mix
+///    encoded back, so mid-gradients keep the brightness the eye expects
+///    instead of darkening, otherwise the encoded channels are mixed.
+pub fn blend_colors(from : Rgb<u8>, to : Rgb<u8>, t : f64, gamma : Option<f64>) -> Rgb<u8> {
+    Rgb(std::array::from_fn(|channel| {
+        let (from, to) = (from.0[channel], to.0[channel]);
+        match gamma {
+            Some(gamma) => from_linear(to_linear(from, gamma) * (1.0 - t) + to_linear(to, gamma) * t, gamma),
+            None => (from as f64 * (1.0 - t) + to as f64 * t).round() as u8,
+        }
+    }))
+}
+
+
+///    ### (PURE)
+///    Color of escape value under the trigonometric palette, channel c
+///    255 (sin(freqs.c escape + phase) + 1) / 2, see
+///    ColoringMode::TrigPalette.
+pub fn trig_color(escape : f64, freqs : (f64, f64, f64), phase : f64) -> Rgb<u8> {
+    let channel = |freq : f64| (((freq * escape + phase).sin() + 1.0) / 2.0 * 255.0).round() as u8;
+    Rgb([channel(freqs.0), channel(freqs.1), channel(freqs.2)])
+}
+
+
+///    ### (PURE)
+///    Scales the channels of color by brightness \[0 to 1\], in linear
+///    light when gamma is given like blend_colors.
+fn shade(color : Rgb<u8>, brightness : f64, gamma : Option<f64>) -> Rgb<u8> {
+    let brightness = brightness.clamp(0.0, 1.0);
+    match gamma {
+        Some(gamma) => Rgb(color.0.map(|channel| from_linear(to_linear(channel, gamma) * brightness, gamma))),
+        None => Rgb(color.0.map(|channel| (channel as f64 * brightness).round() as u8)),
+    }
+}
+
+
+///    Options controlling how escape values are turned into colors.
+#[derive(Debug, Clone)]
+pub struct PaintOptions {
+    ///    Escape value of interior pixels, see RenderConfig::max_iterations.
+    pub max_iterations : u32,
+    pub curve : PaletteCurve,
+    ///    Palette slots the colors rotate by per frame, 0 disables cycling.
+    pub cycle_speed : usize,
+    ///    Keep the fraction of smooth escape values and perturb the
+    ///    palette position with a 4x4 Bayer pattern before rounding,
+    ///    breaking up banding in smooth gradients. No effect on escape
+    ///    count grids, whose values have no fraction to dither.
+    pub dither : bool,
+    ///    When set, paint only the set boundary instead of the palette.
+    pub edges : Option<EdgeStyle>,
+    pub coloring : ColoringMode,
+    ///    When set, the largest connected interior region (the main body
+    ///    of the ship) is painted in this color instead of the palette.
+    pub body_color : Option<Rgb<u8>>,
+    ///    Color of interior cells (escape value of max_iterations), kept
+    ///    apart from the palette ramp.
+    pub interior_color : Rgb<u8>,
+    pub interior_coloring : InteriorColoring,
+    ///    Escaped cells whose orbit rate is below this are painted
+    ///    floor_color instead of the palette, clearing the speckle of the
+    ///    far exterior. 0 disables the floor.
+    pub escape_floor : u32,
+    pub floor_color : Rgb<u8>,
+    ///    Gradient for interior cells, independent of the exterior palette.
+    ///    Only used with a non-Flat interior_coloring, and None (or empty)
+    ///    falls back to interior_color.
+    pub interior_palette : Option<Vec<Rgb<u8>>>,
+    ///    Print resolution recorded in the pHYs chunk of saved frames,
+    ///    None leaves it unspecified.
+    pub dpi : Option<u32>,
+    ///    PNG compression of saved frames.
+    pub compression : PngCompression,
+    ///    Overlay the viewport coordinates, ticks and scale bar of a frame,
+    ///    see annotate::decorate_frame.
+    pub annotate : bool,
+    ///    Burn the frame number and magnification into the top left
+    ///    corner of a frame, see annotate::decorate_frame.
+    pub stamp : bool,
+    ///    Contrast stretch of the orbit rates before indexing the palette,
+    ///    None indexes it with the rates as they are.
+    pub contrast : Option<ContrastStretch>,
+    ///    Fades the corners of painted frames into interior_color when
+    ///    set.
+    pub vignette : Option<Vignette>,
+    ///    Display gamma the Hybrid, Shaded and Potential colorings blend
+    ///    and darken colors under, see blend_colors. None works on the
+    ///    encoded channels.
+    pub gamma : Option<f64>,
+}
+
+
+///    Circular vignette fading the corners of a frame into the interior
+///    color. Distances run from 0 at the center to 1 at the corners.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vignette {
+    ///    Distance up to which the frame is left untouched.
+    pub radius : f64,
+    ///    Distance beyond radius over which the fade goes from none to
+    ///    the full interior color, 0 gives a hard edge.
+    pub softness : f64,
+}
+
+impl Vignette {
+    ///    ### (PURE)
+    ///    Fade \[0 to 1\] at distance from the center, smoothstep shaped
+    ///    across the softness band.
+    pub fn fade(&self, distance : f64) -> f64 {
+        if distance <= self.radius {
+            return 0.0;
+        }
+        if self.softness <= 0.0 {
+            return 1.0;
+        }
+        let t = ((distance - self.radius) / self.softness).min(1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+
+///    Style of the boundary-only "outline" rendering.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeStyle {
+    ///    Minimum Sobel gradient magnitude of the escape values for a cell
+    ///    to count as an edge.
+    pub threshold : f64,
+    pub edge_color : Rgb<u8>,
+    pub background : Rgb<u8>,
+}
+
+impl Default for PaintOptions {
+    fn default() -> Self {
+        PaintOptions {
+            max_iterations : MAX_ITERATIONS,
+            curve : PaletteCurve::default(),
+            cycle_speed : 0,
+            dither : false,
+            edges : None,
+            coloring : ColoringMode::default(),
+            body_color : None,
+            interior_color : Rgb([0, 0, 0]),
+            interior_coloring : InteriorColoring::default(),
+            escape_floor : 0,
+            floor_color : Rgb([0, 0, 0]),
+            interior_palette : None,
+            dpi : None,
+            compression : PngCompression::default(),
+            annotate : false,
+            stamp : false,
+            gamma : None,
+            contrast : None,
+            vignette : None,
+        }
+    }
+}
+
+impl PaintOptions {
+    ///    ### (PURE)
+    ///    Palette offset for frame_number when cycling colors.
+    pub fn palette_offset(&self, frame_number : u32) -> usize {
+        frame_number as usize * self.cycle_speed
+    }
+
+    ///    ### (PURE)
+    ///    Options painting frames whose interior escapes at max_iterations,
+    ///    for frames with their own cap, see
+    ///    RenderConfig::max_iterations_at. Borrows self when it already
+    ///    matches.
+    pub fn with_max_iterations(&self, max_iterations : u32) -> Cow<'_, PaintOptions> {
+        if max_iterations == self.max_iterations {
+            return Cow::Borrowed(self);
+        }
+        Cow::Owned(PaintOptions { max_iterations, ..self.clone() })
+    }
+}
+
+
+///    ### (PURE)
+///    Palette offset of step \[0 to steps) of a color cycle that turns a
+///    palette of palette_len colors once over steps frames, so the last
+///    frame flows back into the first.
+pub fn cycle_offset(palette_len : usize, step : usize, steps : usize) -> usize {
+    step * palette_len / steps.max(1)
+}
 
 /// ### (PURE)
-/// Builds a random RGB color making use of rand crate.
-fn generate_random_color() -> image::Rgb<u8>{
-    let mut rng = rand::thread_rng();
+/// Builds a random RGB color drawn from rng making use of rand crate.
+fn generate_random_color(rng : &mut impl Rng) -> image::Rgb<u8>{
     let x: f64 = rng.gen();
     let y: f64 = rng.gen();
     let z: f64 = rng.gen();
@@ -23,66 +485,882 @@ fn generate_random_color() -> image::Rgb<u8>{
 
 
 ///    ### (PURE)
-///    Generates a palette of random colors.
-pub fn generate_random_palette(
-    number_of_colors : u8
+///    Generates a palette of number_of_colors + 1 colors drawn from rng.
+fn generate_palette_from(
+    number_of_colors : u32,
+    rng : &mut impl Rng
 ) -> Vec<image::Rgb<u8>>
 {
     let mut color_vec : Vec<image::Rgb<u8>>=  Vec::new();
-    for i in (0..number_of_colors + 1).into_iter(){
-        let color = generate_random_color();
-        let _ = color_vec.insert(i as usize, color);
+    for i in 0..number_of_colors + 1{
+        let color = generate_random_color(rng);
+        color_vec.insert(i as usize, color);
     }
     color_vec
 }
 
+
+///    ### (PURE)
+///    Generates a palette of random colors, from a fresh random seed
+///    (see generate_seeded_palette).
+pub fn generate_random_palette(
+    number_of_colors : u32
+) -> Vec<image::Rgb<u8>>
+{
+    generate_seeded_palette(number_of_colors, rand::random())
+}
+
+
+///    ### (PURE)
+///    Generates the same palette of random colors for the same seed,
+///    for reproducible renders.
+pub fn generate_seeded_palette(
+    number_of_colors : u32,
+    seed : u64
+) -> Vec<image::Rgb<u8>>
+{
+    generate_palette_from(number_of_colors, &mut rand::rngs::StdRng::seed_from_u64(seed))
+}
+
+
+///    How generate_blended_palette combines the palettes of its seeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    ///    Each band is the channel mean of the bands of every palette.
+    #[default]
+    Average,
+    ///    Band i is band i of the palette of seed i % seeds.len(), so the
+    ///    palettes take turns band by band.
+    PerBand,
+}
+
+impl BlendMode {
+    ///    ### (PURE)
+    ///    Mode called name, one of BLEND_NAMES.
+    pub fn from_name(name : &str) -> Option<Self> {
+        match name {
+            "average" => Some(BlendMode::Average),
+            "per-band" => Some(BlendMode::PerBand),
+            _ => None,
+        }
+    }
+
+    ///    ### (PURE)
+    ///    Name of the mode in BLEND_NAMES, the inverse of from_name.
+    pub fn name(self) -> &'static str {
+        match self {
+            BlendMode::Average => "average",
+            BlendMode::PerBand => "per-band",
+        }
+    }
+}
+
+
+///    ### (PURE)
+///    Palette of len colors combined under mode from the seeded palettes
+///    of seeds, see generate_seeded_palette. The same seeds in the same
+///    order always give the same palette. No seeds give black.
+pub fn generate_blended_palette(seeds : &[u64], len : usize, mode : BlendMode) -> Vec<image::Rgb<u8>> {
+    let palettes : Vec<Vec<image::Rgb<u8>>> = seeds.iter().map(|seed| generate_seeded_palette(len as u32, *seed)).collect();
+    if palettes.is_empty() {
+        return vec![Rgb([0, 0, 0]); len];
+    }
+    (0..len).map(|i| match mode {
+        BlendMode::Average => Rgb(std::array::from_fn(|channel|{
+            let sum : u32 = palettes.iter().map(|palette| palette[i].0[channel] as u32).sum();
+            (sum as f64 / palettes.len() as f64).round() as u8
+        })),
+        BlendMode::PerBand => palettes[i % palettes.len()][i],
+    }).collect()
+}
+
  
+///    ### (PURE)
+///    Samples the colormap through control_points, evenly spaced from 0
+///    to 1, at len evenly spaced positions, linearly interpolating between
+///    neighbouring control points.
+fn interpolate_colormap(control_points : &[[u8; 3]], len : usize) -> Vec<image::Rgb<u8>> {
+    let segments = (control_points.len() - 1) as f64;
+    (0..len).map(|i| {
+        let position = i as f64 / (len - 1).max(1) as f64 * segments;
+        let low = (position.floor() as usize).min(control_points.len() - 2);
+        let t = position - low as f64;
+        let (from, to) = (control_points[low], control_points[low + 1]);
+        Rgb(std::array::from_fn(|channel| {
+            (from[channel] as f64 * (1.0 - t) + to[channel] as f64 * t).round() as u8
+        }))
+    }).collect()
+}
+
+
+///    ### (PURE)
+///    Generates a palette of len colors running through the perceptually
+///    uniform, colorblind safe colormap name, one of PRESET_NAMES.
+///    None for an unknown name.
+pub fn generate_preset_palette(name : &str, len : usize) -> Option<Vec<image::Rgb<u8>>> {
+    let control_points : &[[u8; 3]] = match name {
+        "viridis" => &VIRIDIS,
+        "magma" => &MAGMA,
+        "cividis" => &CIVIDIS,
+        _ => return None,
+    };
+    Some(interpolate_colormap(control_points, len))
+}
+
+
+///    ### (PURE)
+///    Precomputes the color of every escape value \[0 to max_iterations\]
+///    under options.curve rotated by offset, with max_iterations mapped to
+///    options.interior_color and values below options.escape_floor mapped
+///    to options.floor_color. Escape values are stretched from bounds
+///    first when given, see stretch_escape. Only valid when the color is a
+///    pure function of the orbit rate, i.e. Bands without dithering or edges.
+pub fn palette_lut(
+    palette : &[image::Rgb<u8>],
+    options : &PaintOptions,
+    offset : usize,
+    bounds : Option<(u32, u32)>
+) -> Vec<image::Rgb<u8>>
+{
+    (0..=options.max_iterations)
+    .map(|escape| {
+        if escape == options.max_iterations {
+            return options.interior_color;
+        }
+        if escape < options.escape_floor {
+            return options.floor_color;
+        }
+        let escape = bounds.map_or(escape, |bounds| stretch_escape(escape, bounds, options.max_iterations));
+        palette_color(palette, options.curve.palette_index(escape as f64, options.max_iterations, palette.len()), offset)
+    }).collect()
+}
+
+
+///    ### (PURE)
+///    Color of palette at index rotated by offset (mod palette length).
+///    An index past the last color, e.g. from a palette shorter than
+///    max_iterations, is clamped to it with a warning logged once per
+///    run instead of panicking mid-render. An empty palette paints black.
+pub fn palette_color(palette : &[image::Rgb<u8>], index : usize, offset : usize) -> Rgb<u8> {
+    static CLAMP_WARNING : Once = Once::new();
+    if index >= palette.len() {
+        CLAMP_WARNING.call_once(|| log::warn!(
+            "Palette index {} is past the last of {} colors, clamping out of range indices to the last color",
+            index,
+            palette.len()
+        ));
+        if palette.is_empty() {
+            return Rgb([0, 0, 0]);
+        }
+    }
+    palette[(index.min(palette.len() - 1) + offset) % palette.len()]
+}
+
+
 ///    ### (PURE)
 ///    Given a frame of orbits [0 to MAX_ITERATIONS], maps integer to 
 ///    color in palette such that each orbit rate is represented as a
-///    unique color.
-pub fn paint_frame(
+///    unique color. The escape value is first stretched by
+///    options.contrast, then mapped through options.curve, with its
+///    fraction kept and dithered when options.dither is set, clamped to
+///    the palette (see palette_color), then rotated by offset (mod
+///    palette length) to cycle the colors. Hybrid and Shaded coloring
+///    then darken it by the fractional escape value or the lighting,
+///    Potential coloring blends palette colors by the potential instead.
+///    Interior cells get options.interior_color instead, and cells
+///    escaping below options.escape_floor get options.floor_color. Bands
+///    without dithering go through a palette_lut built once per frame.
+///    Rows are painted in parallel into imgbuf, which must be as large
+///    as frame.
+fn paint_palette<T : EscapeValue>(
+    imgbuf : &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+    frame : &[Vec<T>],
+    palette : &[image::Rgb<u8>],
+    options : &PaintOptions,
+    offset : usize
+) {
+    let bounds = options.contrast.and_then(|contrast| contrast.bounds(frame, options.max_iterations));
+    let lut = (!options.dither && options.coloring == ColoringMode::Bands)
+        .then(|| palette_lut(palette, options, offset, bounds));
+    let pixel_color = |i : usize, j : usize, cell : T| -> Rgb<u8> {
+        let value = cell.escape_value();
+        if value >= options.max_iterations as f64 {
+            return options.interior_color;
+        }
+        if value < options.escape_floor as f64 {
+            return options.floor_color;
+        }
+        let band = value.floor();
+        let escape = bounds.map_or(band as u32, |bounds| stretch_escape(band as u32, bounds, options.max_iterations));
+        let fraction = if options.dither { value - band } else { 0.0 };
+        let mut position = options.curve.palette_position(
+            escape as f64 + fraction,
+            options.max_iterations,
+            palette.len()
+        );
+        if options.dither {
+            position = (position + bayer_offset(j, i)).clamp(0.0, palette.len().saturating_sub(1) as f64);
+        }
+        let color = palette_color(palette, position.round() as usize, offset);
+        match options.coloring {
+            ColoringMode::Bands => color,
+            ColoringMode::Hybrid { weight } => shade(color, 1.0 - weight * (value - band), options.gamma),
+            ColoringMode::Shaded { light_dir } => shade(color, lambert(frame, j, i, light_dir), options.gamma),
+            ColoringMode::Potential => potential_color(palette, value, options.max_iterations, offset, options.gamma),
+            ColoringMode::TrigPalette { r_freq, g_freq, b_freq, phase } => {
+                let phase = phase + std::f64::consts::TAU * offset as f64 / palette.len().max(1) as f64;
+                trig_color(value, (r_freq, g_freq, b_freq), phase)
+            },
+            ColoringMode::Contours { interval } if on_contour(frame, j, i, interval) => color,
+            ColoringMode::Contours { .. } => CONTOUR_PAPER,
+        }
+    };
+    // rows are painted in parallel into the raw row-major RGB bytes
+    let row_bytes = imgbuf.width() as usize * 3;
+    imgbuf
+    .par_chunks_mut(row_bytes.max(1))
+    .zip(frame.par_iter())
+    .enumerate()
+    .for_each(|(i, (out, row))| {
+        let pixels = out.chunks_exact_mut(3).zip(row.iter());
+        match &lut {
+            Some(lut) => pixels.for_each(|(pixel, cell)| {
+                let escape = cell.escape_value() as usize;
+                pixel.copy_from_slice(&lut[escape.min(lut.len() - 1)].0);
+            }),
+            None => pixels.enumerate().for_each(|(j, (pixel, cell))| {
+                pixel.copy_from_slice(&pixel_color(i, j, *cell).0);
+            }),
+        }
+    });
+}
+
+
+///    ### (PURE)
+///    Recolors the interior cells of buffer from options.interior_palette
+///    according to options.interior_coloring, leaving escaped cells alone.
+fn paint_interior<T : EscapeValue>(
+    buffer : &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+    frame : &[Vec<T>],
+    options : &PaintOptions
+) {
+    let interior_palette = match (&options.interior_palette, options.interior_coloring) {
+        (Some(interior_palette), InteriorColoring::BoundaryDistance) if !interior_palette.is_empty() => interior_palette,
+        _ => return,
+    };
+    let distance = regions::boundary_distance(frame, options.max_iterations);
+    let deepest = distance.iter().flatten().copied().filter(|d| d.is_finite()).fold(0.0, f64::max);
+    if deepest <= 0.0 {
+        return;
+    }
+    let last = (interior_palette.len() - 1) as f64;
+    for (pixel, d) in buffer.pixels_mut().zip(distance.iter().flatten()) {
+        if *d > 0.0 {
+            let index = (d.min(deepest) / deepest * last).round() as usize;
+            *pixel = interior_palette[index];
+        }
+    }
+}
+
+
+///    ### (PURE)
+///    Paints frame with paint_edges when options.edges is set, otherwise
+///    with the palette (see paint_palette), then shades the interior with
+///    options.interior_palette (see paint_interior) and recolors the main
+///    body when options.body_color is set. Either way options.vignette is
+///    applied last.
+pub fn paint_frame<T : EscapeValue>(
     width: u32, 
     height: u32, 
-    frame : &Vec<Vec<u8>>,
-    palette : &Vec<image::Rgb<u8>>
+    frame : &[Vec<T>],
+    palette : &[image::Rgb<u8>],
+    options : &PaintOptions,
+    offset : usize
 ) -> ImageBuffer<Rgb<u8>, Vec<u8>>{
-    let mut imgbuf = image::ImageBuffer::new(width, height);
+    let mut imgbuf = ImageBuffer::new(width, height);
+    // ⬇
+    paint_into(&mut imgbuf, width, height, frame, palette, options, offset);
+    imgbuf
+}
+
 
-    for (i, row) in frame.into_iter().enumerate(){
-        for(j , cell) in row.into_iter().enumerate(){
-            let color = palette.get(*cell as usize).unwrap();
-            imgbuf.put_pixel(j as u32, i as u32, *color)
+///    ### (PURE)
+///    paint_frame painting into imgbuf instead of a new image, for
+///    callers painting many frames in a row. imgbuf is only resized when
+///    its dimensions differ from width x height, reusing its allocation
+///    when it shrinks, and every pixel is overwritten.
+pub fn paint_into<T : EscapeValue>(
+    imgbuf : &mut ImageBuffer<Rgb<u8>, Vec<u8>>,
+    width: u32,
+    height: u32,
+    frame : &[Vec<T>],
+    palette : &[image::Rgb<u8>],
+    options : &PaintOptions,
+    offset : usize
+) {
+    if imgbuf.dimensions() != (width, height) {
+        let mut raw = std::mem::take(imgbuf).into_raw();
+        raw.resize(width as usize * height as usize * 3, 0);
+        *imgbuf = ImageBuffer::from_raw(width, height, raw).expect("raw is resized to width x height");
+    }
+    match &options.edges {
+        Some(style) => paint_edges_into(imgbuf, frame, style),
+        None => {
+            paint_palette(imgbuf, frame, palette, options, offset);
+            // ⬇
+            paint_interior(imgbuf, frame, options);
+            if let Some(body_color) = options.body_color {
+                let mask = regions::main_body_mask(frame, options.max_iterations);
+                for (pixel, in_body) in imgbuf.pixels_mut().zip(mask.iter().flatten()) {
+                    if *in_body {
+                        *pixel = body_color;
+                    }
+                }
+            }
+        },
+    }
+    // ⬇
+    if let Some(vignette) = options.vignette {
+        apply_vignette(imgbuf, vignette, options.interior_color, options.gamma);
+    }
+}
+
+
+///    ### (PURE)
+///    Fades buffer towards color outside vignette.radius, see Vignette,
+///    blending under gamma like blend_colors.
+pub fn apply_vignette(buffer : &mut ImageBuffer<Rgb<u8>, Vec<u8>>, vignette : Vignette, color : Rgb<u8>, gamma : Option<f64>) {
+    let (center_x, center_y) = (buffer.width() as f64 / 2.0, buffer.height() as f64 / 2.0);
+    let half_diagonal = center_x.hypot(center_y).max(f64::MIN_POSITIVE);
+    for (x, y, pixel) in buffer.enumerate_pixels_mut() {
+        let distance = (x as f64 + 0.5 - center_x).hypot(y as f64 + 0.5 - center_y) / half_diagonal;
+        let fade = vignette.fade(distance);
+        if fade > 0.0 {
+            *pixel = blend_colors(*pixel, color, fade, gamma);
         }
     }
+}
+
+
+///    ### (PURE)
+///    Whether (x, y) lies on a contour line, its integer escape value
+///    being in a higher multiple of interval than one of its 4
+///    neighbours. Only the upper side of each step is marked, so lines
+///    are one pixel thin.
+fn on_contour<T : EscapeValue>(frame : &[Vec<T>], x : usize, y : usize, interval : u32) -> bool {
+    let level = |cell : &T| cell.escape_value() as u64 / interval.max(1) as u64;
+    let own = level(&frame[y][x]);
+    let neighbours = [
+        x.checked_sub(1).map(|x| &frame[y][x]),
+        frame[y].get(x + 1),
+        y.checked_sub(1).map(|y| &frame[y][x]),
+        frame.get(y + 1).and_then(|row| row.get(x)),
+    ];
+    neighbours.into_iter().flatten().any(|cell| level(cell) < own)
+}
+
+
+///    ### (PURE)
+///    Sobel gradient (x, y) of the escape values around (x, y),
+///    with neighbours clamped at the frame border.
+fn sobel_gradient<T : EscapeValue>(frame : &[Vec<T>], x : usize, y : usize) -> (f64, f64) {
+    let height = frame.len();
+    let width = frame[0].len();
+    let cell = |dx : isize, dy : isize| {
+        let cx = (x as isize + dx).clamp(0, width as isize - 1) as usize;
+        let cy = (y as isize + dy).clamp(0, height as isize - 1) as usize;
+        frame[cy][cx].escape_value()
+    };
+    let gx = (cell(1, -1) + 2.0 * cell(1, 0) + cell(1, 1))
+        - (cell(-1, -1) + 2.0 * cell(-1, 0) + cell(-1, 1));
+    let gy = (cell(-1, 1) + 2.0 * cell(0, 1) + cell(1, 1))
+        - (cell(-1, -1) + 2.0 * cell(0, -1) + cell(1, -1));
+    (gx, gy)
+}
+
+
+///    ### (PURE)
+///    Sobel gradient magnitude of the escape values around (x, y).
+fn sobel_magnitude<T : EscapeValue>(frame : &[Vec<T>], x : usize, y : usize) -> f64 {
+    let (gx, gy) = sobel_gradient(frame, x, y);
+    (gx * gx + gy * gy).sqrt()
+}
+
+
+///    ### (PURE)
+///    Paints only the cells whose neighbours have very different escape
+///    values, giving an outline of the boundary between escaped and
+///    interior regions over a flat background.
+pub fn paint_edges<T : EscapeValue>(
+    width : u32,
+    height : u32,
+    frame : &[Vec<T>],
+    style : &EdgeStyle
+) -> ImageBuffer<Rgb<u8>, Vec<u8>>{
+    let mut imgbuf = image::ImageBuffer::new(width, height);
+    paint_edges_into(&mut imgbuf, frame, style);
     imgbuf
 }
 
 
+///    ### (PURE)
+///    paint_edges painting into imgbuf, which must be as large as frame.
+fn paint_edges_into<T : EscapeValue>(imgbuf : &mut ImageBuffer<Rgb<u8>, Vec<u8>>, frame : &[Vec<T>], style : &EdgeStyle) {
+    for pixel in imgbuf.pixels_mut() {
+        *pixel = style.background;
+    }
+    for (i, row) in frame.iter().enumerate(){
+        for j in 0..row.len(){
+            if sobel_magnitude(frame, j, i) >= style.threshold {
+                imgbuf.put_pixel(j as u32, i as u32, style.edge_color)
+            }
+        }
+    }
+}
+
+
+///    ### (I/O)
+///    Encodes buffer as a PNG at path with its pHYs chunk set to dpi, so
+///    print software sizes it correctly, at the given compression.
+fn save_png_with_dpi<P : Pixel<Subpixel = u8> + PixelWithColorType>(
+    buffer : &ImageBuffer<P, Vec<u8>>,
+    path : &str,
+    dpi : u32,
+    compression : PngCompression
+) -> ImageResult<()>
+{
+    let to_image_error = |err : png::EncodingError| ImageError::Encoding(error::EncodingError::new(
+        error::ImageFormatHint::Exact(ImageFormat::Png),
+        err
+    ));
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, buffer.width(), buffer.height());
+    encoder.set_color(match P::CHANNEL_COUNT {
+        4 => png::ColorType::Rgba,
+        _ => png::ColorType::Rgb,
+    });
+    encoder.set_depth(png::BitDepth::Eight);
+    match compression {
+        PngCompression::Fast => {
+            encoder.set_compression(png::Compression::Fast);
+            encoder.set_filter(png::FilterType::NoFilter);
+        },
+        PngCompression::Default => {},
+        PngCompression::Best => {
+            encoder.set_compression(png::Compression::Best);
+            encoder.set_adaptive_filter(png::AdaptiveFilterType::Adaptive);
+        },
+    }
+    // pHYs stores pixels per meter
+    let pixels_per_meter = (dpi as f64 / 0.0254).round() as u32;
+    encoder.set_pixel_dims(Some(png::PixelDimensions {
+        xppu : pixels_per_meter,
+        yppu : pixels_per_meter,
+        unit : png::Unit::Meter,
+    }));
+    let mut writer = encoder.write_header().map_err(to_image_error)?;
+    writer.write_image_data(buffer.as_raw()).map_err(to_image_error)
+}
+
+
+///    ### (PURE)
+///    Path of frame frame_number with extension inside OUTPUT_DIR,
+///    "{OUTPUT_DIR}/{frame_number:010}.{extension}". Zero padded to the
+///    ten digits of u32::MAX so the paths of every frame sort lexically
+///    in render order.
+pub fn frame_path(frame_number : u32, extension : &str) -> String {
+    format!("{}/{:010}.{}", OUTPUT_DIR, frame_number, extension)
+}
+
+
 ///    ### (I/0)
-///    Saves image buffer to file at "imgs/{frame_number}.png
-pub fn save_img_buff(
-    buffer : ImageBuffer<Rgb<u8>, Vec<u8>>,
-    frame_number : u16
+///    Saves image buffer to file at "{OUTPUT_DIR}/{frame_number}.png",
+///    see save_img_buff_at.
+pub fn save_img_buff<P : Pixel<Subpixel = u8> + PixelWithColorType>(
+    buffer : ImageBuffer<P, Vec<u8>>,
+    frame_number : u32,
+    options : &PaintOptions
 )-> ImageResult<()>
 {
-    let path = format!("frames/{:08}.png", frame_number);
-    buffer.save(path)
+    save_img_buff_at(buffer, &frame_path(frame_number, "png"), options)
+}
+
+
+///    ### (I/0)
+///    Saves image buffer as a PNG at path, recording options.dpi in the
+///    PNG when set and encoding it at options.compression. The PNG is
+///    written to "{path}.tmp" first and renamed into place once complete,
+///    so a process killed mid-write never leaves a truncated frame behind.
+pub fn save_img_buff_at<P : Pixel<Subpixel = u8> + PixelWithColorType>(
+    buffer : ImageBuffer<P, Vec<u8>>,
+    path : &str,
+    options : &PaintOptions
+)-> ImageResult<()>
+{
+    let tmp_path = format!("{}.tmp", path);
+    let written = match options.dpi {
+        Some(dpi) => save_png_with_dpi(&buffer, &tmp_path, dpi, options.compression),
+        None => std::fs::File::create(&tmp_path).map_err(ImageError::IoError).and_then(|file|{
+            let (compression, filter) = options.compression.encoder_settings();
+            codecs::png::PngEncoder::new_with_quality(std::io::BufWriter::new(file), compression, filter)
+                .write_image(buffer.as_raw(), buffer.width(), buffer.height(), P::COLOR_TYPE)
+        }),
+    };
+    // ⬇
+    written.and_then(|_| std::fs::rename(&tmp_path, path).map_err(ImageError::IoError))
+}
+
+
+///    Summary of the per-channel differences between two images.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffReport {
+    pub max_channel_diff : u8,
+    pub mean_channel_diff : f64,
+    ///    Number of pixels with any channel differing by more than tolerance.
+    pub pixels_over_tolerance : usize,
+}
+
+impl DiffReport {
+    ///    ### (PURE)
+    ///    True if no pixel differs by more than the tolerance.
+    pub fn within_tolerance(&self) -> bool {
+        self.pixels_over_tolerance == 0
+    }
+}
+
+
+///    ### (I/O)
+///    Loads a previously saved frame from path and compares it channel by
+///    channel against buffer. Fails if the file cannot be read or the
+///    dimensions differ.
+pub fn diff_against_file(
+    buffer : &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    path : &str,
+    tolerance : u8
+) -> ImageResult<DiffReport>
+{
+    let saved = image::open(path)?.to_rgb8();
+    if saved.dimensions() != buffer.dimensions() {
+        return Err(ImageError::Parameter(error::ParameterError::from_kind(
+            error::ParameterErrorKind::DimensionMismatch
+        )));
+    }
+
+    let mut max_channel_diff = 0;
+    let mut total_diff : u64 = 0;
+    let mut pixels_over_tolerance = 0;
+    for (saved_pixel, pixel) in saved.pixels().zip(buffer.pixels()) {
+        let pixel_diff = saved_pixel.0
+            .iter()
+            .zip(pixel.0.iter())
+            .map(|(a, b)| a.abs_diff(*b))
+            .fold(0, |acc, diff| {
+                total_diff += diff as u64;
+                acc.max(diff)
+            });
+        max_channel_diff = max_channel_diff.max(pixel_diff);
+        if pixel_diff > tolerance {
+            pixels_over_tolerance += 1;
+        }
+    }
+    let channel_count = buffer.as_raw().len().max(1) as f64;
+    Ok(DiffReport {
+        max_channel_diff,
+        mean_channel_diff : total_diff as f64 / channel_count,
+        pixels_over_tolerance,
+    })
+}
+
+
+///    ### (PURE)
+///    Paints palette as a horizontal strip, one STRIP_BAND_WIDTH wide
+///    vertical band per color in palette order.
+pub fn paint_palette_strip(palette : &[image::Rgb<u8>]) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let width = (palette.len() as u32 * STRIP_BAND_WIDTH).max(1);
+    ImageBuffer::from_fn(width, STRIP_HEIGHT, |x, _| {
+        palette.get((x / STRIP_BAND_WIDTH) as usize).copied().unwrap_or(Rgb([0, 0, 0]))
+    })
+}
+
+
+///    ### (I/O)
+///    Composes paint_palette_strip and saves it to
+///    "{OUTPUT_DIR}/{PALETTE_FILE}", returning the path.
+pub fn save_palette_strip(palette : &[image::Rgb<u8>]) -> ImageResult<String> {
+    let path = format!("{}/{}", OUTPUT_DIR, PALETTE_FILE);
+    paint_palette_strip(palette).save(&path)?;
+    Ok(path)
 }
 
 
 ///    ### (I/O)
-///    Composes paint_frame and save_img_buff
-pub fn paint_and_save_frame(
+///    Creates OUTPUT_DIR (and any missing parents) if it does not exist.
+pub fn create_output_dir() -> std::io::Result<()> {
+    std::fs::create_dir_all(OUTPUT_DIR)
+}
+
+
+///    ### (PURE)
+///    Paints frame like paint_frame and returns its pixels as raw
+///    row-major RGBA bytes, 4 per pixel with alpha 255, e.g. to copy
+///    into a canvas ImageData.
+pub fn paint_frame_rgba<T : EscapeValue>(
     width: u32, 
     height: u32, 
-    frame : &Vec<Vec<u8>>,
-    palette : &Vec<image::Rgb<u8>>,
-    frame_number : u16
-)  -> ImageResult<()>
+    frame : &[Vec<T>],
+    palette : &[image::Rgb<u8>],
+    options : &PaintOptions,
+    offset : usize
+) -> Vec<u8>
 {
-    let buffer = paint_frame(width, height, frame, palette);
+    DynamicImage::ImageRgb8(paint_frame(width, height, frame, palette, options, offset))
+        .into_rgba8()
+        .into_raw()
+}
+
+
+///    ### (PURE)
+///    Composes functions:\
+///    render_escape_grid (render_smooth_grid for smooth coloring) ->\
+///    paint_frame_rgba\
+///    To return frame_number of config as RGBA bytes without touching
+///    the filesystem.
+pub fn render_to_rgba(
+    config : &RenderConfig,
+    frame_number : u32,
+    palette : &[image::Rgb<u8>],
+    options : &PaintOptions
+) -> Result<Vec<u8>, RenderError>
+{
+    let (width, height) = (config.img_width as u32, config.img_height as u32);
+    let offset = options.palette_offset(frame_number);
+    if options.coloring != ColoringMode::Bands {
+        let frame = render_smooth_grid(config, frame_number)?;
+        return Ok(paint_frame_rgba(width, height, &frame, palette, options, offset));
+    }
+    let frame : Fractal<u32> = render_escape_grid(config, frame_number)?;
     // ⬇
-    save_img_buff(buffer, frame_number)
-    
-}
\ No newline at end of file
+    Ok(paint_frame_rgba(width, height, &frame, palette, options, offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gamma_blend_keeps_mid_gradient_brightness() {
+        let (black, white) = (Rgb([0, 0, 0]), Rgb([255, 255, 255]));
+        assert_eq!(blend_colors(black, white, 0.5, None), Rgb([128, 128, 128]));
+        assert_eq!(blend_colors(black, white, 0.5, Some(2.2)), Rgb([186, 186, 186]));
+        let color = Rgb([12, 200, 97]);
+        for gamma in [None, Some(2.2)] {
+            assert_eq!(blend_colors(color, white, 0.0, gamma), color);
+            assert_eq!(blend_colors(black, color, 1.0, gamma), color);
+        }
+    }
+
+    #[test]
+    fn contrast_stretch_spans_the_escaping_rates() {
+        let frame : Fractal<u8> = vec![vec![40, 10, 255], vec![25, 255, 30]];
+        let bounds = ContrastStretch::PerFrame.bounds(&frame, 255).unwrap();
+        assert_eq!(bounds, (10, 40));
+        assert_eq!(stretch_escape(10, bounds, 255), 0);
+        assert_eq!(stretch_escape(25, bounds, 255), 127);
+        assert_eq!(stretch_escape(40, bounds, 255), 254);
+        assert_eq!(stretch_escape(90, bounds, 255), 254);
+        assert_eq!(ContrastStretch::PerFrame.bounds(&[vec![255u8]], 255), None);
+        assert_eq!(stretch_escape(7, (7, 7), 255), 0);
+    }
+
+    #[test]
+    fn vignette_leaves_center_and_fades_corners() {
+        let mut buffer = ImageBuffer::from_pixel(64, 36, Rgb([200u8, 100, 50]));
+        apply_vignette(&mut buffer, Vignette { radius : 0.5, softness : 0.4 }, Rgb([0, 0, 0]), None);
+        assert_eq!(*buffer.get_pixel(32, 18), Rgb([200, 100, 50]));
+        assert_eq!(*buffer.get_pixel(0, 0), Rgb([0, 0, 0]));
+        let (edge, mid) = (buffer.get_pixel(0, 18)[0], buffer.get_pixel(16, 18)[0]);
+        assert!(edge < mid && mid <= 200, "{} {}", edge, mid);
+    }
+
+    #[test]
+    fn short_palette_clamps_to_its_last_color() {
+        let palette = [Rgb([10u8, 0, 0]), Rgb([20, 0, 0]), Rgb([30, 0, 0])];
+        assert_eq!(palette_color(&palette, 7, 0), Rgb([30, 0, 0]));
+        assert_eq!(palette_color(&palette, 7, 1), Rgb([10, 0, 0]));
+        assert_eq!(palette_color(&[], 0, 0), Rgb([0, 0, 0]));
+        let frame : Fractal<u32> = vec![vec![0, 1, 2, 5, 9, 10]];
+        let options = PaintOptions { max_iterations : 10, ..PaintOptions::default() };
+        let buffer = paint_frame(6, 1, &frame, &palette, &options, 0);
+        let reds : Vec<u8> = buffer.pixels().map(|pixel| pixel[0]).collect();
+        assert_eq!(reds, [10, 20, 30, 30, 30, 0]);
+    }
+
+    #[test]
+    fn contours_mark_the_upper_side_of_each_step() {
+        let palette = [Rgb([200u8, 0, 0]); 8];
+        let frame : Fractal<u32> = vec![vec![0, 1, 3, 4, 5, 6, 7, 9]];
+        let options = PaintOptions {
+            max_iterations : 9,
+            coloring : ColoringMode::Contours { interval : 3 },
+            ..PaintOptions::default()
+        };
+        let buffer = paint_frame(8, 1, &frame, &palette, &options, 0);
+        let lines : Vec<bool> = buffer.pixels().map(|pixel| *pixel == Rgb([200, 0, 0])).collect();
+        assert_eq!(lines, [false, false, true, false, false, true, false, false]);
+    }
+
+    #[test]
+    fn trig_palette_channels_follow_their_sinusoids() {
+        use std::f64::consts::PI;
+        assert_eq!(trig_color(0.0, (1.0, 2.0, 3.0), 0.0), Rgb([128, 128, 128]));
+        assert_eq!(trig_color(PI / 2.0, (1.0, 0.0, 3.0), 0.0), Rgb([255, 128, 0]));
+        assert_eq!(trig_color(1.0, (0.5, 0.5, 0.5), PI), trig_color(1.0 + 2.0 * PI, (0.5, 0.5, 0.5), 0.0));
+    }
+
+    #[test]
+    fn potential_coloring_walks_the_palette_and_survives_an_empty_one() {
+        let palette = [Rgb([0u8, 0, 0]), Rgb([100, 100, 100]), Rgb([200, 200, 200])];
+        let rotated = [palette[1], palette[2], palette[0]];
+        assert_eq!(potential_color(&palette, 3.0, 10, 1, None), potential_color(&rotated, 3.0, 10, 0, None));
+        assert_eq!(potential_color(&[Rgb([9, 9, 9]); 2], 3.0, 10, 0, None), Rgb([9, 9, 9]));
+        assert_eq!(potential_color(&[], 3.0, 10, 0, None), Rgb([0, 0, 0]));
+        let frame : Fractal<f64> = vec![vec![1.5, 4.0, 10.0]];
+        let options = PaintOptions { max_iterations : 10, coloring : ColoringMode::Potential, ..PaintOptions::default() };
+        assert_eq!(paint_frame(3, 1, &frame, &[], &options, 0).pixels().count(), 3);
+    }
+
+    #[test]
+    fn blended_palettes_are_reproducible_mixes_of_their_seeds() {
+        let (a, b) = (generate_seeded_palette(8, 7), generate_seeded_palette(8, 42));
+        let average = generate_blended_palette(&[7, 42], 8, BlendMode::Average);
+        assert_eq!(average, generate_blended_palette(&[7, 42], 8, BlendMode::Average));
+        assert_eq!(average.len(), 8);
+        for (i, color) in average.iter().enumerate() {
+            for channel in 0..3 {
+                let mean = (a[i].0[channel] as f64 + b[i].0[channel] as f64) / 2.0;
+                assert!((color.0[channel] as f64 - mean).abs() <= 0.5);
+            }
+        }
+        let per_band = generate_blended_palette(&[7, 42], 8, BlendMode::PerBand);
+        assert_eq!((per_band[0], per_band[1], per_band[6], per_band[7]), (a[0], b[1], a[6], b[7]));
+        assert_eq!(generate_blended_palette(&[7], 8, BlendMode::Average), a[..8].to_vec());
+    }
+
+    #[test]
+    fn escape_floor_paints_the_far_exterior_in_its_color() {
+        let palette = [Rgb([10u8, 0, 0]), Rgb([20, 0, 0]), Rgb([30, 0, 0]), Rgb([40, 0, 0])];
+        let floor_color = Rgb([0, 0, 255]);
+        let options = PaintOptions { max_iterations : 4, escape_floor : 2, floor_color, ..PaintOptions::default() };
+        let frame : Fractal<u32> = vec![vec![0, 1, 2, 3, 4]];
+        let buffer = paint_frame(5, 1, &frame, &palette, &options, 0);
+        let pixels : Vec<Rgb<u8>> = buffer.pixels().copied().collect();
+        assert_eq!(pixels, [floor_color, floor_color, Rgb([30, 0, 0]), Rgb([40, 0, 0]), Rgb([0, 0, 0])]);
+
+        let hybrid = PaintOptions { coloring : ColoringMode::Hybrid { weight : 0.0 }, ..options };
+        let smooth : Fractal<f64> = vec![vec![1.5, 2.5]];
+        let buffer = paint_frame(2, 1, &smooth, &palette, &hybrid, 0);
+        assert_eq!(*buffer.get_pixel(0, 0), floor_color);
+        assert_ne!(*buffer.get_pixel(1, 0), floor_color);
+    }
+
+    #[test]
+    fn frame_paths_sort_in_render_order() {
+        let frames = [0, 10, 65535, 65536, 100000, 99999999, 100000000, u32::MAX];
+        let mut paths : Vec<String> = frames.iter().rev().map(|frame| frame_path(*frame, "png")).collect();
+        paths.sort();
+        assert_eq!(paths, frames.map(|frame| frame_path(frame, "png")));
+        assert_eq!(frame_path(100000, "png"), "frames/0000100000.png");
+    }
+
+    #[test]
+    fn palette_curves_parse_and_keep_smooth_fractions() {
+        assert_eq!(PaletteCurve::from_name("log"), Some(PaletteCurve::Log));
+        assert_eq!(PaletteCurve::from_name("power=2"), Some(PaletteCurve::Power(2.0)));
+        assert_eq!(PaletteCurve::from_name("power=0"), None);
+        assert_eq!(PaletteCurve::from_name("cubic"), None);
+        assert_eq!(PaletteCurve::Sqrt.palette_position(2.25, 9, 4), 1.5);
+        assert_eq!(PaletteCurve::Linear.palette_position(2.25, 9, 4), 2.25);
+        assert_eq!(PaletteCurve::Sqrt.palette_index(9.0, 9, 0), 0);
+    }
+
+    #[test]
+    fn dither_spreads_a_smooth_band_over_neighbouring_colors() {
+        let palette : Vec<Rgb<u8>> = (0..8).map(|i| Rgb([i * 30, 0, 0])).collect();
+        let frame : Fractal<f64> = vec![vec![3.5; 4]];
+        let options = PaintOptions { max_iterations : 8, dither : true, ..PaintOptions::default() };
+        let buffer = paint_frame(4, 1, &frame, &palette, &options, 0);
+        let indices : Vec<u8> = buffer.pixels().map(|pixel| pixel[0] / 30).collect();
+        assert_eq!(indices, [3, 4, 3, 4]);
+        let plain = paint_frame(4, 1, &frame, &palette, &PaintOptions { dither : false, ..options.clone() }, 0);
+        assert!(plain.pixels().all(|pixel| pixel[0] == 90));
+        let empty = paint_frame(4, 1, &frame, &[], &options, 0);
+        assert!(empty.pixels().all(|pixel| *pixel == Rgb([0, 0, 0])));
+    }
+
+    #[test]
+    fn cycle_speed_rotates_the_palette_per_frame() {
+        let palette = [Rgb([10u8, 0, 0]), Rgb([20, 0, 0]), Rgb([30, 0, 0])];
+        let frame : Fractal<u32> = vec![vec![0, 1, 2]];
+        let options = PaintOptions { max_iterations : 3, cycle_speed : 2, ..PaintOptions::default() };
+        assert_eq!(options.palette_offset(0), 0);
+        assert_eq!(options.palette_offset(4), 8);
+        let buffer = paint_frame(3, 1, &frame, &palette, &options, options.palette_offset(1));
+        let reds : Vec<u8> = buffer.pixels().map(|pixel| pixel[0]).collect();
+        assert_eq!(reds, [30, 10, 20]);
+    }
+
+    #[test]
+    fn edges_outline_where_escape_values_jump() {
+        let style = EdgeStyle { threshold : 1.0, edge_color : Rgb([255, 255, 255]), background : Rgb([0, 0, 40]) };
+        let frame : Fractal<u32> = (0..5).map(|_| vec![2, 2, 2, 9, 9, 9]).collect();
+        let buffer = paint_edges(6, 5, &frame, &style);
+        assert_eq!(*buffer.get_pixel(2, 2), style.edge_color);
+        assert_eq!(*buffer.get_pixel(0, 2), style.background);
+        assert_eq!(*buffer.get_pixel(5, 2), style.background);
+        let options = PaintOptions { edges : Some(style), ..PaintOptions::default() };
+        assert_eq!(paint_frame(6, 5, &frame, &[Rgb([9, 9, 9])], &options, 0), buffer);
+    }
+
+    fn scratch_path(name : &str) -> String {
+        let dir = std::env::temp_dir().join(format!("burning_ship_painter_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name).to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn diff_against_file_compares_saved_frames() {
+        let path = scratch_path("diff.png");
+        let buffer = ImageBuffer::from_fn(4, 3, |x, y| Rgb([x as u8 * 10, y as u8 * 10, 100]));
+        buffer.save(&path).unwrap();
+
+        let identical = diff_against_file(&buffer, &path, 0).unwrap();
+        assert_eq!(identical, DiffReport { max_channel_diff : 0, mean_channel_diff : 0.0, pixels_over_tolerance : 0 });
+        assert!(identical.within_tolerance());
+
+        let mut differing = buffer.clone();
+        differing.put_pixel(1, 1, Rgb([10, 10, 130]));
+        differing.put_pixel(2, 0, Rgb([25, 0, 100]));
+        let report = diff_against_file(&differing, &path, 5).unwrap();
+        assert_eq!(report.max_channel_diff, 30);
+        assert_eq!(report.mean_channel_diff, 35.0 / 36.0);
+        assert_eq!(report.pixels_over_tolerance, 1);
+        assert!(!report.within_tolerance());
+
+        let smaller = ImageBuffer::from_pixel(3, 3, Rgb([0, 0, 100]));
+        assert!(matches!(diff_against_file(&smaller, &path, 255), Err(ImageError::Parameter(_))));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn save_img_buff_at_leaves_only_the_complete_png() {
+        let buffer = ImageBuffer::from_fn(5, 4, |x, y| Rgb([x as u8 * 50, y as u8 * 60, 7]));
+        for (name, dpi) in [("plain.png", None), ("dpi.png", Some(300))] {
+            let path = scratch_path(name);
+            let options = PaintOptions { dpi, ..PaintOptions::default() };
+            save_img_buff_at(buffer.clone(), &path, &options).unwrap();
+            assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+            assert_eq!(image::open(&path).unwrap().to_rgb8(), buffer);
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+}
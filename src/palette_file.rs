@@ -0,0 +1,86 @@
+//!   Module contains the palette interchange file, a plain
+//!   hex list with one #RRGGBB color per line in palette
+//!   order, as read and written by most palette editors.
+//!   @author Van Gouache
+
+use std::io::{Error, ErrorKind};
+use image::Rgb;
+use crate::painter::OUTPUT_DIR;
+
+//file name of the palette --show-palette exports inside OUTPUT_DIR
+pub const PALETTE_HEX_FILE : &str = "palette.hex";
+//lines starting with this are comments, as in Paint.NET palettes
+const COMMENT : char = ';';
+
+
+///    ### (PURE)
+///    Hex list of palette, one lowercase "#rrggbb" line per color.
+pub fn encode_hex_palette(palette : &[Rgb<u8>]) -> String {
+    palette.iter().map(|Rgb([r, g, b])| format!("#{:02x}{:02x}{:02x}\n", r, g, b)).collect()
+}
+
+
+///    ### (PURE)
+///    Parses a hex list written by encode_hex_palette. The '#' is
+///    optional, blank lines and lines starting with ';' are skipped.
+///    Fails on any other line and on lists without colors.
+pub fn decode_hex_palette(text : &str) -> std::io::Result<Vec<Rgb<u8>>> {
+    let invalid = |reason : String| Error::new(ErrorKind::InvalidData, format!("not a hex palette: {}", reason));
+    let palette = text
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with(COMMENT))
+        .map(|(i, line)|{
+            let hex = line.strip_prefix('#').unwrap_or(line);
+            let channel = |at : usize| hex.get(at..at + 2).and_then(|digits| u8::from_str_radix(digits, 16).ok());
+            match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Rgb([r, g, b])),
+                _ => Err(invalid(format!("line {}: expected #RRGGBB, got '{}'", i + 1, line))),
+            }
+        }).collect::<std::io::Result<Vec<_>>>()?;
+    if palette.is_empty() {
+        return Err(invalid("no colors".to_string()));
+    }
+    Ok(palette)
+}
+
+
+///    ### (I/O)
+///    Writes palette to path as a hex list, see encode_hex_palette.
+pub fn save_palette(palette : &[Rgb<u8>], path : &str) -> std::io::Result<()> {
+    std::fs::write(path, encode_hex_palette(palette))
+}
+
+
+///    ### (I/O)
+///    Composes save_palette with the path "{OUTPUT_DIR}/{PALETTE_HEX_FILE}",
+///    returning the path.
+pub fn export_palette(palette : &[Rgb<u8>]) -> std::io::Result<String> {
+    let path = format!("{}/{}", OUTPUT_DIR, PALETTE_HEX_FILE);
+    save_palette(palette, &path)?;
+    Ok(path)
+}
+
+
+///    ### (I/O)
+///    Reads the hex list palette at path, see decode_hex_palette.
+pub fn load_palette_from_file(path : &str) -> std::io::Result<Vec<Rgb<u8>>> {
+    decode_hex_palette(&std::fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_file_round_trips() {
+        use crate::painter::generate_seeded_palette;
+        let palette = generate_seeded_palette(300, 7);
+        assert_eq!(decode_hex_palette(&encode_hex_palette(&palette)).unwrap(), palette);
+        let edited = format!("; edited\n\n{}FF8000\n", encode_hex_palette(&palette[..2]));
+        assert_eq!(decode_hex_palette(&edited).unwrap(), [palette[0], palette[1], image::Rgb([255, 128, 0])]);
+        assert!(decode_hex_palette("#12345\n").is_err());
+        assert!(decode_hex_palette("; nothing\n").is_err());
+    }
+}
@@ -0,0 +1,58 @@
+//!   Module contains the parallel iterator API used by the
+//!   library. With the `parallel` feature it is rayon's, without
+//!   it the same method names run sequentially on std iterators,
+//!   for targets without threads such as wasm32. Also builds the
+//!   pool of --threads and --single-thread.
+//!   @author Van Gouache
+
+#[cfg(feature = "parallel")]
+pub use rayon::prelude::*;
+
+
+///    ### (PURE)
+///    Builder of the pool a --threads or --single-thread run uses, None
+///    keeps rayon's default of one worker per core. With single_thread
+///    the calling thread is the pool's only worker, so every par_iter
+///    installed on it runs in order on that thread.
+#[cfg(feature = "parallel")]
+pub fn pool_builder(threads : Option<usize>, single_thread : bool) -> Option<rayon::ThreadPoolBuilder> {
+    let threads = threads.or(single_thread.then_some(1))?;
+    let builder = rayon::ThreadPoolBuilder::new().num_threads(threads);
+    Some(if single_thread { builder.use_current_thread() } else { builder })
+}
+
+#[cfg(not(feature = "parallel"))]
+pub use sequential::*;
+
+#[cfg(not(feature = "parallel"))]
+mod sequential {
+    ///    Sequential stand-in for rayon's into_par_iter.
+    pub trait IntoParallelIterator : IntoIterator + Sized {
+        fn into_par_iter(self) -> Self::IntoIter {
+            self.into_iter()
+        }
+    }
+
+    impl<I : IntoIterator> IntoParallelIterator for I {}
+
+    ///    Sequential stand-ins for rayon's slice methods.
+    pub trait ParallelSlice<T> {
+        fn par_iter(&self) -> std::slice::Iter<'_, T>;
+        fn par_iter_mut(&mut self) -> std::slice::IterMut<'_, T>;
+        fn par_chunks_mut(&mut self, chunk_size : usize) -> std::slice::ChunksMut<'_, T>;
+    }
+
+    impl<T> ParallelSlice<T> for [T] {
+        fn par_iter(&self) -> std::slice::Iter<'_, T> {
+            self.iter()
+        }
+
+        fn par_iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+            self.iter_mut()
+        }
+
+        fn par_chunks_mut(&mut self, chunk_size : usize) -> std::slice::ChunksMut<'_, T> {
+            self.chunks_mut(chunk_size)
+        }
+    }
+}
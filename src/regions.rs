@@ -0,0 +1,123 @@
+//!   Module contains a flood fill labelling the connected
+//!   interior regions of an escape grid, used to tell the main
+//!   body of the ship apart from its smaller satellites.
+//!   @author Van Gouache
+
+use crate::burning_ship_frac::EscapeValue;
+
+
+///    Connected interior regions of a frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InteriorRegions {
+    ///    Region of every cell, 0 for escaped cells and 1.. for interior
+    ///    cells, row-major like the frame.
+    pub labels : Vec<Vec<u32>>,
+    ///    Cell count of region n at sizes\[n - 1\].
+    pub sizes : Vec<usize>,
+}
+
+impl InteriorRegions {
+    ///    ### (PURE)
+    ///    Label of the region with the most cells, None if the frame has
+    ///    no interior.
+    pub fn largest(&self) -> Option<u32> {
+        self.sizes
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, size)| **size)
+        .map(|(index, _)| index as u32 + 1)
+    }
+}
+
+
+///    ### (PURE)
+///    Labels the 4-connected regions of interior cells (escape value of
+///    max_iterations or more) with an iterative flood fill.
+pub fn label_interior_regions<T : EscapeValue>(
+    frame : &[Vec<T>],
+    max_iterations : u32
+) -> InteriorRegions
+{
+    let interior = |x : usize, y : usize| frame[y][x].escape_value() >= max_iterations as f64;
+    let mut labels : Vec<Vec<u32>> = frame.iter().map(|row| vec![0; row.len()]).collect();
+    let mut sizes = Vec::new();
+    let mut stack = Vec::new();
+    for y in 0..frame.len() {
+        for x in 0..frame[y].len() {
+            if labels[y][x] != 0 || !interior(x, y) {
+                continue;
+            }
+            let label = sizes.len() as u32 + 1;
+            let mut size = 0;
+            labels[y][x] = label;
+            stack.push((x, y));
+            while let Some((cx, cy)) = stack.pop() {
+                size += 1;
+                let neighbours = [
+                    (cx.wrapping_sub(1), cy),
+                    (cx + 1, cy),
+                    (cx, cy.wrapping_sub(1)),
+                    (cx, cy + 1),
+                ];
+                for (nx, ny) in neighbours {
+                    let in_frame = ny < frame.len() && nx < frame[ny].len();
+                    if in_frame && labels[ny][nx] == 0 && interior(nx, ny) {
+                        labels[ny][nx] = label;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+    }
+    InteriorRegions { labels, sizes }
+}
+
+
+///    ### (PURE)
+///    Mask of the cells in the largest interior region of frame.
+pub fn main_body_mask<T : EscapeValue>(frame : &[Vec<T>], max_iterations : u32) -> Vec<Vec<bool>> {
+    let regions = label_interior_regions(frame, max_iterations);
+    // ⬇
+    let body = regions.largest();
+    regions.labels
+    .iter()
+    .map(|row| row.iter().map(|label| Some(*label) == body).collect())
+    .collect()
+}
+
+
+///    ### (PURE)
+///    Chamfer distance in cells from every interior cell to the nearest
+///    escaped cell, 0.0 for escaped cells. Two raster passes with weights
+///    1 (edge) and sqrt 2 (diagonal) approximate the Euclidean distance.
+pub fn boundary_distance<T : EscapeValue>(frame : &[Vec<T>], max_iterations : u32) -> Vec<Vec<f64>> {
+    let mut distance : Vec<Vec<f64>> = frame
+    .iter()
+    .map(|row| row.iter().map(|cell|{
+        if cell.escape_value() >= max_iterations as f64 { f64::INFINITY } else { 0.0 }
+    }).collect())
+    .collect();
+    let diagonal = std::f64::consts::SQRT_2;
+    let forward = [(-1, 0, 1.0), (0, -1, 1.0), (-1, -1, diagonal), (1, -1, diagonal)];
+    let backward = [(1, 0, 1.0), (0, 1, 1.0), (1, 1, diagonal), (-1, 1, diagonal)];
+    let relax = |distance : &mut Vec<Vec<f64>>, x : usize, y : usize, steps : &[(isize, isize, f64)]|{
+        for (dx, dy, weight) in steps {
+            let (nx, ny) = (x.wrapping_add_signed(*dx), y.wrapping_add_signed(*dy));
+            if ny < distance.len() && nx < distance[ny].len() {
+                distance[y][x] = distance[y][x].min(distance[ny][nx] + weight);
+            }
+        }
+    };
+    for y in 0..distance.len() {
+        for x in 0..distance[y].len() {
+            relax(&mut distance, x, y, &forward);
+        }
+    }
+    for y in (0..distance.len()).rev() {
+        for x in (0..distance[y].len()).rev() {
+            relax(&mut distance, x, y, &backward);
+        }
+    }
+    distance
+}
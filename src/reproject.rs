@@ -0,0 +1,181 @@
+//!   Module contains frame reprojection for long zooms. Frame
+//!   k+1 of a zoom is a magnified crop of frame k, so its cells
+//!   inside flat areas of frame k are copied over and only the
+//!   cells near detail are iterated afresh.
+//!   @author Van Gouache
+
+use std::ops::Range;
+use std::time::{Duration, Instant};
+use crate::parallel::*;
+use crate::burning_ship_frac::{
+    calc_step_size, frame_ranges, get_orbit_rate, render_escape_grid, validate_viewport, EscapeCount, Fractal, RenderError
+};
+use crate::config::{AnimationMode, RenderConfig};
+
+//names of the ReprojectQuality settings, as accepted by --reproject
+pub const QUALITY_NAMES : [&str; 2] = ["hybrid", "fast"];
+
+
+///    How sure a cell of the previous frame must be before it is reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReprojectQuality {
+    ///    Reuse a cell only when the 3x3 cells of the previous frame around
+    ///    the nearest one agree, iterating everything near an edge.
+    #[default]
+    Hybrid,
+    ///    Reuse a cell when the 2x2 cells of the previous frame it falls
+    ///    between agree. Reuses more, but can round off features thinner
+    ///    than a pixel.
+    Fast,
+}
+
+impl ReprojectQuality {
+    ///    ### (PURE)
+    ///    Quality called name, one of QUALITY_NAMES.
+    pub fn from_name(name : &str) -> Option<Self> {
+        match name {
+            "hybrid" => Some(ReprojectQuality::Hybrid),
+            "fast" => Some(ReprojectQuality::Fast),
+            _ => None,
+        }
+    }
+
+    ///    ### (PURE)
+    ///    First index and length of the run of source cells checked
+    ///    along one axis for source position position.
+    fn neighbourhood(&self, position : f64) -> (f64, usize) {
+        match self {
+            ReprojectQuality::Hybrid => (position.round() - 1.0, 3),
+            ReprojectQuality::Fast => (position.floor(), 2),
+        }
+    }
+}
+
+
+///    ### (PURE)
+///    Cell of previous at source position (source_x, source_y) when the
+///    neighbourhood quality checks lies inside previous and holds a single
+///    value, None when the cell has to be iterated.
+fn reused_cell<T : Copy + PartialEq>(
+    previous : &[Vec<T>],
+    source_x : f64,
+    source_y : f64,
+    quality : ReprojectQuality
+) -> Option<T>
+{
+    let ((first_x, span_x), (first_y, span_y)) = (quality.neighbourhood(source_x), quality.neighbourhood(source_y));
+    let width = previous.first()?.len();
+    if first_x < 0.0 || first_y < 0.0 || first_x as usize + span_x > width || first_y as usize + span_y > previous.len() {
+        return None;
+    }
+    let (first_x, first_y) = (first_x as usize, first_y as usize);
+    let value = previous[first_y][first_x];
+    previous[first_y..first_y + span_y]
+        .iter()
+        .all(|row| row[first_x..first_x + span_x].iter().all(|cell| *cell == value))
+        .then_some(value)
+}
+
+
+///    ### (PURE)
+///    Escape grid of frame_number of config built from previous, the grid
+///    of frame_number - 1: cells reused_cell finds in flat areas of
+///    previous are copied, the rest are iterated with get_orbit_rate.
+///    Frame 0, Julia sweeps, frames whose iteration cap differs from the
+///    one before and previous grids of the wrong size are rendered in full
+///    with render_escape_grid.
+pub fn reproject_escape_grid<T : EscapeCount + PartialEq>(
+    config : &RenderConfig,
+    frame_number : u32,
+    previous : &[Vec<T>],
+    quality : ReprojectQuality
+) -> Result<Fractal<T>, RenderError>
+{
+    let reprojectable = frame_number > 0
+        && config.animation == AnimationMode::Zoom
+        && config.max_iterations_at(frame_number) == config.max_iterations_at(frame_number - 1)
+        && previous.len() == config.img_height
+        && previous.iter().all(|row| row.len() == config.img_width);
+    if !reprojectable {
+        return render_escape_grid(config, frame_number);
+    }
+    let (x_range, y_range) = frame_ranges(config, frame_number);
+    let (x_step_size, y_step_size) = validate_viewport(config.img_width, config.img_height, x_range, y_range)?;
+    let (previous_x_range, previous_y_range) = frame_ranges(config, frame_number - 1);
+    let (previous_x_step, previous_y_step) = calc_step_size(
+        config.img_width,
+        config.img_height,
+        previous_x_range,
+        previous_y_range
+    );
+    let params = config.orbit_params(frame_number);
+    let (frame, reused) : (Fractal<T>, Vec<usize>) = (0..config.img_height).into_par_iter().map(|y|{
+        let row = params.row_position(y as f64);
+        let source_y = params.row_position((y_range.0 + row * y_step_size - previous_y_range.0) / previous_y_step);
+        let mut reused = 0;
+        let row = (0..config.img_width).map(|x|{
+            let source_x = (x_range.0 + x as f64 * x_step_size - previous_x_range.0) / previous_x_step;
+            if let Some(cell) = reused_cell(previous, source_x, source_y, quality) {
+                reused += 1;
+                return cell;
+            }
+            T::from_count(get_orbit_rate(x, y, x_step_size, y_step_size, x_range.0, y_range.0, &params))
+        }).collect();
+        (row, reused)
+    }).unzip();
+    // ⬇
+    log::debug!(
+        "~~~Reprojected frame {}, reused {} of {} cells~~~",
+        frame_number,
+        reused.iter().sum::<usize>(),
+        config.img_width * config.img_height
+    );
+    Ok(frame)
+}
+
+
+///    ### (PURE)
+///    Renders the frames of frame_range in order, the first in full with
+///    render_escape_grid and each later one reprojected from the one
+///    before it, returning every grid with the time it took to build.
+///    Errors compound from frame to frame, so keep frame_range to a burst
+///    and start the next one from a full render.
+pub fn render_reprojected_frames<T : EscapeCount + PartialEq>(
+    config : &RenderConfig,
+    frame_range : Range<u32>,
+    quality : ReprojectQuality
+) -> Result<Vec<(Fractal<T>, Duration)>, RenderError>
+{
+    let mut frames : Vec<(Fractal<T>, Duration)> = Vec::with_capacity(frame_range.len());
+    for frame_number in frame_range {
+        let frame_timer = Instant::now();
+        let frame = match frames.last() {
+            Some((previous, _)) => reproject_escape_grid(config, frame_number, previous, quality)?,
+            None => render_escape_grid(config, frame_number)?,
+        };
+        frames.push((frame, frame_timer.elapsed()));
+    }
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reprojected_frames_match_full_renders_away_from_edges() {
+        let config = RenderConfig {
+            img_width : 96,
+            img_height : 64,
+            zoom_center : Some((-1.75, -0.03)),
+            ..RenderConfig::default()
+        };
+        let frames = render_reprojected_frames::<u32>(&config, 3..6, ReprojectQuality::Hybrid).unwrap();
+        assert_eq!(frames[0].0, render_escape_grid::<u32>(&config, 3).unwrap());
+        for (frame_number, (frame, _)) in (3..).zip(&frames) {
+            let exact : Vec<Vec<u32>> = render_escape_grid(&config, frame_number).unwrap();
+            let differing = exact.iter().flatten().zip(frame.iter().flatten()).filter(|(a, b)| a != b).count();
+            assert!(differing * 100 < config.img_width * config.img_height, "frame {}: {} cells differ", frame_number, differing);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//!   Module contains funcs resampling escape grids, used to
+//!   blow a cheap low resolution render up to the output size
+//!   for quick previews.
+//!   @author Van Gouache
+
+use crate::parallel::*;
+use crate::burning_ship_frac::{render_escape_grid, EscapeValue, Fractal, RenderError};
+use crate::config::RenderConfig;
+
+
+///    ### (PURE)
+///    Position in a source axis of length source_len sampled by pixel
+///    index of a target axis of length target_len, aligning pixel centers.
+///    Returns the two neighbouring source indices and the weight of the
+///    second one.
+fn sample_position(index : usize, source_len : usize, target_len : usize) -> (usize, usize, f64) {
+    let position = ((index as f64 + 0.5) * source_len as f64 / target_len as f64 - 0.5)
+        .clamp(0.0, (source_len - 1) as f64);
+    let low = position.floor() as usize;
+    let high = (low + 1).min(source_len - 1);
+    (low, high, position - low as f64)
+}
+
+
+///    ### (PURE)
+///    Resizes frame to target_width x target_height, bilinearly
+///    interpolating its escape values. Interpolated cells are fractional,
+///    so the result paints like a smooth grid.
+pub fn upscale_fractal<T : EscapeValue>(
+    frame : &[Vec<T>],
+    target_width : usize,
+    target_height : usize
+) -> Fractal<f64>
+{
+    let source_height = frame.len();
+    let source_width = frame.first().map_or(0, |row| row.len());
+    if source_width == 0 || source_height == 0 {
+        return vec![vec![0.0; target_width]; target_height];
+    }
+    (0..target_height).into_par_iter().map(|y|{
+        let (top, bottom, ty) = sample_position(y, source_height, target_height);
+        (0..target_width).map(|x|{
+            let (left, right, tx) = sample_position(x, source_width, target_width);
+            let lerp = |row : &[T]| {
+                row[left].escape_value() * (1.0 - tx) + row[right].escape_value() * tx
+            };
+            lerp(&frame[top]) * (1.0 - ty) + lerp(&frame[bottom]) * ty
+        }).collect()
+    }).collect()
+}
+
+
+///    ### (PURE)
+///    Renders frame_number of config at 1/scale of its resolution and
+///    upscales it back to config.img_width x config.img_height, costing
+///    about 1/scale^2 of a full render.
+pub fn render_preview_grid(
+    config : &RenderConfig,
+    frame_number : u32,
+    scale : u32
+) -> Result<Fractal<f64>, RenderError>
+{
+    let preview_config = RenderConfig {
+        img_width : (config.img_width / scale as usize).max(1),
+        img_height : (config.img_height / scale as usize).max(1),
+        ..config.clone()
+    };
+    let frame : Fractal<u32> = render_escape_grid(&preview_config, frame_number)?;
+    // ⬇
+    Ok(upscale_fractal(&frame, config.img_width, config.img_height))
+}
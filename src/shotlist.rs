@@ -0,0 +1,122 @@
+//!   Module contains the shot list of a multi-shot render, config
+//!   files rendered back to back into one continuously numbered
+//!   frame sequence, e.g. for a reel. Each shot keeps the random
+//!   palette its config was rendered with.
+//!   @author Van Gouache
+
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use crate::config::RenderConfig;
+use crate::metadata::{self, RecordedPalette};
+
+
+///    One shot of a shot list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shot {
+    ///    Path of the render config (a sidecar JSON) of the shot.
+    pub config_path : String,
+    pub config : RenderConfig,
+    ///    Frames 0..frames of config make up the shot.
+    pub frames : u32,
+    ///    Output frame number of the shot's first frame, the frame count
+    ///    of every shot before it.
+    pub first_output : u32,
+    ///    Random palette recorded in the config, None for preset
+    ///    palettes, see Sidecar::recorded_palette.
+    pub palette : Option<RecordedPalette>,
+}
+
+
+///    ### (PURE)
+///    Parses a shot list line "CONFIG FRAMES" into its config path and
+///    frame count. Blank lines and lines starting with '#' give None.
+pub fn parse_shot_line(line : &str) -> Option<Result<(String, u32), String>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let invalid = || format!("expected CONFIG FRAMES, got '{}'", line);
+    Some(line.rsplit_once(char::is_whitespace).ok_or_else(invalid).and_then(|(path, frames)|{
+        let frames = frames.parse::<u32>().map_err(|_| invalid())?;
+        Ok((path.trim_end().to_string(), frames))
+    }))
+}
+
+
+///    ### (I/O)
+///    Reads the shot list at path, one "CONFIG FRAMES" line per shot.
+///    Config paths are relative to the shot list's directory and each
+///    config is read like --from reads a sidecar, along with its recorded
+///    palette. Fails on malformed lines, unreadable configs, configs
+///    RenderConfig::validate rejects, unknown palette blend modes or
+///    more than u32::MAX frames in total.
+pub fn read_shotlist(path : &str) -> std::io::Result<Vec<Shot>> {
+    let text = std::fs::read_to_string(path)?;
+    let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+    let mut shots = Vec::new();
+    let mut first_output : u32 = 0;
+    for (line_number, line) in text.lines().enumerate() {
+        let Some(shot) = parse_shot_line(line) else {
+            continue;
+        };
+        let (config_path, frames) = shot.map_err(|err|{
+            Error::new(ErrorKind::InvalidData, format!("line {}: {}", line_number + 1, err))
+        })?;
+        let config_path = dir.join(config_path).to_string_lossy().into_owned();
+        let sidecar = metadata::read_sidecar(&config_path).map_err(|err|{
+            Error::new(err.kind(), format!("{}: {}", config_path, err))
+        })?;
+        sidecar.config.validate().map_err(|err|{
+            Error::new(ErrorKind::InvalidData, format!("{}: {}", config_path, err))
+        })?;
+        let palette = sidecar.recorded_palette().map_err(|err|{
+            Error::new(err.kind(), format!("{}: {}", config_path, err))
+        })?;
+        shots.push(Shot {
+            config_path,
+            config : sidecar.config,
+            frames,
+            first_output,
+            palette,
+        });
+        first_output = first_output.checked_add(frames).ok_or_else(||{
+            Error::new(ErrorKind::InvalidData, format!("more than {} frames in total", u32::MAX))
+        })?;
+    }
+    Ok(shots)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::Sidecar;
+
+    #[test]
+    fn shot_configs_are_validated() {
+        let dir = std::env::temp_dir().join(format!("burning_ship_shotlist_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("shot.json");
+        let mut sidecar = Sidecar {
+            config : RenderConfig::default(),
+            max_escapes : Default::default(),
+            iteration_caps : Default::default(),
+            palette_seed : Some(7),
+            blend_seeds : Vec::new(),
+            palette_blend : None,
+        };
+        metadata::write_sidecar(&sidecar, config_path.to_str().unwrap()).unwrap();
+        let shotlist_path = dir.join("shots.txt");
+        std::fs::write(&shotlist_path, "# reel\nshot.json 3\nshot.json 2\n").unwrap();
+        let shots = read_shotlist(shotlist_path.to_str().unwrap()).unwrap();
+        assert_eq!(shots.iter().map(|shot| (shot.frames, shot.first_output)).collect::<Vec<_>>(), [(3, 0), (2, 3)]);
+        assert_eq!(shots[0].palette, Some(RecordedPalette::Seeded(7)));
+
+        sidecar.config.max_iterations = 0;
+        metadata::write_sidecar(&sidecar, config_path.to_str().unwrap()).unwrap();
+        let err = read_shotlist(shotlist_path.to_str().unwrap()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains("shot.json"), "{}", err);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+//!   Module contains funcs joining two frames of equal height
+//!   side by side, as escape grids or painted images, with an
+//!   optional divider between them. Useful for comparison
+//!   figures of two fractals or settings over one viewport.
+//!   @author Van Gouache
+
+use std::fmt;
+use image::{ImageBuffer, Rgb};
+use crate::parallel::*;
+use crate::burning_ship_frac::Fractal;
+
+
+///    Heights of two frames that cannot be joined side by side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeightMismatch {
+    pub left : usize,
+    pub right : usize,
+}
+
+impl fmt::Display for HeightMismatch {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "frames must be equally tall to join, got {} and {} rows", self.left, self.right)
+    }
+}
+
+impl std::error::Error for HeightMismatch {}
+
+
+///    ### (PURE)
+///    Grid with the rows of left followed by the rows of right, separated
+///    by divider = (width, cell) columns of cell when given.
+pub fn join_grids<T : Copy + Send + Sync>(
+    left : &[Vec<T>],
+    right : &[Vec<T>],
+    divider : Option<(usize, T)>
+) -> Result<Fractal<T>, HeightMismatch>
+{
+    if left.len() != right.len() {
+        return Err(HeightMismatch { left : left.len(), right : right.len() });
+    }
+    Ok(left.par_iter().zip(right.par_iter()).map(|(left_row, right_row)|{
+        let mut row = Vec::with_capacity(left_row.len() + right_row.len() + divider.map_or(0, |(width, _)| width));
+        row.extend_from_slice(left_row);
+        if let Some((width, cell)) = divider {
+            row.extend(std::iter::repeat_n(cell, width));
+        }
+        row.extend_from_slice(right_row);
+        row
+    }).collect())
+}
+
+
+///    ### (PURE)
+///    Image with left on the left and right on the right, separated by a
+///    divider = (width, color) stripe when given.
+pub fn join_images(
+    left : &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    right : &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    divider : Option<(u32, Rgb<u8>)>
+) -> Result<ImageBuffer<Rgb<u8>, Vec<u8>>, HeightMismatch>
+{
+    if left.height() != right.height() {
+        return Err(HeightMismatch { left : left.height() as usize, right : right.height() as usize });
+    }
+    let (divider_width, divider_color) = divider.unwrap_or((0, Rgb([0, 0, 0])));
+    let mut joined = ImageBuffer::from_pixel(left.width() + divider_width + right.width(), left.height(), divider_color);
+    // ⬇
+    image::imageops::replace(&mut joined, left, 0, 0);
+    image::imageops::replace(&mut joined, right, (left.width() + divider_width) as i64, 0);
+    Ok(joined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_grids_places_frames_side_by_side() {
+        let left : Fractal<u8> = vec![vec![1, 2], vec![3, 4]];
+        let right : Fractal<u8> = vec![vec![5], vec![6]];
+        assert_eq!(join_grids(&left, &right, None).unwrap(), vec![vec![1, 2, 5], vec![3, 4, 6]]);
+        assert_eq!(join_grids(&left, &right, Some((2, 0))).unwrap(), vec![vec![1, 2, 0, 0, 5], vec![3, 4, 0, 0, 6]]);
+        assert_eq!(join_grids(&left, &right[..1], None), Err(HeightMismatch { left : 2, right : 1 }));
+    }
+}
@@ -0,0 +1,131 @@
+//!   Module contains a SIMD variant of fill_row, enabled by
+//!   the `simd` feature. LANES neighbouring pixels of a row are
+//!   iterated together with the `wide` crate; lanes whose orbit
+//!   escapes are masked out and stop counting. The arithmetic is
+//!   ordered exactly like calculate_next_z so results are identical
+//!   to the scalar path, which still handles the columns left over
+//!   when the row width is not a multiple of LANES.
+//!   @author Van Gouache
+
+use wide::f64x4;
+use crate::burning_ship_frac::{get_orbit_rate, pixel_coordinate, EscapeCount, FractalKind, OrbitParams};
+
+const LANES : usize = 4;
+
+
+///    ### (PURE)
+///    Iterates LANES points at once from Z\[0\] = (start_a, start_b),
+///    returning the orbit rate of each lane.
+fn get_orbit_rates(
+    start_a : f64x4,
+    start_b : f64x4,
+    constant_a : f64x4,
+    constant_b : f64x4,
+    params : &OrbitParams
+) -> [u32; LANES] {
+    let one = f64x4::splat(1.0);
+    let k_a = f64x4::splat(params.coefficients.0);
+    let k_b = f64x4::splat(params.coefficients.1);
+    let two = f64x4::splat(2.0);
+    let bailout = f64x4::splat(4.0);
+
+    let mut a = start_a;
+    let mut b = start_b;
+    let mut counts = f64x4::ZERO;
+    // all lanes start active
+    let mut active = f64x4::ZERO.simd_lt(one);
+    for _ in 0..params.iteration_cap() {
+        let sqr_a = a * a;
+        let sqr_b = b * b;
+        active &= (sqr_a + sqr_b).simd_lt(bailout);
+        if !active.any() {
+            break;
+        }
+        counts += active & one;
+        let new_a = k_a * (sqr_a - sqr_b) + constant_a;
+        let new_b = (k_b * (two * a * b).abs()).abs() + constant_b;
+        a = active.select(new_a, a);
+        b = active.select(new_b, b);
+    }
+    counts.to_array().map(|count| params.interior_clamp(count as u32))
+}
+
+
+///    ### (PURE)
+///    SIMD counterpart of fill_row, overwrites row with the orbit rates
+///    of row row_index.
+pub fn fill_row<T : EscapeCount>(
+    row_index : usize,
+    row : &mut [T],
+    x_step_size : f64,
+    y_step_size : f64,
+    x_floor : f64,
+    y_floor : f64,
+    params : &OrbitParams
+) {
+    let img_width = row.len();
+    let simd_width = img_width - img_width % LANES;
+    for col_index in (0..simd_width).step_by(LANES) {
+        let pixels : [_; LANES] = std::array::from_fn(|lane| pixel_coordinate(
+            (col_index + lane) as f64,
+            row_index as f64,
+            x_step_size,
+            y_step_size,
+            x_floor,
+            y_floor,
+            params
+        ));
+        let start_a = f64x4::new(pixels.map(|pixel| pixel.re));
+        let start_b = f64x4::new(pixels.map(|pixel| pixel.im));
+        let (seed_a, seed_b, constant_a, constant_b) = match (params.kind, params.z0) {
+            (FractalKind::BurningShip, None) => (start_a, start_b, start_a, start_b),
+            (FractalKind::BurningShip, Some(z0)) => (f64x4::splat(z0.0), f64x4::splat(z0.1), start_a, start_b),
+            (FractalKind::Julia { c }, _) => (start_a, start_b, f64x4::splat(c.0), f64x4::splat(c.1)),
+        };
+        let rates = get_orbit_rates(seed_a, seed_b, constant_a, constant_b, params);
+        for (cell, rate) in row[col_index..col_index + LANES].iter_mut().zip(rates) {
+            *cell = T::from_count(rate);
+        }
+    }
+    // scalar fallback for the trailing columns
+    for (col_index, cell) in row.iter_mut().enumerate().skip(simd_width) {
+        *cell = T::from_count(get_orbit_rate(
+            col_index,
+            row_index,
+            x_step_size,
+            y_step_size,
+            x_floor,
+            y_floor,
+            params
+        ));
+    }
+}
+
+#[cfg(all(test, feature = "simd"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_row_matches_the_scalar_orbit_rates() {
+        let base = OrbitParams { max_iterations : 200, ..OrbitParams::default() };
+        let variants = [
+            base,
+            OrbitParams { kind : FractalKind::Julia { c : (-1.2, -0.3) }, ..base },
+            OrbitParams { z0 : Some((0.1, -0.2)), ..base },
+            OrbitParams { interior_threshold : Some(50), ..base },
+        ];
+        // 4 * LANES + 3 columns, so the scalar fallback runs too
+        let img_width = 4 * LANES + 3;
+        let (x_step_size, y_step_size) = (3.0 / img_width as f64, 0.1);
+        for params in variants {
+            for row_index in 0..20 {
+                let mut row = vec![0u32; img_width];
+                fill_row(row_index, &mut row, x_step_size, y_step_size, -2.0, -1.0, &params);
+                let scalar : Vec<u32> = (0..img_width).map(|col_index|{
+                    get_orbit_rate(col_index, row_index, x_step_size, y_step_size, -2.0, -1.0, &params)
+                }).collect();
+                assert_eq!(row, scalar, "row {} of {:?}", row_index, params);
+            }
+        }
+    }
+}
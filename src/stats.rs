@@ -0,0 +1,189 @@
+//!   Module contains funcs summarising the escape values
+//!   of a rendered frame.
+//!   @author Van Gouache
+
+use std::fmt;
+use std::time::Duration;
+use crate::burning_ship_frac::EscapeValue;
+
+
+///    Escape iteration statistics of a single frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameStats {
+    pub min_escape : u32,
+    pub max_escape : u32,
+    pub mean_escape : f64,
+    ///    Fraction of pixels that never escaped \[0 to 1\].
+    pub interior_fraction : f64,
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "escape min {} / max {} / mean {:.2}, interior {:.1}%",
+            self.min_escape,
+            self.max_escape,
+            self.mean_escape,
+            self.interior_fraction * 100.0
+        )
+    }
+}
+
+
+///    ### (PURE)
+///    Scans a frame once and summarises its escape values. Cells equal to
+///    max_iterations count as interior, smooth cells are truncated to
+///    their orbit rate.
+pub fn frame_stats<T : EscapeValue>(
+    frame : &[Vec<T>],
+    max_iterations : u32
+) -> FrameStats
+{
+    let mut min_escape = u32::MAX;
+    let mut max_escape = 0;
+    let mut total : u64 = 0;
+    let mut interior : usize = 0;
+    let mut count : usize = 0;
+    for cell in frame.iter().flatten() {
+        let escape = cell.escape_value() as u32;
+        min_escape = min_escape.min(escape);
+        max_escape = max_escape.max(escape);
+        total += escape as u64;
+        if escape >= max_iterations {
+            interior += 1;
+        }
+        count += 1;
+    }
+    if count == 0 {
+        return FrameStats {
+            min_escape : 0,
+            max_escape : 0,
+            mean_escape : 0.0,
+            interior_fraction : 0.0,
+        };
+    }
+    FrameStats {
+        min_escape,
+        max_escape,
+        mean_escape : total as f64 / count as f64,
+        interior_fraction : interior as f64 / count as f64,
+    }
+}
+
+
+///    ### (PURE)
+///    Lowest and highest orbit rate of the escaping cells of frame,
+///    leaving out interior cells. None when no cell escapes.
+pub fn escape_bounds<T : EscapeValue>(frame : &[Vec<T>], max_iterations : u32) -> Option<(u32, u32)> {
+    frame.iter().flatten()
+    .map(|cell| cell.escape_value() as u32)
+    .filter(|escape| *escape < max_iterations)
+    .fold(None, |bounds, escape| match bounds {
+        Some((low, high)) => Some((escape.min(low), escape.max(high))),
+        None => Some((escape, escape)),
+    })
+}
+
+
+///    ### (PURE)
+///    Counts the escaping cells of frame by orbit rate, entry i holds the
+///    number of cells escaping after i iterations \[0 to max_iterations)\.
+///    Interior cells are left out.
+pub fn escape_histogram<T : EscapeValue>(
+    frame : &[Vec<T>],
+    max_iterations : u32
+) -> Vec<usize>
+{
+    let mut histogram = vec![0; max_iterations as usize];
+    for cell in frame.iter().flatten() {
+        if let Some(count) = histogram.get_mut(cell.escape_value() as usize) {
+            *count += 1;
+        }
+    }
+    histogram
+}
+
+
+///    ### (PURE)
+///    Smallest orbit rate at or below which at least fraction \[0 to 1\]
+///    of the cells counted in histogram escape. None for an empty
+///    histogram.
+pub fn escape_percentile(histogram : &[usize], fraction : f64) -> Option<u32> {
+    let total : usize = histogram.iter().sum();
+    if total == 0 {
+        return None;
+    }
+    let target = (total as f64 * fraction).ceil().max(1.0) as usize;
+    let mut seen = 0;
+    histogram.iter().position(|count|{
+        seen += count;
+        seen >= target
+    }).map(|rate| rate as u32)
+}
+
+
+///    Spread of per-frame render times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingSummary {
+    pub min : Duration,
+    pub median : Duration,
+    pub mean : Duration,
+    pub max : Duration,
+}
+
+impl fmt::Display for TimingSummary {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "frame time min {:?} / median {:?} / mean {:?} / max {:?}",
+            self.min,
+            self.median,
+            self.mean,
+            self.max
+        )
+    }
+}
+
+
+///    ### (PURE)
+///    Summarises frame render times, None if there are none. The median of
+///    an even count is the upper of the two middle times.
+///    See split_warmup to leave out the cold start of each burst.
+pub fn timing_summary(times : &[Duration]) -> Option<TimingSummary> {
+    let mut sorted = times.to_vec();
+    sorted.sort();
+    Some(TimingSummary {
+        min : *sorted.first()?,
+        median : sorted[sorted.len() / 2],
+        mean : sorted.iter().sum::<Duration>() / sorted.len() as u32,
+        max : *sorted.last()?,
+    })
+}
+
+
+///    ### (PURE)
+///    Splits the frame times of a burst into the time of its first frame,
+///    slowed by thread pool spin-up and cold caches, and the steady state
+///    times of the rest. None and all of times when warmup is false.
+pub fn split_warmup(times : &[Duration], warmup : bool) -> (Option<Duration>, &[Duration]) {
+    match times.split_first() {
+        Some((first, rest)) if warmup => (Some(*first), rest),
+        _ => (None, times),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warmup_is_split_off_the_steady_state() {
+        let times = [90, 10, 12, 14].map(Duration::from_millis);
+        assert_eq!(split_warmup(&times, false), (None, &times[..]));
+        let (warmup, steady) = split_warmup(&times, true);
+        assert_eq!(warmup, Some(Duration::from_millis(90)));
+        assert_eq!(timing_summary(steady).unwrap().mean, Duration::from_millis(12));
+        assert_eq!(split_warmup(&[], true), (None, &[][..]));
+    }
+}
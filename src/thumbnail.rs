@@ -0,0 +1,158 @@
+//!   Module contains a scouting preview of a whole zoom: every
+//!   Nth frame rendered at thumbnail resolution and assembled
+//!   into a single looping GIF, to check the path stays
+//!   interesting all the way down before a full render, or
+//!   evenly spaced frames pasted side by side into a strip
+//!   summarizing the zoom in one image.
+//!   @author Van Gouache
+
+use std::fs::File;
+use std::io::BufWriter;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, DynamicImage, Frame, ImageBuffer, ImageResult, Rgb};
+use crate::burning_ship_frac::{precision_exhausted, render_escape_grid, Fractal, RenderError};
+use crate::config::RenderConfig;
+use crate::painter::{paint_frame, PaintOptions, OUTPUT_DIR};
+use crate::side_by_side::join_images;
+
+//file name of the thumbnail GIF inside OUTPUT_DIR
+pub const THUMBNAIL_FILE : &str = "zoom.gif";
+//pixel width of thumbnail frames, heights keep the aspect ratio
+pub const THUMBNAIL_WIDTH : usize = 320;
+//time each thumbnail frame is shown
+const FRAME_DELAY_MS : u32 = 100;
+//file name of the zoom strip inside OUTPUT_DIR
+pub const STRIP_FILE : &str = "strip.png";
+//gap between neighbouring frames of the strip, in pixels
+const STRIP_GAP : u32 = 4;
+const STRIP_GAP_COLOR : Rgb<u8> = Rgb([255, 255, 255]);
+
+
+///    ### (PURE)
+///    Config rendering the frames of config at THUMBNAIL_WIDTH pixels wide.
+pub fn thumbnail_config(config : &RenderConfig) -> RenderConfig {
+    let aspect = config.img_height as f64 / config.img_width.max(1) as f64;
+    RenderConfig {
+        img_width : THUMBNAIL_WIDTH,
+        img_height : ((THUMBNAIL_WIDTH as f64 * aspect).round() as usize).max(1),
+        ..config.clone()
+    }
+}
+
+
+///    ### (PURE)
+///    Renders every every-th frame of frames 0..total_frames of config at
+///    thumbnail resolution, painted with palette and options. Stops at the
+///    first frame past float precision, like the full render.
+pub fn render_thumbnails(
+    config : &RenderConfig,
+    palette : &[Rgb<u8>],
+    options : &PaintOptions,
+    total_frames : u32,
+    every : u32
+) -> Result<Vec<DynamicImage>, RenderError>
+{
+    let thumb_config = thumbnail_config(config);
+    (0..total_frames)
+        .step_by(every.max(1) as usize)
+        .take_while(|frame_number| !precision_exhausted(config, *frame_number))
+        .map(|frame_number|{
+            let frame : Fractal<u32> = render_escape_grid(&thumb_config, frame_number)?;
+            let options = options.with_max_iterations(config.max_iterations_at(frame_number));
+            // ⬇
+            Ok(DynamicImage::ImageRgb8(paint_frame(
+                thumb_config.img_width as u32,
+                thumb_config.img_height as u32,
+                &frame,
+                palette,
+                &options,
+                options.palette_offset(frame_number)
+            )))
+        }).collect()
+}
+
+
+///    ### (I/O)
+///    Encodes thumbnails as a looping GIF at "{OUTPUT_DIR}/{THUMBNAIL_FILE}",
+///    returning the path.
+pub fn save_thumbnail_gif(thumbnails : Vec<DynamicImage>) -> ImageResult<String> {
+    let path = format!("{}/{}", OUTPUT_DIR, THUMBNAIL_FILE);
+    let mut encoder = GifEncoder::new(BufWriter::new(File::create(&path)?));
+    encoder.set_repeat(Repeat::Infinite)?;
+    encoder.encode_frames(thumbnails.into_iter().map(|thumbnail|{
+        Frame::from_parts(thumbnail.into_rgba8(), 0, 0, Delay::from_numer_denom_ms(FRAME_DELAY_MS, 1))
+    }))?;
+    Ok(path)
+}
+
+
+///    ### (PURE)
+///    Numbers of count frames evenly spaced from the first to the last of
+///    frames 0..total_frames, shallowest first. Fewer when total_frames
+///    is smaller than count, but always at least frame 0.
+pub fn strip_frames(total_frames : u32, count : u32) -> Vec<u32> {
+    let last = total_frames.saturating_sub(1) as f64;
+    let mut frames : Vec<u32> = (0..count.max(1)).map(|k|{
+        (k as f64 * last / count.saturating_sub(1).max(1) as f64).round() as u32
+    }).collect();
+    frames.dedup();
+    frames
+}
+
+
+///    ### (PURE)
+///    Renders the strip_frames of frames 0..total_frames of config at
+///    thumbnail resolution, painted with palette and options, and pastes
+///    them left (shallow) to right (deep) into one image separated by
+///    STRIP_GAP wide gaps. Frames are spaced over the part of the zoom
+///    before the float precision wall.
+pub fn render_strip(
+    config : &RenderConfig,
+    palette : &[Rgb<u8>],
+    options : &PaintOptions,
+    total_frames : u32,
+    count : u32
+) -> Result<ImageBuffer<Rgb<u8>, Vec<u8>>, RenderError>
+{
+    let thumb_config = thumbnail_config(config);
+    let reachable = (0..total_frames).find(|frame_number| precision_exhausted(config, *frame_number)).unwrap_or(total_frames);
+    let thumbnails = strip_frames(reachable.max(1), count).into_iter().map(|frame_number|{
+        let frame : Fractal<u32> = render_escape_grid(&thumb_config, frame_number)?;
+        let options = options.with_max_iterations(config.max_iterations_at(frame_number));
+        // ⬇
+        Ok(paint_frame(
+            thumb_config.img_width as u32,
+            thumb_config.img_height as u32,
+            &frame,
+            palette,
+            &options,
+            options.palette_offset(frame_number)
+        ))
+    }).collect::<Result<Vec<_>, RenderError>>()?;
+    // ⬇
+    Ok(thumbnails.into_iter().reduce(|strip, thumbnail|{
+        join_images(&strip, &thumbnail, Some((STRIP_GAP, STRIP_GAP_COLOR))).expect("thumbnails share thumb_config's height")
+    }).expect("strip_frames returns at least one frame"))
+}
+
+
+///    ### (I/O)
+///    Saves strip to "{OUTPUT_DIR}/{STRIP_FILE}", returning the path.
+pub fn save_strip(strip : &ImageBuffer<Rgb<u8>, Vec<u8>>) -> ImageResult<String> {
+    let path = format!("{}/{}", OUTPUT_DIR, STRIP_FILE);
+    strip.save(&path)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_spans_first_to_last_frame() {
+        assert_eq!(strip_frames(300, 5), [0, 75, 150, 224, 299]);
+        assert_eq!(strip_frames(3, 5), [0, 1, 2]);
+        assert_eq!(strip_frames(300, 1), [0]);
+        assert_eq!(strip_frames(300, 0), [0]);
+    }
+}
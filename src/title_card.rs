@@ -0,0 +1,122 @@
+//!   Module contains the title card of a finished render: one
+//!   image listing its frame count, final magnification,
+//!   iteration cap, highest escape and total time over a strip
+//!   of its palette, for archiving or sharing an animation.
+//!   @author Van Gouache
+
+use std::time::Duration;
+use image::{ImageBuffer, ImageResult, Rgb};
+use crate::annotate::{draw_text, text_size};
+use crate::painter::OUTPUT_DIR;
+
+//file name of the title card inside OUTPUT_DIR
+pub const TITLE_CARD_FILE : &str = "title_card.png";
+//width of the card, its height fits the lines and palette strip
+const CARD_WIDTH : u32 = 640;
+//size of a font pixel, margin around the card and gap between lines
+const CARD_SCALE : u32 = 4;
+const CARD_MARGIN : u32 = 32;
+const LINE_GAP : u32 = 16;
+const STRIP_HEIGHT : u32 = 48;
+const CARD_BACKGROUND : Rgb<u8> = Rgb([0, 0, 0]);
+
+
+///    Figures of a finished render shown on its title card.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderSummary {
+    ///    Number of frames rendered.
+    pub frames : u32,
+    ///    Magnification of the last frame, see RenderConfig::magnification.
+    pub final_magnification : f64,
+    ///    Iteration cap of the last frame.
+    pub max_iterations : u32,
+    ///    Largest orbit rate over every frame, None when no frame
+    ///    statistics were collected.
+    pub peak_escape : Option<u32>,
+    pub total_time : Duration,
+}
+
+
+///    ### (PURE)
+///    Text lines of the title card of summary, in the uppercase the
+///    embedded font draws.
+pub fn title_lines(summary : &RenderSummary) -> Vec<String> {
+    let mut lines = vec![
+        "BURNING SHIP".to_string(),
+        format!("FRAMES: {}", summary.frames),
+        format!("ZOOM: x{:.2e}", summary.final_magnification),
+        format!("ITERATIONS: {}", summary.max_iterations),
+    ];
+    if let Some(peak_escape) = summary.peak_escape {
+        lines.push(format!("PEAK ESCAPE: {}", peak_escape));
+    }
+    lines.push(format!("TIME: {:.1}S", summary.total_time.as_secs_f64()));
+    lines
+}
+
+
+///    ### (PURE)
+///    CARD_WIDTH wide title card with the title_lines of summary stacked
+///    from the top left and palette drawn below them as a strip of its
+///    colors in order.
+pub fn render_title_card(summary : &RenderSummary, palette : &[Rgb<u8>]) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let lines = title_lines(summary);
+    let (_, line_height) = text_size("", CARD_SCALE);
+    let text_height = lines.len() as u32 * (line_height + LINE_GAP);
+    let height = CARD_MARGIN + text_height + STRIP_HEIGHT + CARD_MARGIN;
+    let mut card = ImageBuffer::from_pixel(CARD_WIDTH, height, CARD_BACKGROUND);
+    for (index, line) in lines.iter().enumerate() {
+        draw_text(&mut card, CARD_MARGIN, CARD_MARGIN + index as u32 * (line_height + LINE_GAP), line, CARD_SCALE);
+    }
+    // ⬇
+    let strip_width = CARD_WIDTH - 2 * CARD_MARGIN;
+    let strip_top = CARD_MARGIN + text_height;
+    for x in 0..strip_width {
+        let color = palette.get(x as usize * palette.len() / strip_width as usize).copied().unwrap_or(CARD_BACKGROUND);
+        for y in strip_top..strip_top + STRIP_HEIGHT {
+            card.put_pixel(CARD_MARGIN + x, y, color);
+        }
+    }
+    card
+}
+
+
+///    ### (I/O)
+///    Composes render_title_card with saving it to
+///    "{OUTPUT_DIR}/{TITLE_CARD_FILE}", returning the path.
+pub fn save_title_card(summary : &RenderSummary, palette : &[Rgb<u8>]) -> ImageResult<String> {
+    let path = format!("{}/{}", OUTPUT_DIR, TITLE_CARD_FILE);
+    std::fs::create_dir_all(OUTPUT_DIR).map_err(image::ImageError::IoError)?;
+    render_title_card(summary, palette).save(&path)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_card_lists_the_summary_over_the_palette() {
+        let summary = RenderSummary {
+            frames : 120,
+            final_magnification : 2.5e6,
+            max_iterations : 4000,
+            peak_escape : Some(3120),
+            total_time : Duration::from_millis(81_300),
+        };
+        let lines = title_lines(&summary);
+        assert!(lines.contains(&"FRAMES: 120".to_string()));
+        assert!(lines.contains(&"ZOOM: x2.50e6".to_string()));
+        assert!(lines.contains(&"PEAK ESCAPE: 3120".to_string()));
+        assert!(lines.contains(&"TIME: 81.3S".to_string()));
+
+        let palette = [Rgb([255, 0, 0]), Rgb([0, 255, 0]), Rgb([0, 0, 255])];
+        let card = render_title_card(&summary, &palette);
+        for color in palette {
+            assert!(card.pixels().any(|pixel| *pixel == color));
+        }
+        let without_peak = render_title_card(&RenderSummary { peak_escape : None, ..summary }, &palette);
+        assert_eq!(without_peak.width(), card.width());
+        assert!(without_peak.height() < card.height());
+    }
+}
@@ -0,0 +1,82 @@
+//!   Module contains a tiny plot of a single orbit traced by
+//!   burning_ship_frac::trace_orbit, for checking the map by eye
+//!   and seeing why a pixel escapes when it does.
+//!   @author Van Gouache
+
+use image::{ImageBuffer, ImageResult, Rgb};
+use crate::burning_ship_frac::ESCAPE_RADIUS;
+use crate::painter::OUTPUT_DIR;
+
+//file name of the orbit plot inside OUTPUT_DIR
+pub const ORBIT_FILE : &str = "orbit.png";
+//side of the square plot in pixels
+const PLOT_SIZE : u32 = 256;
+//half width of the plotted square of the complex plane
+const PLOT_EXTENT : f64 = 2.5;
+const AXIS_COLOR : Rgb<u8> = Rgb([60, 60, 60]);
+const CIRCLE_COLOR : Rgb<u8> = Rgb([90, 40, 40]);
+const PATH_COLOR : Rgb<u8> = Rgb([200, 200, 200]);
+const POINT_COLOR : Rgb<u8> = Rgb([255, 80, 40]);
+
+
+///    ### (PURE)
+///    Plot pixel of the complex point (a, b), imaginary axis pointing down
+///    like the rendered frames. None outside the plot.
+fn plot_pixel((a, b) : (f64, f64)) -> Option<(u32, u32)> {
+    let scale = PLOT_SIZE as f64 / (2.0 * PLOT_EXTENT);
+    let x = ((a + PLOT_EXTENT) * scale).floor();
+    let y = ((b + PLOT_EXTENT) * scale).floor();
+    let inside = (0.0..PLOT_SIZE as f64).contains(&x) && (0.0..PLOT_SIZE as f64).contains(&y);
+    inside.then_some((x as u32, y as u32))
+}
+
+
+///    ### (PURE)
+///    Draws the segment from a to b in steps of a quarter pixel, clipped
+///    to the plot.
+fn draw_segment(buffer : &mut ImageBuffer<Rgb<u8>, Vec<u8>>, a : (f64, f64), b : (f64, f64), color : Rgb<u8>) {
+    let pixel_length = 2.0 * PLOT_EXTENT / PLOT_SIZE as f64;
+    let length = ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+    let steps = (4.0 * length / pixel_length).ceil().clamp(1.0, 4.0 * PLOT_SIZE as f64) as usize;
+    for step in 0..=steps {
+        let t = step as f64 / steps as f64;
+        if let Some((x, y)) = plot_pixel((a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1))) {
+            buffer.put_pixel(x, y, color);
+        }
+    }
+}
+
+
+///    ### (PURE)
+///    Plots orbit over the square of half width PLOT_EXTENT about the
+///    origin, with the axes, the escape circle, the path between
+///    consecutive Z and each Z as a dot.
+pub fn plot_orbit(orbit : &[(f64, f64)]) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
+    let mut buffer = ImageBuffer::new(PLOT_SIZE, PLOT_SIZE);
+    draw_segment(&mut buffer, (-PLOT_EXTENT, 0.0), (PLOT_EXTENT, 0.0), AXIS_COLOR);
+    draw_segment(&mut buffer, (0.0, -PLOT_EXTENT), (0.0, PLOT_EXTENT), AXIS_COLOR);
+    let circle_steps = 4 * PLOT_SIZE;
+    for step in 0..circle_steps {
+        let angle = std::f64::consts::TAU * step as f64 / circle_steps as f64;
+        if let Some((x, y)) = plot_pixel((ESCAPE_RADIUS * angle.cos(), ESCAPE_RADIUS * angle.sin())) {
+            buffer.put_pixel(x, y, CIRCLE_COLOR);
+        }
+    }
+    for pair in orbit.windows(2) {
+        draw_segment(&mut buffer, pair[0], pair[1], PATH_COLOR);
+    }
+    for (x, y) in orbit.iter().filter_map(|z| plot_pixel(*z)) {
+        buffer.put_pixel(x, y, POINT_COLOR);
+    }
+    buffer
+}
+
+
+///    ### (I/O)
+///    Saves plot_orbit of orbit to "{OUTPUT_DIR}/orbit.png", returning
+///    its path.
+pub fn save_orbit_plot(orbit : &[(f64, f64)]) -> ImageResult<String> {
+    let path = format!("{}/{}", OUTPUT_DIR, ORBIT_FILE);
+    plot_orbit(orbit).save(&path)?;
+    Ok(path)
+}
@@ -0,0 +1,69 @@
+//!   Module contains an adaptive burst size tuner. The first
+//!   bursts of a render are trial runs at a few burst sizes up
+//!   to the memory budget's, and the remaining bursts use the
+//!   size that saved frames fastest.
+//!   @author Van Gouache
+
+use std::time::Duration;
+
+//fractions of the memory budget's burst size tried, largest last
+const TRIAL_DIVISORS : [u32; 3] = [4, 2, 1];
+
+
+///    Burst sizes tried so far and their throughput.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BurstTuner {
+    candidates : Vec<u32>,
+    ///    (burst size, frames per second) of every recorded burst.
+    measured : Vec<(u32, f64)>,
+}
+
+impl BurstTuner {
+    ///    ### (PURE)
+    ///    Tuner trying max_burst / 4, / 2 and max_burst itself, skipping
+    ///    repeats and sizes below 1.
+    pub fn new(max_burst : u32) -> Self {
+        let mut candidates : Vec<u32> = TRIAL_DIVISORS.iter().map(|divisor| (max_burst / divisor).max(1)).collect();
+        candidates.dedup();
+        BurstTuner { candidates, measured : Vec::new() }
+    }
+
+    ///    ### (PURE)
+    ///    Size of the next burst: the next untried candidate, then the
+    ///    candidate with the best throughput.
+    pub fn next_size(&self) -> u32 {
+        if let Some(untried) = self.candidates.get(self.measured.len()) {
+            return *untried;
+        }
+        self.best().unwrap_or(self.candidates[0])
+    }
+
+    ///    ### (PURE)
+    ///    Measured burst size with the most frames per second, None before
+    ///    any burst is recorded.
+    pub fn best(&self) -> Option<u32> {
+        self.measured
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(size, _)| *size)
+    }
+
+    ///    ### (PURE)
+    ///    True once every candidate has been tried.
+    pub fn settled(&self) -> bool {
+        self.measured.len() >= self.candidates.len()
+    }
+
+    ///    ### (PURE)
+    ///    Records a burst of size that saved frames frames in elapsed.
+    ///    Bursts after the trials are not recorded, their frames are
+    ///    deeper in the zoom and slower whatever the burst size. The trials
+    ///    share that bias, so the best size tends to be an early one.
+    pub fn record(&mut self, size : u32, frames : u32, elapsed : Duration) {
+        if self.settled() {
+            return;
+        }
+        let frames_per_second = frames as f64 / elapsed.as_secs_f64().max(f64::MIN_POSITIVE);
+        self.measured.push((size, frames_per_second));
+    }
+}
@@ -0,0 +1,126 @@
+//!   Module contains the viewport overview, an SVG diagram of
+//!   the camera path of a zoom: the starting viewport with the
+//!   nested viewports of chosen frames drawn inside it, each
+//!   labelled with its magnification. Crisp at any scale.
+//!   @author Van Gouache
+
+use crate::burning_ship_frac::{frame_ranges, Range};
+use crate::config::RenderConfig;
+use crate::painter::OUTPUT_DIR;
+
+//file name of the overview inside OUTPUT_DIR
+pub const VIEWPORT_SVG_FILE : &str = "viewport.svg";
+//width of the label column right of the diagram, in starting viewport widths
+const LABEL_COLUMN : f64 = 0.6;
+//font size and margins, in hundredths of the larger side of the diagram
+const FONT_SIZE : f64 = 2.5;
+const MARGIN : f64 = 4.0;
+const START_COLOR : &str = "#222222";
+const FRAME_COLOR : &str = "#c0392b";
+
+
+///    ### (PURE)
+///    point turned by config.rotation about its zoom pivot, as the
+///    renderer turns every viewport.
+fn rotate_point(config : &RenderConfig, (x, y) : (f64, f64)) -> (f64, f64) {
+    let (px, py) = config.zoom_pivot();
+    let (sin, cos) = config.rotation.sin_cos();
+    (px + (x - px) * cos - (y - py) * sin, py + (x - px) * sin + (y - py) * cos)
+}
+
+
+///    ### (PURE)
+///    Viewport of frame_number in plane coordinates, undoing the
+///    RenderConfig::frame_origin offset of DoubleDouble frames.
+fn plane_ranges(config : &RenderConfig, frame_number : u32) -> (Range, Range) {
+    let (x_range, y_range) = frame_ranges(config, frame_number);
+    let (ox, oy) = config.frame_origin();
+    ((x_range.0 + ox, x_range.1 + ox), (y_range.0 + oy, y_range.1 + oy))
+}
+
+
+///    ### (PURE)
+///    SVG rect of the viewport x_range by y_range, turned with the
+///    diagram. Strokes keep their width however small the rect is.
+fn svg_rect(x_range : Range, y_range : Range, color : &str, stroke_width : f64) -> String {
+    format!(
+        "    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" vector-effect=\"non-scaling-stroke\"/>\n",
+        x_range.0, y_range.0, x_range.1 - x_range.0, y_range.1 - y_range.0, color, stroke_width
+    )
+}
+
+
+///    ### (PURE)
+///    SVG overview of the camera path of config: the starting viewport
+///    and the viewport of each of frames nested inside it, with a label
+///    "frame N xM" per frame in a column to the right, joined to the
+///    corner of its viewport by a leader line, M the magnification as in
+///    the frame stamps. The y axis points down as in the rendered frames,
+///    and the viewports are turned by config.rotation.
+pub fn viewport_svg(config : &RenderConfig, frames : &[u32]) -> String {
+    let (x_range, y_range) = (config.starting_x_range, config.starting_y_range);
+    let corners = [(x_range.0, y_range.0), (x_range.1, y_range.0), (x_range.0, y_range.1), (x_range.1, y_range.1)]
+        .map(|corner| rotate_point(config, corner));
+    let min_x = corners.iter().map(|corner| corner.0).fold(f64::INFINITY, f64::min);
+    let max_x = corners.iter().map(|corner| corner.0).fold(f64::NEG_INFINITY, f64::max);
+    let min_y = corners.iter().map(|corner| corner.1).fold(f64::INFINITY, f64::min);
+    let max_y = corners.iter().map(|corner| corner.1).fold(f64::NEG_INFINITY, f64::max);
+    let unit = (max_x - min_x).max(max_y - min_y) / 100.0;
+    let (margin, font_size) = (MARGIN * unit, FONT_SIZE * unit);
+    let label_x = max_x + margin;
+    let (view_width, view_height) = (max_x - min_x + LABEL_COLUMN * (max_x - min_x) + 2.0 * margin, max_y - min_y + 2.0 * margin);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" font-family=\"monospace\" font-size=\"{}\">\n",
+        min_x - margin, min_y - margin, view_width, view_height, font_size
+    );
+    let (pivot_x, pivot_y) = config.zoom_pivot();
+    svg += &format!("  <g transform=\"rotate({} {} {})\">\n", config.rotation.to_degrees(), pivot_x, pivot_y);
+    svg += &svg_rect(x_range, y_range, START_COLOR, 2.0);
+    let viewports : Vec<(u32, (Range, Range))> = frames.iter().map(|frame_number| (*frame_number, plane_ranges(config, *frame_number))).collect();
+    for (_, (x_range, y_range)) in &viewports {
+        svg += &svg_rect(*x_range, *y_range, FRAME_COLOR, 1.0);
+    }
+    svg += "  </g>\n";
+    for (k, (frame_number, (x_range, y_range))) in viewports.iter().enumerate() {
+        let label_y = min_y + (k + 1) as f64 * (max_y - min_y) / (viewports.len() + 1) as f64;
+        let (corner_x, corner_y) = rotate_point(config, (x_range.1, y_range.0));
+        // ⬇
+        svg += &format!(
+            "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"0.5\" vector-effect=\"non-scaling-stroke\"/>\n",
+            corner_x, corner_y, label_x, label_y, FRAME_COLOR
+        );
+        svg += &format!(
+            "  <text x=\"{}\" y=\"{}\" dominant-baseline=\"middle\">frame {} x{:.2e}</text>\n",
+            label_x + unit, label_y, frame_number, config.magnification(*frame_number)
+        );
+    }
+    svg += "</svg>\n";
+    svg
+}
+
+
+///    ### (I/O)
+///    Composes viewport_svg with writing it to
+///    "{OUTPUT_DIR}/{VIEWPORT_SVG_FILE}", returning the path.
+pub fn save_viewport_svg(config : &RenderConfig, frames : &[u32]) -> std::io::Result<String> {
+    let path = format!("{}/{}", OUTPUT_DIR, VIEWPORT_SVG_FILE);
+    std::fs::create_dir_all(OUTPUT_DIR)?;
+    std::fs::write(&path, viewport_svg(config, frames))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewport_svg_nests_a_labelled_rect_per_frame() {
+        let config = RenderConfig { zoom_center : Some((-1.75, -0.03)), ..RenderConfig::default() };
+        let svg = viewport_svg(&config, &[0, 10, 20]);
+        assert_eq!(svg.matches("<rect").count(), 4);
+        assert_eq!(svg.matches("<line").count(), 3);
+        assert!(svg.contains(&format!("frame 20 x{:.2e}", config.magnification(20))));
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,52 @@
+//!   Integration tests pinning down that a render is a pure
+//!   function of its config and palette seed, so frames can be
+//!   compared byte for byte across runs.
+//!   @author Van Gouache
+
+use std::io::Cursor;
+use image::ImageOutputFormat;
+use burning_ship_fractal::painter::{generate_seeded_palette, paint_frame, PaintOptions};
+use burning_ship_fractal::{render_escape_grid, Fractal, RenderConfig};
+
+fn render_png(seed : u64) -> Vec<u8> {
+    let config = RenderConfig {
+        img_width : 96,
+        img_height : 54,
+        ..RenderConfig::default()
+    };
+    let frame : Fractal<u8> = render_escape_grid(&config, 12).unwrap();
+    let palette = generate_seeded_palette(config.max_iterations, seed);
+    let options = PaintOptions { max_iterations : config.max_iterations, ..PaintOptions::default() };
+    let buffer = paint_frame(config.img_width as u32, config.img_height as u32, &frame, &palette, &options, 0);
+    let mut png = Cursor::new(Vec::new());
+    buffer.write_to(&mut png, ImageOutputFormat::Png).unwrap();
+    png.into_inner()
+}
+
+#[test]
+fn same_seed_renders_identical_png() {
+    assert_eq!(render_png(7), render_png(7));
+}
+
+#[test]
+fn different_seeds_render_different_pngs() {
+    assert_ne!(render_png(7), render_png(8));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn single_thread_pool_renders_the_same_grid() {
+    let config = RenderConfig {
+        img_width : 96,
+        img_height : 54,
+        ..RenderConfig::default()
+    };
+    let single_thread = burning_ship_fractal::parallel::pool_builder(None, true).unwrap().build().unwrap();
+    let caller = std::thread::current().id();
+    let (worker, sequential) = single_thread.install(||{
+        (std::thread::current().id(), render_escape_grid::<u8>(&config, 12).unwrap())
+    });
+    assert_eq!(worker, caller);
+    let parallel : Fractal<u8> = render_escape_grid(&config, 12).unwrap();
+    assert_eq!(sequential, parallel);
+}
@@ -0,0 +1,53 @@
+//!   Integration test running the binary twice, pinning down
+//!   that --from repeats a recorded render pixel for pixel,
+//!   random palette included.
+//!   @author Van Gouache
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn scratch_dir(name : &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burning_ship_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run_in(dir : &Path, args : &[&str]) {
+    let status = Command::new(env!("CARGO_BIN_EXE_burning_ship_fractal"))
+        .args(args)
+        .arg("--quiet")
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+///    Renders two frames with palette_args, repeats them with --from
+///    in another dir and compares the frames pixel for pixel.
+fn assert_from_reproduces(name : &str, palette_args : &[&str]) {
+    let (first, second) = (scratch_dir(&format!("{}_recorded", name)), scratch_dir(&format!("{}_reproduced", name)));
+    run_in(&first, &[&["--print", "0.8x0.46in@100dpi", "--frames", "2"], palette_args].concat());
+    let sidecar = first.join("frames/render.json");
+    run_in(&second, &["--from", sidecar.to_str().unwrap(), "--frames", "2"]);
+    for frame in ["0000000000.png", "0000000001.png"] {
+        let recorded = image::open(first.join("frames").join(frame)).unwrap().to_rgb8();
+        let reproduced = image::open(second.join("frames").join(frame)).unwrap().to_rgb8();
+        assert_eq!(recorded, reproduced, "{} differs", frame);
+    }
+    let rewritten = std::fs::read_to_string(second.join("frames/render.json")).unwrap();
+    assert_eq!(rewritten, std::fs::read_to_string(&sidecar).unwrap());
+    for dir in [first, second] {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
+
+#[test]
+fn from_reproduces_the_recorded_frames() {
+    assert_from_reproduces("seeded", &[]);
+}
+
+#[test]
+fn from_reproduces_blended_palettes() {
+    assert_from_reproduces("blended", &["--blend-seeds", "7,42,1999", "--palette-blend", "per-band"]);
+}